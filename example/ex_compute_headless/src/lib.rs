@@ -1,17 +1,46 @@
 use wgpu_engine::third_party::*;
 use wgpu_engine::*;
 
+#[allow(unused_imports)]
 use image::{DynamicImage, GenericImage, GenericImageView, ImageBuffer, Rgb};
 
 pub fn run() -> anyhow::Result<()> {
-    let state = pollster::block_on(gfx::GfxState::new(None))?;
-
     let storage_size = (1024u32, 1024u32);
+    let floats = pollster::block_on(async {
+        let options = gfx::GfxStateOptions::new()
+            .with_power_preference(wgpu::PowerPreference::HighPerformance);
+        let state = gfx::GfxState::with_options(None, options).await?;
+        generate_noise(&state, storage_size).await
+    })?;
+
+    let mut floats_rgb = Vec::with_capacity(floats.len() * 3);
+    for f in floats {
+        floats_rgb.push(f);
+        floats_rgb.push(f);
+        floats_rgb.push(f);
+    }
+
+    // Create image from pixel data and save to disk
+    let img: ImageBuffer<Rgb<f32>, _> =
+        ImageBuffer::from_raw(storage_size.0, storage_size.1, floats_rgb).unwrap();
+    let img = DynamicImage::from(img);
+    img.into_rgb16().save("output.png")?;
+
+    Ok(())
+}
 
-    let storage = make_storage_buffer(&state, storage_size);
+/// Runs the noise compute shader and reads the result back
+///
+/// This only uses async readback, so the same job also runs on the web
+/// where blocking on the GPU is not possible.
+pub async fn generate_noise(
+    state: &gfx::GfxState,
+    storage_size: (u32, u32),
+) -> anyhow::Result<Vec<f32>> {
+    let storage = make_storage_buffer(state, storage_size);
 
     let params = make_params_buffer(
-        &state,
+        state,
         NoiseParams {
             width: storage_size.0,
             height: storage_size.1,
@@ -24,66 +53,23 @@ pub fn run() -> anyhow::Result<()> {
         },
     );
 
-    let (group_layouts, groups) = make_compute_bind_groups(&state, &storage, &params);
+    let (group_layouts, groups) = make_compute_bind_groups(state, &storage, &params);
     let group_layouts: Vec<_> = group_layouts.iter().collect();
 
-    let compute = make_compute_pipeline(&state, &group_layouts);
-
-    let staging = make_staging_buffer(&state, storage_size);
-
-    let mut encoder = state
-        .device
-        .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-            label: Some("Compute Commands"),
-        });
-
-    {
-        let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
-            label: Some("Compute Pass"),
-            timestamp_writes: None,
-        });
-
-        cpass.set_pipeline(&compute);
-        for (i, bg) in groups.iter().enumerate() {
-            cpass.set_bind_group(i as u32, bg, &[]);
-        }
-
-        let workgroups_x = storage_size.0.div_ceil(8);
-        let workgroups_y = storage_size.1.div_ceil(8);
-        cpass.dispatch_workgroups(workgroups_x, workgroups_y, 1);
-    }
-    encoder.copy_buffer_to_buffer(&storage, 0, &staging, 0, storage.size());
+    let compute = make_compute_pipeline(state, &group_layouts);
 
-    state.queue.submit(Some(encoder.finish()));
-    state.device.poll(wgpu::PollType::Wait)?;
-
-    let buffer_slice = staging.slice(..);
-    buffer_slice.map_async(wgpu::MapMode::Read, |_| {});
-
-    state.device.poll(wgpu::PollType::Wait)?;
+    state.immediate_submit(Some("Compute Commands"), |encoder| {
+        let groups: Vec<_> = groups.iter().collect();
+        let workgroups = (
+            gfx::workgroups_for(storage_size.0, 8),
+            gfx::workgroups_for(storage_size.1, 8),
+            1,
+        );
+        gfx::dispatch(encoder, &compute, &groups, workgroups);
+    });
 
     // Copy pixel data from GPU memory to CPU memory
-    let data = buffer_slice.get_mapped_range();
-    let bytes = data.to_vec();
-    drop(data);
-
-    // Unmap the buffer to release GPU resources
-    staging.unmap();
-    let floats: &[f32] = bytemuck::cast_slice(&bytes);
-    let mut floats_rgb = Vec::with_capacity(floats.len() * 3);
-    for f in floats {
-        floats_rgb.push(*f);
-        floats_rgb.push(*f);
-        floats_rgb.push(*f);
-    }
-
-    // Create image from pixel data and save to disk
-    let img: ImageBuffer<Rgb<f32>, _> =
-        ImageBuffer::from_raw(storage_size.0, storage_size.1, floats_rgb).unwrap();
-    let img = DynamicImage::from(img);
-    img.into_rgb16().save("output.png")?;
-
-    Ok(())
+    storage.read_back_async(&state.device, &state.queue).await
 }
 
 #[derive(encase::ShaderType)]
@@ -113,25 +99,17 @@ fn make_params_buffer(state: &gfx::GfxState, params: NoiseParams) -> wgpu::Buffe
             usage: wgpu::BufferUsages::UNIFORM,
         })
 }
-fn make_staging_buffer(state: &gfx::GfxState, storage_size: (u32, u32)) -> wgpu::Buffer {
-    let count = storage_size.0 * storage_size.1;
-    let buffer_size = (count * (std::mem::size_of::<f32>() as u32)) as u64;
-    state.device.create_buffer(&wgpu::BufferDescriptor {
-        label: Some("Staging Buffer"),
-        size: buffer_size,
-        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
-        mapped_at_creation: false,
-    })
-}
-fn make_storage_buffer(state: &gfx::GfxState, storage_size: (u32, u32)) -> wgpu::Buffer {
-    let count = storage_size.0 * storage_size.1;
-    let size = (count * (std::mem::size_of::<f32>() as u32)) as u64;
-    state.device.create_buffer(&wgpu::BufferDescriptor {
-        label: Some("Compute Storage"),
-        size,
-        usage: wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::STORAGE,
-        mapped_at_creation: false,
-    })
+fn make_storage_buffer(
+    state: &gfx::GfxState,
+    storage_size: (u32, u32),
+) -> gfx::StorageBuffer<Vec<f32>> {
+    let count = (storage_size.0 * storage_size.1) as usize;
+    gfx::StorageBuffer::new(
+        &state.device,
+        &vec![0.0; count],
+        wgpu::BufferUsages::empty(),
+        Some("Compute Storage"),
+    )
 }
 fn make_compute_bind_groups(
     state: &gfx::GfxState,
@@ -197,22 +175,7 @@ fn make_compute_pipeline(
             source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(code)),
         });
 
-    let layout = state
-        .device
-        .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("perlin.wgsl Layout"),
-            bind_group_layouts,
-            push_constant_ranges: &[],
-        });
-
-    state
-        .device
-        .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
-            label: Some("perlin.wgsl Pipeline"),
-            layout: Some(&layout),
-            entry_point: Some("cs_main"),
-            module: &perlin_module,
-            compilation_options: wgpu::PipelineCompilationOptions::default(),
-            cache: None,
-        })
+    gfx::ComputePipelineBuilder::new(&state.device, &perlin_module, bind_group_layouts)
+        .label("perlin.wgsl Pipeline")
+        .build()
 }
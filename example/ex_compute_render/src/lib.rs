@@ -0,0 +1,178 @@
+//! Compute-to-render example implementation.
+//!
+//! This module demonstrates running compute and rendering in the same frame by:
+//! - Creating a `gfx::StorageTexture` usable as both storage and sampled texture
+//! - Dispatching a compute shader writing into it from `AppClient::encode`
+//! - Drawing the result over the whole window with a `gfx::Blitter`
+
+use wgpu_engine::observer::{FnSubscriber, Subscription};
+use wgpu_engine::third_party::*;
+use wgpu_engine::*;
+
+pub use parking_lot::Mutex;
+
+/// Side length of the generated texture in texels
+const TEXTURE_SIZE: u32 = 512;
+/// `@workgroup_size` of `cs_main` in plasma.wgsl
+const WORKGROUP_SIZE: (u32, u32) = (8, 8);
+
+/// GPU resources created once the graphics state is available
+struct Resources {
+    texture: gfx::StorageTexture,
+    params: gfx::UniformBuffer<glam::Vec4>,
+    compute: wgpu::ComputePipeline,
+    compute_group: wgpu::BindGroup,
+    blitter: gfx::Blitter,
+    blit_group: wgpu::BindGroup,
+}
+
+/// Application client animating a texture with a compute shader
+struct ComputeRenderClient {
+    resources: Mutex<Option<Resources>>,
+}
+impl std::fmt::Debug for ComputeRenderClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ComputeRenderClient")
+            .finish_non_exhaustive()
+    }
+}
+impl ComputeRenderClient {
+    /// Creates a new ComputeRenderClient instance wrapped in Arc for shared ownership.
+    #[allow(clippy::new_ret_no_self)]
+    fn new() -> SharedAppClient {
+        std::sync::Arc::new(Self {
+            resources: Mutex::new(None),
+        })
+    }
+
+    /// Creates the storage texture and the compute and blit pipelines
+    fn create_resources(state: &gfx::GfxState) -> Resources {
+        let device = &state.device;
+        // STORAGE_BINDING | TEXTURE_BINDING lets the compute pass write the
+        // texture and the render pass sample it afterwards
+        let texture = gfx::StorageTexture::new(
+            device,
+            wgpu::TextureFormat::Rgba8Unorm,
+            (TEXTURE_SIZE, TEXTURE_SIZE),
+            Some("Plasma Texture"),
+        );
+        let params = gfx::UniformBuffer::new(
+            device,
+            &glam::Vec4::ZERO,
+            wgpu::BufferUsages::COPY_DST,
+            Some("Plasma Params"),
+        );
+
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Plasma Layout"),
+            entries: &[
+                texture.storage_layout_entry(0),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let compute_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Plasma Group"),
+            layout: &layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(texture.view()),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: params.as_entire_binding(),
+                },
+            ],
+        });
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("plasma.wgsl"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("plasma.wgsl").into()),
+        });
+        let compute = gfx::ComputePipelineBuilder::new(device, &module, &[&layout])
+            .label("plasma.wgsl Pipeline")
+            .build();
+
+        let blitter = gfx::Blitter::new(device, state.config.as_ref().unwrap().format);
+        let blit_group = blitter.bind_group(device, texture.view());
+        Resources {
+            texture,
+            params,
+            compute,
+            compute_group,
+            blitter,
+            blit_group,
+        }
+    }
+}
+
+impl AppClient for ComputeRenderClient {
+    /// Creates the GPU resources and subscribes to keyboard events
+    fn init(&self) {
+        EVENTS.keyboard().subscribe(
+            FnSubscriber::new(|data: &KeyboardData| {
+                if data.is_pressed && data.key_code == KeyCode::Escape {
+                    app().exit();
+                }
+                Subscription::Keep
+            })
+            .boxed(),
+        );
+
+        let app = app();
+        let state = app.state();
+        *self.resources.lock() = Some(Self::create_resources(state.as_ref().unwrap()));
+    }
+
+    /// Uploads the elapsed time used to animate the texture
+    ///
+    /// This happens here rather than in `encode`, which runs while the
+    /// graphics state is locked.
+    fn update(&self, _delta_time: f32) {
+        let resources = self.resources.lock();
+        let Some(resources) = resources.as_ref() else {
+            return;
+        };
+        let app = app();
+        let state = app.state();
+        let state = state.as_ref().unwrap();
+        let params = glam::vec4(TIME.running_time(), 0.0, 0.0, 0.0);
+        resources.params.write(&state.queue, 0, &params);
+    }
+
+    /// Records the compute pass on the frame's encoder, before the main pass
+    fn encode(&self, encoder: &mut wgpu::CommandEncoder) {
+        let resources = self.resources.lock();
+        let Some(resources) = resources.as_ref() else {
+            return;
+        };
+        let (x, y) = resources.texture.workgroups(WORKGROUP_SIZE);
+        gfx::dispatch(
+            encoder,
+            &resources.compute,
+            &[&resources.compute_group],
+            (x, y, 1),
+        );
+    }
+
+    /// Draws the texture written by the compute pass over the whole window
+    fn render(&self, rpass: &mut wgpu::RenderPass<'_>) {
+        let resources = self.resources.lock();
+        let Some(resources) = resources.as_ref() else {
+            return;
+        };
+        resources.blitter.blit(rpass, &resources.blit_group);
+    }
+}
+
+// Define the application entry point with our ComputeRenderClient
+define_entry_point!(ComputeRenderClient::new());
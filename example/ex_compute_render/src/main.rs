@@ -0,0 +1,15 @@
+//! Compute-to-render example entry point.
+//!
+//! This example demonstrates:
+//! - Writing a storage texture from a compute shader every frame
+//! - Sampling that texture in the main render pass of the same frame
+//! - Sharing one command encoder through the `AppClient::encode` hook
+
+use wgpu_engine::third_party::anyhow;
+
+/// Main entry point for the compute-to-render example.
+///
+/// Runs the application with the ComputeRenderClient.
+fn main() -> anyhow::Result<()> {
+    ex_compute_render::run()
+}
@@ -0,0 +1,19 @@
+@group(0) @binding(0)
+var output: texture_storage_2d<rgba8unorm, write>;
+
+// x: elapsed time in seconds, yzw: unused
+@group(0) @binding(1)
+var<uniform> params: vec4f;
+
+@compute @workgroup_size(8, 8)
+fn cs_main(@builtin(global_invocation_id) id: vec3u) {
+    let size = textureDimensions(output);
+    if id.x >= size.x || id.y >= size.y {
+        return;
+    }
+    let uv = vec2f(id.xy) / vec2f(size);
+    let t = params.x;
+    let v = sin(uv.x * 10.0 + t) + sin(uv.y * 10.0 + t * 1.3) + sin((uv.x + uv.y) * 10.0 + t * 0.7);
+    let color = vec3f(sin(v), sin(v + 2.094), sin(v + 4.188)) * 0.5 + 0.5;
+    textureStore(output, vec2i(id.xy), vec4f(color, 1.0));
+}
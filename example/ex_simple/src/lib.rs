@@ -21,7 +21,7 @@ pub use parking_lot::Mutex;
 /// - Escape key exits the application
 struct SimpleClient {
     /// Render pipeline for drawing the triangle (protected by mutex for thread safety)
-    pipeline: Mutex<Option<wgpu::RenderPipeline>>,
+    pipeline: Mutex<Option<gfx::RenderPipeline>>,
 
     mesh_index: Mutex<u8>,
     meshes: Mutex<Vec<gfx::Mesh<gfx::Vertex3D, u16>>>,
@@ -53,12 +53,17 @@ impl SimpleClient {
 }
 
 impl AppClient for SimpleClient {
+    /// Renders with a depth buffer and no multisampling
+    fn render_config(&self) -> gfx::RenderConfig {
+        gfx::RenderConfig::new().with_depth(gfx::Texture2D::DEPTH_FORMAT)
+    }
+
     /// Initializes the client by setting up event subscriptions and creating the render pipeline.
     ///
     /// This method:
     /// 1. Subscribes to mouse movement and keyboard events
     /// 2. Creates a shader module from the embedded vertex_color.wgsl source
-    /// 3. Sets up a basic render pipeline with no vertex buffers
+    /// 3. Sets up a render pipeline with `gfx::RenderPipelineBuilder`
     /// 4. Stores the pipeline for use during rendering
     /// 5. Sets up the vertex buffer and stores it for use during rendering
     fn init(&self) {
@@ -106,6 +111,7 @@ impl AppClient for SimpleClient {
         let mut bind_groups = self.bind_groups.lock();
         let mut bind_group_layouts = self.bind_group_layouts.lock();
 
+        gfx::check_layout::<GpuParams>();
         *self.params.lock() = Some(gfx::UniformBuffer::new(
             &state.device,
             &GpuParams {
@@ -115,29 +121,13 @@ impl AppClient for SimpleClient {
             None,
         ));
 
-        bind_group_layouts.push(state.device.create_bind_group_layout(
-            &wgpu::BindGroupLayoutDescriptor {
-                label: Some("Group Layout 0"),
-                entries: &[wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                }],
-            },
-        ));
-        bind_groups.push(state.device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Group 0"),
-            layout: bind_group_layouts.last().unwrap(),
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: self.params.lock().as_ref().unwrap().as_entire_binding(),
-            }],
-        }));
+        let params = self.params.lock();
+        let (layout, group) = gfx::BindGroupBuilder::new()
+            .label("Group 0")
+            .uniform(params.as_ref().unwrap(), wgpu::ShaderStages::FRAGMENT)
+            .build(&state.device);
+        bind_group_layouts.push(layout);
+        bind_groups.push(group);
 
         let ref_bind_group_layouts: Vec<_> = bind_group_layouts.iter().collect();
 
@@ -150,73 +140,26 @@ impl AppClient for SimpleClient {
             vertex_info.as_ref(),
             None,
             Some("vertex_color.wgsl"),
-        );
-        // Create pipeline layout (no bind groups or push constants needed for this simple example)
-        let layout = state
-            .device
-            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                label: Some("vertex_color.wgsl Layout"),
-                bind_group_layouts: &ref_bind_group_layouts,
-                push_constant_ranges: &[],
-            });
-
-        // Create render pipeline with vertex and fragment shaders
-        let pipeline = state
-            .device
-            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                label: Some("vertex_color.wgsl Pipeline"),
-                layout: Some(&layout),
-                vertex: wgpu::VertexState {
-                    module: &module,
-                    entry_point: Some("vs_main"),
-                    compilation_options: wgpu::PipelineCompilationOptions::default(),
-                    buffers: &[vertex_info.describe()], // One vertex buffer (Vertex3D)
-                },
-                primitive: wgpu::PrimitiveState {
-                    topology: wgpu::PrimitiveTopology::TriangleList,
-                    strip_index_format: None,
-                    front_face: wgpu::FrontFace::Ccw, // Counter-clockwise front faces
-                    cull_mode: Some(wgpu::Face::Back), // Cull back-facing triangles
-                    unclipped_depth: false,
-                    polygon_mode: wgpu::PolygonMode::Fill,
-                    conservative: false,
-                },
-                depth_stencil: Some(wgpu::DepthStencilState {
-                    format: gfx::Texture2D::DEPTH_FORMAT,
-                    depth_write_enabled: true,
-                    depth_compare: wgpu::CompareFunction::Less,
-                    stencil: wgpu::StencilState::default(),
-                    bias: wgpu::DepthBiasState::default(),
-                }), // No depth testing for this simple example
-                multisample: wgpu::MultisampleState {
-                    count: 1, // No multisampling
-                    mask: !0,
-                    alpha_to_coverage_enabled: false,
-                },
-                fragment: Some(wgpu::FragmentState {
-                    module: &module,
-                    entry_point: Some("fs_main"),
-                    compilation_options: wgpu::PipelineCompilationOptions::default(),
-                    targets: &[Some(wgpu::ColorTargetState {
-                        format: state.config.clone().unwrap().format, // Match surface format
-                        blend: Some(wgpu::BlendState::REPLACE),       // Replace existing colors
-                        write_mask: wgpu::ColorWrites::ALL,           // Write all color channels
-                    })],
-                }),
-                multiview: None,
-                cache: None,
-            });
+        )
+        .unwrap();
+        // Create render pipeline matching the attachments declared in `render_config`
+        let pipeline = gfx::RenderPipelineBuilder::new(
+            &state.device,
+            &module,
+            vertex_info.as_ref(),
+            state.config.clone().unwrap().format, // Match surface format
+            &ref_bind_group_layouts,
+        )
+        .label("vertex_color.wgsl Pipeline")
+        .render_config(&state.render_config())
+        .build();
         // Store the pipeline for use during rendering
         *self.pipeline.lock() = Some(pipeline);
 
         // The indices of the quad to render
-        const QUAD_INDICES: &[u16] = &[
-            0, 1, 2, // Triangle 0
-            2, 3, 0, // Triangle 1
-        ];
-        let quad_indices = Some(gfx::IndexBuffer::new_filled(
+        let quad_indices = Some(gfx::IndexBuffer::<u16>::new_quads(
             &state.device,
-            QUAD_INDICES,
+            1,
             wgpu::BufferUsages::empty(),
             Some("Quad Indices"),
         ));
@@ -297,14 +240,16 @@ impl AppClient for SimpleClient {
         meshes.push(tri);
     }
 
-    /// Update function called each frame (currently unused).
+    /// Update function called each frame, animating the tint uniform.
+    ///
+    /// The uniform is only uploaded when the tint actually changed.
     fn update(&self, _delta_time: f32) {
         let app = app();
         let mut state = app.state();
         let state = state.as_mut().unwrap();
 
         let gray = TIME.running_time().sin() * 0.5 + 0.5;
-        self.params.lock().as_ref().unwrap().write(
+        self.params.lock().as_ref().unwrap().write_if_changed(
             &state.queue,
             0,
             &GpuParams {
@@ -329,10 +274,7 @@ impl AppClient for SimpleClient {
             &meshes[..]
         };
 
-        rpass.set_pipeline(pipeline);
-        for (i, bind_group) in self.bind_groups.lock().iter().enumerate() {
-            rpass.set_bind_group(i as u32, bind_group, &[]);
-        }
+        pipeline.bind_all(rpass, &*self.bind_groups.lock());
         for mesh in meshes {
             mesh.bind(rpass);
             mesh.draw(0..1, rpass);
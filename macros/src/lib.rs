@@ -0,0 +1,123 @@
+//! Procedural macros of `wgpu-engine`
+//!
+//! Use them through the `wgpu_engine` macros that wrap them, e.g. the
+//! directory form of `shader_lib!`.
+
+use std::path::{Path, PathBuf};
+
+use proc_macro::{Delimiter, Group, Literal, Span, TokenStream, TokenTree};
+
+/// Expands to a `&[(&str, &str)]` of every `.wgsl` file below a directory
+///
+/// The directory is relative to the file the macro is used in. Each file is
+/// paired with its path relative to the directory without the extension,
+/// using `/` as separator, and embedded with `include_str!`.
+#[proc_macro]
+pub fn shader_files(input: TokenStream) -> TokenStream {
+    let mut tokens = input.into_iter();
+    let token = match (tokens.next(), tokens.next()) {
+        // `macro_rules!` fragments arrive wrapped in an invisible group
+        (Some(TokenTree::Group(group)), None) if group.delimiter() == Delimiter::None => {
+            let mut tokens = group.stream().into_iter();
+            tokens.next().filter(|_| tokens.next().is_none())
+        }
+        (token, None) => token,
+        _ => None,
+    };
+    let Some(TokenTree::Literal(lit)) = token else {
+        return compile_error("Expected a directory path string", Span::call_site());
+    };
+    let Some(dir) = parse_str_literal(&lit.to_string()) else {
+        return compile_error("Expected a directory path string", lit.span());
+    };
+    // `local_file` may be relative to the working directory of rustc, while
+    // `include_str!` resolves relative paths against the calling file
+    let file = lit
+        .span()
+        .local_file()
+        .and_then(|f| std::path::absolute(f).ok());
+    let Some(base) = file.as_deref().and_then(Path::parent) else {
+        return compile_error("Unable to resolve the path of the calling file", lit.span());
+    };
+    let root = base.join(&dir);
+    let mut files = Vec::new();
+    if let Err(e) = visit(&root, "", &mut files) {
+        let message = format!("Unable to read shader directory {}: {e}", root.display());
+        return compile_error(&message, lit.span());
+    }
+    files.sort();
+
+    let entries: Vec<_> = files
+        .iter()
+        .map(|(key, path)| {
+            format!(
+                "({}, ::core::include_str!({}))",
+                Literal::string(key),
+                Literal::string(&path.to_string_lossy())
+            )
+        })
+        .collect();
+    format!(
+        "{{ const FILES: &[(&str, &str)] = &[{}]; FILES }}",
+        entries.join(", ")
+    )
+    .parse()
+    .unwrap()
+}
+
+/// Collects the `.wgsl` files below `dir` with their keys, like `ShaderLib::from_dir`
+fn visit(dir: &Path, prefix: &str, files: &mut Vec<(String, PathBuf)>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        if path.is_dir() {
+            visit(&path, &format!("{prefix}{name}/"), files)?;
+        } else if let Some(stem) = name.strip_suffix(".wgsl") {
+            files.push((format!("{prefix}{stem}"), path));
+        }
+    }
+    Ok(())
+}
+
+/// Returns the value of a plain string literal, unescaping `\\` and `\"`
+fn parse_str_literal(lit: &str) -> Option<String> {
+    let inner = lit.strip_prefix('"')?.strip_suffix('"')?;
+    let mut value = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next()? {
+                c @ ('\\' | '"') => value.push(c),
+                _ => return None,
+            },
+            c => value.push(c),
+        }
+    }
+    Some(value)
+}
+
+/// Returns a `compile_error!` invocation reporting `message` at `span`
+fn compile_error(message: &str, span: Span) -> TokenStream {
+    let tokens: TokenStream = format!("::core::compile_error!({})", Literal::string(message))
+        .parse()
+        .unwrap();
+    tokens
+        .into_iter()
+        .map(|token| respan(token, span))
+        .collect()
+}
+
+/// Sets the span of `token` and all tokens nested in it
+fn respan(token: TokenTree, span: Span) -> TokenTree {
+    let mut token = match token {
+        TokenTree::Group(group) => {
+            let stream = group.stream().into_iter().map(|t| respan(t, span));
+            TokenTree::Group(Group::new(group.delimiter(), stream.collect()))
+        }
+        token => token,
+    };
+    token.set_span(span);
+    token
+}
@@ -4,10 +4,75 @@ use std::sync::Arc;
 
 use downcast_rs::{DowncastSync, impl_downcast};
 
+use crate::gfx::{GfxStateOptions, RenderConfig, RenderGraph};
+
 pub struct AppClientInfo {
+    /// Title of the main window
     pub window_title: String,
+    /// Initial inner size of the main window in physical pixels
     pub window_size: glam::UVec2,
+    /// CSS selector of the canvas to render into on the web
     pub wasm_canvas_selector: String,
+    /// Smallest inner size the main window can be resized to, in physical pixels
+    pub min_window_size: Option<glam::UVec2>,
+    /// Largest inner size the main window can be resized to, in physical pixels
+    pub max_window_size: Option<glam::UVec2>,
+    /// Whether the user can resize the main window (`true` by default, native only)
+    pub resizable: bool,
+    /// Icon of the main window (native only)
+    pub window_icon: Option<WindowIcon>,
+    /// Initial background color of the main pass, opaque black by default
+    pub clear_color: glam::Vec4,
+    /// Runs `AppClient::update` on a dedicated thread at this many updates
+    /// per second instead of once per frame (native only, `None` by default)
+    ///
+    /// Rates that are not positive and finite are rejected with an error
+    /// when the app is created, and `update` runs once per frame instead.
+    pub update_thread_rate: Option<f32>,
+    /// Time step of `AppClient::fixed_update` in seconds, 1/60 by default
+    ///
+    /// Time steps that are not positive and finite are replaced with the
+    /// default with an error when the app is created.
+    pub fixed_timestep: f32,
+    /// Maximum number of `fixed_update` calls per frame (8 by default)
+    ///
+    /// Time that cannot be caught up within this limit, e.g. after a long
+    /// stall, is dropped instead of slowing down every following frame.
+    pub max_fixed_steps: u32,
+    /// Exits the application after this many frames have been rendered
+    pub exit_after_frames: Option<u64>,
+    /// Saves every rendered frame as `frame_NNNNN.png` into this directory (native only)
+    pub capture_frames_to: Option<std::path::PathBuf>,
+    /// Additional formats views of the surface texture may use, e.g. the
+    /// non-sRGB variant of the surface format for a linear post-processing view
+    ///
+    /// Formats that only differ from the surface format in their sRGB-ness
+    /// are supported; any others are ignored with a warning.
+    pub surface_view_formats: Vec<wgpu::TextureFormat>,
+    /// Suppresses key and mouse button presses while the window is not
+    /// focused (`true` by default)
+    ///
+    /// Releases are still published so no key stays held. Mouse movement and
+    /// wheel events are not affected.
+    pub ignore_unfocused_input: bool,
+    /// Preferred presentation mode, `AutoVsync` by default
+    ///
+    /// Falls back to the first mode the surface supports with a warning.
+    /// Can be changed at runtime with `GfxState::set_present_mode`.
+    pub present_mode: wgpu::PresentMode,
+    /// Bytes of push constants pipelines may use, 0 (disabled) by default
+    ///
+    /// A non-zero size enables `wgpu::Features::PUSH_CONSTANTS` where the
+    /// adapter supports it. WebGL never does, so check
+    /// `GfxState::supports_push_constants` before creating pipelines with
+    /// push constants.
+    pub push_constant_size: u32,
+    /// Adapter and device requirements of the main window's `GfxState`
+    ///
+    /// The app fails to start if the adapter lacks a required feature or limit.
+    /// Its main pass settings are replaced with the fields above and
+    /// `AppClient::render_config`.
+    pub gfx_options: GfxStateOptions,
 }
 impl AppClientInfo {
     #[inline]
@@ -16,10 +81,57 @@ impl AppClientInfo {
             window_title: String::from("wgpu-engine"),
             window_size: glam::uvec2(1280, 720),
             wasm_canvas_selector: String::from("#wgpu-canvas"),
+            min_window_size: None,
+            max_window_size: None,
+            resizable: true,
+            window_icon: None,
+            clear_color: glam::vec4(0.0, 0.0, 0.0, 1.0),
+            update_thread_rate: None,
+            fixed_timestep: 1.0 / 60.0,
+            max_fixed_steps: 8,
+            exit_after_frames: None,
+            capture_frames_to: None,
+            surface_view_formats: Vec::new(),
+            ignore_unfocused_input: true,
+            present_mode: wgpu::PresentMode::AutoVsync,
+            push_constant_size: 0,
+            gfx_options: GfxStateOptions::new(),
+        }
+    }
+    /// Replaces invalid settings with their defaults, logging an error for each
+    pub(crate) fn validate(&mut self) {
+        if let Some(rate) = self.update_thread_rate
+            && !is_valid_rate(rate)
+        {
+            log::error!("Invalid update thread rate {rate}, updating once per frame instead");
+            self.update_thread_rate = None;
+        }
+        if !(self.fixed_timestep.is_finite() && self.fixed_timestep > 0.0) {
+            log::error!(
+                "Invalid fixed timestep {}, using 1/60 seconds instead",
+                self.fixed_timestep
+            );
+            self.fixed_timestep = 1.0 / 60.0;
+        }
+    }
+    /// Returns the options of the main window's state, see `gfx_options`
+    pub(crate) fn main_gfx_options(&self, render_config: RenderConfig) -> GfxStateOptions {
+        GfxStateOptions {
+            push_constant_size: self.push_constant_size,
+            render_config,
+            clear_color: self.clear_color,
+            surface_view_formats: self.surface_view_formats.clone(),
+            present_mode: self.present_mode,
+            ..self.gfx_options.clone()
         }
     }
 }
 
+/// Returns `true` if `rate` updates per second give a representable step
+fn is_valid_rate(rate: f32) -> bool {
+    rate.is_finite() && rate > 0.0 && std::time::Duration::try_from_secs_f32(1.0 / rate).is_ok()
+}
+
 impl Default for AppClientInfo {
     #[inline]
     fn default() -> Self {
@@ -27,6 +139,26 @@ impl Default for AppClientInfo {
     }
 }
 
+/// RGBA8 pixels of a window icon
+#[derive(Debug, Clone)]
+pub struct WindowIcon {
+    /// Pixels in row-major order, four bytes per pixel
+    pub rgba: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+}
+impl WindowIcon {
+    /// Decodes an icon from image file contents, e.g. via `include_bytes!`
+    pub fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        let image = image::load_from_memory(bytes)?.into_rgba8();
+        Ok(Self {
+            width: image.width(),
+            height: image.height(),
+            rgba: image.into_raw(),
+        })
+    }
+}
+
 /// Trait for application-specific logic that can be plugged into the main App
 ///
 /// Implementors of this trait define custom behavior for initialization,
@@ -36,21 +168,156 @@ pub trait AppClient: DowncastSync + std::fmt::Debug {
     fn init_client_info(&self) -> AppClientInfo {
         AppClientInfo::new()
     }
+    /// Returns the attachment requirements of the main render pass
+    ///
+    /// Called once when the app is created. Defaults to a single sample, no
+    /// depth buffer and an automatically chosen surface format.
+    fn render_config(&self) -> RenderConfig {
+        RenderConfig::new()
+    }
 
     /// Called once when the application is initialized
     fn init(&self) {}
+    /// Called when the app is suspended, e.g. sent to the background on mobile
+    ///
+    /// Rendering stops until `on_resume`. Only called after `init`.
+    fn on_suspend(&self) {}
+    /// Called when the app is resumed after `on_suspend`
+    ///
+    /// Unlike `init` this can happen many times, but never for the initial
+    /// start of the app. The surface has been recreated at this point, so
+    /// surface dependent resources can be rebuilt from `app().state()`.
+    fn on_resume(&self) {}
     /// Called every frame to update application logic
     ///
+    /// If `AppClientInfo::update_thread_rate` is set, this is instead called
+    /// from a dedicated update thread at that rate, concurrently with `render`.
+    ///
     /// # Arguments
     /// * `delta_time` - Time elapsed since the last frame in seconds
     fn update(&self, delta_time: f32) {}
+    /// Called zero or more times per frame with a constant time step
+    ///
+    /// Runs before `update`, as often as needed to keep up with real time
+    /// at `AppClientInfo::fixed_timestep`, which makes it suitable for
+    /// physics and deterministic simulation. Use `TIME.fixed_alpha()` to
+    /// interpolate between simulation states when rendering.
+    ///
+    /// # Arguments
+    /// * `dt` - The fixed time step in seconds
+    fn fixed_update(&self, dt: f32) {}
+    /// Called every frame before the main render pass
+    ///
+    /// Commands recorded here, e.g. compute passes, run in the same submission
+    /// right before `render`, so their output can be sampled in the same frame.
+    ///
+    /// # Arguments
+    /// * `encoder` - The frame's command encoder
+    fn encode(&self, encoder: &mut wgpu::CommandEncoder) {}
+    /// Called every frame after `encode` to record the whole frame manually
+    ///
+    /// Return `true` to replace the main render pass: the engine then neither
+    /// begins it nor calls `render`, and the client records any number of
+    /// passes writing `view` itself. `view` is the single-sampled frame
+    /// target, which is upscaled onto the surface afterwards when
+    /// `render_scale` is below 1. The default records nothing and returns
+    /// `false`.
+    ///
+    /// # Arguments
+    /// * `encoder` - The frame's command encoder
+    /// * `view` - The color target of the frame
+    fn encode_frame(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) -> bool {
+        false
+    }
+    /// Called every frame after the frame has been drawn onto the surface
+    ///
+    /// Commands recorded here run last in the frame's submission, e.g. a
+    /// post-processing pass writing the surface through `linear_view`.
+    ///
+    /// # Arguments
+    /// * `encoder` - The frame's command encoder
+    /// * `surface` - The surface texture that is presented afterwards
+    /// * `linear_view` - A non-sRGB view of `surface`, `None` unless the
+    ///   surface format is linear or its linear variant is listed in
+    ///   `AppClientInfo::surface_view_formats`
+    fn encode_surface(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        surface: &wgpu::Texture,
+        linear_view: Option<&wgpu::TextureView>,
+    ) {
+    }
     /// Called every frame to render application content
     ///
     /// # Arguments
     /// * `rpass` - WGPU render pass for drawing commands
     fn render(&self, rpass: &mut wgpu::RenderPass<'_>) {}
+    /// Returns a graph to execute instead of the main render pass
+    ///
+    /// When this returns a graph, `render` is not called and the graph's
+    /// final pass writes the frame directly, so `render_scale` and
+    /// multisampling do not apply. `encode` still runs first.
+    fn render_graph(&self) -> Option<&parking_lot::Mutex<RenderGraph>> {
+        None
+    }
 }
 impl_downcast!(sync AppClient);
 
 /// Shared reference to an AppClient implementation
 pub type SharedAppClient = Arc<dyn AppClient>;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn invalid_update_rate_is_rejected() {
+        for rate in [0.0, -30.0, f32::NAN, f32::INFINITY, 1e-30] {
+            let mut info = AppClientInfo {
+                update_thread_rate: Some(rate),
+                ..AppClientInfo::new()
+            };
+            info.validate();
+            assert_eq!(info.update_thread_rate, None, "rate {rate}");
+        }
+        let mut info = AppClientInfo {
+            update_thread_rate: Some(120.0),
+            ..AppClientInfo::new()
+        };
+        info.validate();
+        assert_eq!(info.update_thread_rate, Some(120.0));
+    }
+
+    #[test]
+    fn invalid_fixed_timestep_is_replaced() {
+        for timestep in [0.0, -0.1, f32::NAN, f32::INFINITY] {
+            let mut info = AppClientInfo {
+                fixed_timestep: timestep,
+                ..AppClientInfo::new()
+            };
+            info.validate();
+            assert_eq!(info.fixed_timestep, 1.0 / 60.0, "timestep {timestep}");
+        }
+    }
+
+    #[test]
+    fn main_gfx_options_use_client_settings() {
+        let info = AppClientInfo {
+            push_constant_size: 64,
+            present_mode: wgpu::PresentMode::Fifo,
+            gfx_options: GfxStateOptions::new()
+                .with_power_preference(wgpu::PowerPreference::HighPerformance)
+                .with_push_constant_size(16),
+            ..AppClientInfo::new()
+        };
+        let render_config = RenderConfig::new().with_sample_count(4);
+        let options = info.main_gfx_options(render_config);
+        assert_eq!(options.push_constant_size, 64);
+        assert_eq!(options.present_mode, wgpu::PresentMode::Fifo);
+        assert_eq!(options.render_config, render_config);
+        assert_eq!(
+            options.power_preference,
+            wgpu::PowerPreference::HighPerformance
+        );
+    }
+}
@@ -10,30 +10,39 @@ use winit::application::ApplicationHandler;
 use winit::event::*;
 use winit::event_loop::ActiveEventLoop;
 use winit::keyboard::PhysicalKey;
-use winit::window::Window;
+use winit::window::{Window, WindowAttributes};
 
-use crate::events::{EVENTS, KeyboardData};
+use crate::events::{EVENTS, KeyboardData, SuspendData};
 use crate::gfx::GfxState;
-use crate::{MouseButtonData, MouseMoveData, MouseWheelData, TIME};
+use crate::{
+    FocusData, MouseButtonData, MouseMoveData, MouseWheelData, ResizeData, ScaleFactorData, TIME,
+    TouchData,
+};
 
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
 #[cfg(target_arch = "wasm32")]
 use winit::event_loop::EventLoop;
 
-use super::SharedApp;
+#[cfg(not(target_arch = "wasm32"))]
+use super::spawn_update_thread;
+use super::{AppClientInfo, SharedApp};
 
 impl ApplicationHandler<GfxState> for SharedApp {
     /// Called when the application is resumed or started
     /// Creates the window and initializes the rendering state
+    /// Resuming after a suspend recreates the surfaces instead
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
-        use winit::dpi::PhysicalSize;
+        if self.is_suspended() {
+            self.resume();
+            return;
+        }
+        if self.state.lock().is_some() {
+            return;
+        }
 
-        let size = self.client_info.window_size;
         #[allow(unused_mut)]
-        let mut window_attributes = Window::default_attributes();
-        window_attributes = window_attributes.with_title(&self.client_info.window_title);
-        window_attributes = window_attributes.with_inner_size(PhysicalSize::new(size.x, size.y));
+        let mut window_attributes = window_attributes(&self.client_info);
 
         #[cfg(target_arch = "wasm32")]
         {
@@ -42,32 +51,39 @@ impl ApplicationHandler<GfxState> for SharedApp {
 
             let window = wgpu::web_sys::window().unwrap_throw();
             let document = window.document().unwrap_throw();
+            let selector = &self.client_info.wasm_canvas_selector;
             let canvas = document
-                .query_selector(&self.client_info.wasm_canvas_selector)
-                .unwrap_throw()
-                .unwrap_throw();
+                .query_selector(selector)
+                .ok()
+                .flatten()
+                .unwrap_or_else(|| panic!("No canvas matches `{selector}`"));
             let html_canvas_element = canvas.unchecked_into();
             window_attributes = window_attributes.with_canvas(Some(html_canvas_element));
         }
 
         let window = Arc::new(event_loop.create_window(window_attributes).unwrap());
+        *self.main_window.lock() = Some(window.id());
+        EVENTS.set_scale_factor(window.scale_factor());
 
         #[cfg(not(target_arch = "wasm32"))]
         {
             // If we are not on web we can use pollster to
             // await the
-            let mut state = self.state.lock();
-            *state = Some(pollster::block_on(GfxState::new(Some(window))).unwrap());
+            let options = self.client_info.main_gfx_options(self.render_config);
+            let state = pollster::block_on(GfxState::with_options(Some(window), options)).unwrap();
+            *self.state.lock() = Some(state);
+            self.set_ready();
         }
 
         #[cfg(target_arch = "wasm32")]
         {
             if let Some(proxy) = self.proxy.lock().take() {
+                let options = self.client_info.main_gfx_options(self.render_config);
                 wasm_bindgen_futures::spawn_local(async move {
                     assert!(
                         proxy
                             .send_event(
-                                GfxState::new(Some(window))
+                                GfxState::with_options(Some(window), options)
                                     .await
                                     .expect("Unable to create canvas!!!")
                             )
@@ -88,46 +104,92 @@ impl ApplicationHandler<GfxState> for SharedApp {
             window.request_redraw();
             event.resize(window.inner_size().width, window.inner_size().height);
         }
-        let mut state = self.0.state.lock();
-        *state = Some(event);
+        *self.0.state.lock() = Some(event);
+        self.set_ready();
     }
 
     /// Handles window events such as resize, close, input, and redraw requests
     fn window_event(
         &mut self,
         event_loop: &ActiveEventLoop,
-        _window_id: winit::window::WindowId,
+        window_id: winit::window::WindowId,
         event: WindowEvent,
     ) {
+        let _current = self.enter_window(window_id);
+        if self.handle_window_event(window_id, &event) {
+            return;
+        }
         match event {
-            WindowEvent::CloseRequested => event_loop.exit(),
+            // Exit unless a close requested subscriber canceled it
+            WindowEvent::CloseRequested if self.should_close() => event_loop.exit(),
             WindowEvent::Resized(size) => {
-                let mut state = self.state.lock();
-                let state = match &mut *state {
-                    Some(canvas) => canvas,
-                    None => return,
+                {
+                    let mut state = self.state.lock();
+                    let state = match &mut *state {
+                        Some(canvas) => canvas,
+                        None => return,
+                    };
+                    state.resize(size.width, size.height);
+                    if !state.is_surface_configured || size.width == 0 || size.height == 0 {
+                        return;
+                    }
+                }
+                // Notify without the state lock held
+                let data = ResizeData {
+                    width: size.width,
+                    height: size.height,
+                    logical_size: glam::vec2(size.width as f32, size.height as f32)
+                        / EVENTS.scale_factor() as f32,
                 };
-                state.resize(size.width, size.height);
+                EVENTS.resize().notify(&data);
             }
             WindowEvent::RedrawRequested => {
                 // Initialize the client on first frame
                 {
                     let mut is_initialized = self.is_initialized.lock();
                     if !*is_initialized {
+                        phase_span!("init");
                         self.client.init();
                         *is_initialized = true;
+
+                        #[cfg(not(target_arch = "wasm32"))]
+                        if let Some(rate) = self.client_info.update_thread_rate {
+                            *self.update_thread.lock() = spawn_update_thread(self.clone(), rate);
+                        }
+                    }
+                }
+                // Notify update start and run client update, unless it runs on its own thread
+                if !self.runs_update_thread() {
+                    self.run_fixed_updates(TIME.frame_delta());
+                    phase_span!("update");
+                    EVENTS.update().notify(&());
+                    self.client.update(TIME.frame_delta());
+                }
+
+                // Let clients rebuild their resources after a device loss
+                match self.recover_device_loss() {
+                    Ok(Some(data)) => EVENTS.device_lost().notify(&data),
+                    Ok(None) => {}
+                    Err(e) => {
+                        log::error!("Unable to recreate the lost device, exiting: {e:#}");
+                        self.exit_with(1);
+                        return;
                     }
                 }
-                // Notify update start and run client update
-                EVENTS.update().notify(&());
-                self.client.update(TIME.frame_delta());
 
                 let mut state = self.state.lock();
                 let state = match &mut *state {
                     Some(canvas) => canvas,
                     None => return,
                 };
+                #[cfg(not(target_arch = "wasm32"))]
+                if let Some(dir) = self.client_info.capture_frames_to.as_ref() {
+                    let frame = self.frames_rendered() + 1;
+                    state.request_screenshot(dir.join(format!("frame_{frame:05}.png")));
+                }
                 match state.render() {
+                    // Only count frames that actually reached the surface
+                    Ok(_) if state.is_surface_configured => self.frame_rendered(),
                     Ok(_) => {}
                     Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
                         let size = state.window.as_ref().unwrap().inner_size();
@@ -138,6 +200,15 @@ impl ApplicationHandler<GfxState> for SharedApp {
                     }
                 }
             }
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                // `EVENTS.scale_factor` is the one of the main window
+                if *self.main_window.lock() == Some(window_id) {
+                    EVENTS.set_scale_factor(scale_factor);
+                }
+                EVENTS
+                    .scale_factor_changed()
+                    .notify(&ScaleFactorData { scale_factor });
+            }
             WindowEvent::MouseWheel { delta, .. } => {
                 let delta = match delta {
                     MouseScrollDelta::LineDelta(x, y) => glam::vec2(x, y),
@@ -148,7 +219,16 @@ impl ApplicationHandler<GfxState> for SharedApp {
                 let data = MouseWheelData { delta };
                 EVENTS.mouse_wheel().notify(&data);
             }
+            WindowEvent::Focused(is_focused) => {
+                self.focused.lock().insert(window_id, is_focused);
+                EVENTS.focus().notify(&FocusData {
+                    focused: is_focused,
+                });
+            }
             WindowEvent::MouseInput { state, button, .. } => {
+                if self.suppresses_press(state.is_pressed()) {
+                    return;
+                }
                 let data = MouseButtonData {
                     is_pressed: state.is_pressed(),
                     button,
@@ -160,27 +240,66 @@ impl ApplicationHandler<GfxState> for SharedApp {
                 let current = glam::vec2(position.x as f32, position.y as f32);
                 let data = MouseMoveData {
                     position: current,
+                    logical_position: current / self.window_scale_factor(window_id) as f32,
                     delta: current - last,
                 };
                 EVENTS.mouse_move().notify(&data);
             }
-            WindowEvent::KeyboardInput {
-                event:
-                    KeyEvent {
-                        physical_key: PhysicalKey::Code(key_code),
-                        state,
-                        repeat,
-                        ..
-                    },
-                ..
-            } => {
-                let data = KeyboardData {
-                    key_code,
-                    is_pressed: state.is_pressed(),
-                    is_repeat: repeat,
+            WindowEvent::Touch(touch) => {
+                let position = glam::vec2(touch.location.x as f32, touch.location.y as f32);
+                let data = TouchData {
+                    id: touch.id,
+                    phase: touch.phase,
+                    position,
+                    logical_position: position / self.window_scale_factor(window_id) as f32,
                 };
-                EVENTS.keyboard().notify(&data);
+                EVENTS.touch().notify(&data);
             }
+            WindowEvent::KeyboardInput { event, .. } => {
+                let is_pressed = event.state.is_pressed();
+                if self.suppresses_press(is_pressed) {
+                    return;
+                }
+                if let PhysicalKey::Code(key_code) = event.physical_key {
+                    let data = KeyboardData {
+                        key_code,
+                        is_pressed,
+                        is_repeat: event.repeat,
+                        modifiers: EVENTS.modifiers(),
+                        scancode: scancode(event.physical_key),
+                    };
+                    EVENTS.keyboard().notify(&data);
+                }
+                // Keys typed into a composition arrive as IME events instead
+                if is_pressed
+                    && !*self.is_composing.lock()
+                    && let Some(text) = event.text.as_deref()
+                {
+                    self.publish_text(text, false);
+                }
+            }
+            WindowEvent::ModifiersChanged(modifiers) => {
+                EVENTS.set_modifiers(modifiers.state());
+            }
+            WindowEvent::Ime(ime) => match ime {
+                Ime::Preedit(text, _) => {
+                    let was_composing =
+                        std::mem::replace(&mut *self.is_composing.lock(), !text.is_empty());
+                    if was_composing || !text.is_empty() {
+                        self.publish_text(&text, true);
+                    }
+                }
+                Ime::Commit(text) => {
+                    *self.is_composing.lock() = false;
+                    self.publish_text(&text, false);
+                }
+                Ime::Disabled => {
+                    if std::mem::take(&mut *self.is_composing.lock()) {
+                        self.publish_text("", true);
+                    }
+                }
+                Ime::Enabled => {}
+            },
             _ => {}
         }
     }
@@ -188,7 +307,17 @@ impl ApplicationHandler<GfxState> for SharedApp {
     /// Called at the start of each event loop iteration
     fn new_events(&mut self, event_loop: &ActiveEventLoop, cause: StartCause) {
         let _ = (event_loop, cause);
+        crate::gfx::rotate_render_stats();
         EVENTS.start_of_frame().notify(&());
+        crate::events::publish_replayed_events();
+        #[cfg(any(
+            target_arch = "wasm32",
+            all(
+                feature = "gamepad",
+                not(any(target_os = "android", target_os = "ios"))
+            )
+        ))]
+        crate::events::poll_gamepads();
     }
 
     /// Handles device events (currently unused)
@@ -202,24 +331,55 @@ impl ApplicationHandler<GfxState> for SharedApp {
     }
 
     /// Called when the event loop is about to wait for new events
-    /// Handles application exit logic and frame end notifications
+    /// Handles application exit logic, frame end notifications and
+    /// applies the requested control flow
     fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
         if !*self.exit.lock() {
             EVENTS.end_of_frame().notify(&());
             EVENTS.maintain();
+
+            event_loop.set_control_flow(self.control_flow());
+            self.create_pending_windows(event_loop);
+            // Honor redraws requested while idling
+            let redraw_requested = std::mem::take(&mut *self.redraw_requested.lock());
+            if redraw_requested
+                && let Some(window) = self.state.lock().as_ref().and_then(|s| s.window.as_ref())
+            {
+                window.request_redraw();
+            }
+            self.request_window_redraws(redraw_requested);
             return;
         }
         event_loop.exit();
     }
 
-    /// Called when the application is suspended (currently unused)
+    /// Called when the application is suspended
+    /// Stops rendering and notifies the client
     fn suspended(&mut self, event_loop: &ActiveEventLoop) {
         let _ = event_loop;
+        *self.is_suspended.lock() = true;
+        if let Some(state) = self.state.lock().as_mut() {
+            state.suspend_surface();
+        }
+        if *self.is_initialized.lock() {
+            self.client.on_suspend();
+        }
+        EVENTS.suspend().notify(&SuspendData { suspended: true });
     }
 
-    /// Called when the application is exiting (currently unused)
+    /// Called when the application is exiting
+    /// Makes sure background threads observe the exit
+    /// Waits for the update thread, so `update` doesn't run during shutdown
     fn exiting(&mut self, event_loop: &ActiveEventLoop) {
         let _ = event_loop;
+        *self.exit.lock() = true;
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(thread) = self.update_thread.lock().take() {
+            thread.thread().unpark();
+            if thread.join().is_err() {
+                log::error!("The update thread panicked");
+            }
+        }
     }
 
     /// Called when the system issues a memory warning (currently unused)
@@ -227,3 +387,59 @@ impl ApplicationHandler<GfxState> for SharedApp {
         let _ = event_loop;
     }
 }
+
+/// Returns the platform scancode of `key`, where winit reports one
+fn scancode(key: PhysicalKey) -> Option<u32> {
+    #[cfg(any(
+        target_os = "windows",
+        target_os = "macos",
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    {
+        use winit::platform::scancode::PhysicalKeyExtScancode;
+        key.to_scancode()
+    }
+    #[cfg(not(any(
+        target_os = "windows",
+        target_os = "macos",
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    )))]
+    {
+        let _ = key;
+        None
+    }
+}
+
+/// Returns the attributes of a window described by `info`
+///
+/// The canvas of the main window on the web is set separately.
+pub(crate) fn window_attributes(info: &AppClientInfo) -> WindowAttributes {
+    use winit::dpi::PhysicalSize;
+
+    let size = info.window_size;
+    let mut window_attributes = Window::default_attributes();
+    window_attributes = window_attributes.with_title(&info.window_title);
+    window_attributes = window_attributes.with_inner_size(PhysicalSize::new(size.x, size.y));
+    if let Some(min) = info.min_window_size {
+        window_attributes = window_attributes.with_min_inner_size(PhysicalSize::new(min.x, min.y));
+    }
+    if let Some(max) = info.max_window_size {
+        window_attributes = window_attributes.with_max_inner_size(PhysicalSize::new(max.x, max.y));
+    }
+    window_attributes = window_attributes.with_resizable(info.resizable);
+    if let Some(icon) = info.window_icon.as_ref() {
+        match winit::window::Icon::from_rgba(icon.rgba.clone(), icon.width, icon.height) {
+            Ok(icon) => window_attributes = window_attributes.with_window_icon(Some(icon)),
+            Err(e) => log::warn!("Ignoring invalid window icon: {e}"),
+        }
+    }
+    window_attributes
+}
@@ -3,15 +3,20 @@
 mod client;
 mod current;
 mod handler;
+mod threaded;
+mod windows;
 pub use client::*;
 pub use current::*;
+pub use threaded::*;
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use parking_lot::{Mutex, MutexGuard};
 
-use crate::gfx::GfxState;
+use crate::gfx::{GfxState, RenderConfig};
 
+pub use winit::event_loop::ControlFlow;
 #[cfg(target_arch = "wasm32")]
 use winit::event_loop::EventLoop;
 
@@ -26,10 +31,41 @@ pub struct App {
     /// Client implementation containing app-specific logic
     client: SharedAppClient,
     client_info: AppClientInfo,
+    render_config: RenderConfig,
     /// Flag indicating if the app has been initialized
     is_initialized: Mutex<bool>,
+    /// Flag indicating the graphics state has been created
+    is_ready: Mutex<bool>,
+    /// Flag indicating the app is suspended
+    is_suspended: Mutex<bool>,
+    /// Flag indicating an IME composition is in progress
+    is_composing: Mutex<bool>,
     /// Flag to signal app should exit
     exit: Mutex<bool>,
+    /// Code passed to `exit_with`, reported by `run`
+    exit_code: Mutex<i32>,
+    /// Desired event loop control flow, applied every loop iteration
+    control_flow: Mutex<ControlFlow>,
+    /// Flag indicating a redraw was requested while not polling
+    redraw_requested: Mutex<bool>,
+    /// Number of frames successfully rendered
+    frames_rendered: Mutex<u64>,
+    /// Input focus of every window that reported a focus change
+    focused: Mutex<HashMap<winit::window::WindowId, bool>>,
+    /// Flag set by `cancel_close` while the close requested event is published
+    close_canceled: Mutex<bool>,
+    /// Windows opened with `open_window`
+    windows: Mutex<Vec<windows::SecondaryWindow>>,
+    /// Windows to create at the end of the event loop iteration
+    pending_windows: Mutex<Vec<(SharedAppClient, AppClientInfo)>>,
+    /// Main window, once created
+    main_window: Mutex<Option<winit::window::WindowId>>,
+    /// Window of the event or client callback being handled, `None` for the
+    /// main window outside of window events
+    current_window: Mutex<Option<winit::window::WindowId>>,
+    /// Thread running `AppClient::update`, joined on exit
+    #[cfg(not(target_arch = "wasm32"))]
+    update_thread: Mutex<Option<std::thread::JoinHandle<()>>>,
 }
 impl App {
     /// Creates a new App instance from a client
@@ -40,13 +76,31 @@ impl App {
         #[cfg(target_arch = "wasm32")]
         let proxy = Some(event_loop.create_proxy());
 
-        let client_info = client.init_client_info();
+        let mut client_info = client.init_client_info();
+        client_info.validate();
+        let render_config = client.render_config();
         SharedApp(Arc::new(Self {
             state: Mutex::new(None),
             client,
             client_info,
+            render_config,
             is_initialized: Mutex::new(false),
+            is_ready: Mutex::new(false),
+            is_suspended: Mutex::new(false),
+            is_composing: Mutex::new(false),
             exit: Mutex::new(false),
+            exit_code: Mutex::new(0),
+            control_flow: Mutex::new(ControlFlow::Poll),
+            redraw_requested: Mutex::new(false),
+            frames_rendered: Mutex::new(0),
+            focused: Mutex::new(HashMap::new()),
+            close_canceled: Mutex::new(false),
+            windows: Mutex::new(Vec::new()),
+            pending_windows: Mutex::new(Vec::new()),
+            main_window: Mutex::new(None),
+            current_window: Mutex::new(None),
+            #[cfg(not(target_arch = "wasm32"))]
+            update_thread: Mutex::new(None),
             #[cfg(target_arch = "wasm32")]
             proxy: Mutex::new(proxy),
         }))
@@ -62,11 +116,217 @@ impl App {
     pub fn client_info(&self) -> &AppClientInfo {
         &self.client_info
     }
+    /// Returns the render configuration declared by the client
+    pub fn render_config(&self) -> RenderConfig {
+        self.render_config
+    }
+    /// Returns `true` if `AppClient::update` runs on a dedicated thread
+    pub fn runs_update_thread(&self) -> bool {
+        cfg!(not(target_arch = "wasm32")) && self.client_info.update_thread_rate.is_some()
+    }
+    /// Returns the number of frames successfully rendered so far
+    pub fn frames_rendered(&self) -> u64 {
+        *self.frames_rendered.lock()
+    }
+    /// Counts a rendered frame and exits once `exit_after_frames` is reached
+    fn frame_rendered(&self) {
+        let mut frames_rendered = self.frames_rendered.lock();
+        *frames_rendered += 1;
+        if self
+            .client_info
+            .exit_after_frames
+            .is_some_and(|n| *frames_rendered >= n)
+        {
+            self.exit();
+        }
+    }
+    /// Returns `true` once the window and graphics state have been created
+    ///
+    /// From then on `App::state` always holds a `GfxState`. See
+    /// `Events::ready` to be notified when this happens.
+    pub fn is_ready(&self) -> bool {
+        *self.is_ready.lock()
+    }
+    /// Returns `true` while the app is suspended, see `AppClient::on_suspend`
+    pub fn is_suspended(&self) -> bool {
+        *self.is_suspended.lock()
+    }
+    /// Runs the `AppClient::fixed_update` steps due after `delta` seconds
+    fn run_fixed_updates(&self, delta: f32) {
+        let dt = self.client_info.fixed_timestep;
+        let steps = crate::TIME.accumulate_fixed(delta, dt, self.client_info.max_fixed_steps);
+        if steps > 0 {
+            phase_span!("fixed_update");
+            for _ in 0..steps {
+                self.client.fixed_update(dt);
+            }
+        }
+    }
+    /// Returns `true` if the current window has input focus
+    ///
+    /// See [`App::current_window`] and [`App::is_window_focused`].
+    pub fn is_focused(&self) -> bool {
+        self.current_window()
+            .is_none_or(|window| self.is_window_focused(window))
+    }
+    /// Returns `true` if `window` has input focus
+    ///
+    /// Windows count as focused until they report a focus change.
+    pub fn is_window_focused(&self, window: winit::window::WindowId) -> bool {
+        self.focused.lock().get(&window).copied().unwrap_or(true)
+    }
+    /// Returns `true` if a press should not be published as the window is unfocused
+    fn suppresses_press(&self, is_pressed: bool) -> bool {
+        is_pressed && self.client_info.ignore_unfocused_input && !self.is_focused()
+    }
+    /// Publishes `text` on the text input event, without control characters
+    fn publish_text(&self, text: &str, is_preedit: bool) {
+        let text: String = text.chars().filter(|c| !c.is_control()).collect();
+        if text.is_empty() && !is_preedit {
+            return;
+        }
+        crate::EVENTS
+            .text_input()
+            .notify(&crate::TextInputData { text, is_preedit });
+    }
+    /// Returns `true` if there is no windowed graphics state
+    ///
+    /// This is the case before the app is ready and when the state was
+    /// created without a window, see `GfxState::is_headless`. It locks the
+    /// state, so it must not be called while the state lock is held, e.g.
+    /// from `AppClient::render`; use `GfxState::is_headless` there.
+    pub fn is_headless(&self) -> bool {
+        self.state().as_ref().is_none_or(GfxState::is_headless)
+    }
+    /// Marks the app as ready and publishes the ready event the first time
+    ///
+    /// Must be called without holding the state lock.
+    fn set_ready(&self) {
+        {
+            let mut is_ready = self.is_ready.lock();
+            if *is_ready {
+                return;
+            }
+            *is_ready = true;
+        }
+        crate::EVENTS.ready().notify(&());
+    }
+    /// Recreates the surfaces after a suspend and notifies the client
+    ///
+    /// Must be called without holding the state lock.
+    fn resume(&self) {
+        *self.is_suspended.lock() = false;
+        if let Some(state) = self.state.lock().as_mut() {
+            if let Err(e) = state.resume_surface() {
+                log::error!("Unable to recreate the surface: {e:#}");
+            }
+            self.recreate_window_surfaces(state);
+        }
+        if *self.is_initialized.lock() {
+            self.client.on_resume();
+        }
+        crate::EVENTS
+            .suspend()
+            .notify(&crate::SuspendData { suspended: false });
+    }
+    /// Recreates the device if it was lost and returns the loss details
+    ///
+    /// Must be called without holding the state lock. Recreating blocks,
+    /// so on the web a lost device is only logged.
+    ///
+    /// # Returns
+    ///
+    /// Returns the loss details once the device was recreated, `None` if it
+    /// was not lost, or an error if it could not be recreated.
+    fn recover_device_loss(&self) -> anyhow::Result<Option<crate::DeviceLostData>> {
+        let mut state = self.state.lock();
+        let Some(state) = state.as_mut() else {
+            return Ok(None);
+        };
+        if !state.is_device_lost() {
+            return Ok(None);
+        }
+        #[cfg(target_arch = "wasm32")]
+        return Ok(None);
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let data = state.take_device_lost();
+            pollster::block_on(state.recreate_device())?;
+            self.recreate_window_surfaces(state);
+            Ok(data)
+        }
+    }
+    /// Keeps the app running after the window was asked to close
+    ///
+    /// Only has an effect when called from a subscriber of
+    /// `Events::close_requested`. Explicit calls to [`App::exit`] are not
+    /// affected.
+    pub fn cancel_close(&self) {
+        *self.close_canceled.lock() = true;
+    }
+    /// Publishes the close requested event and returns `true` unless canceled
+    fn should_close(&self) -> bool {
+        *self.close_canceled.lock() = false;
+        crate::EVENTS.close_requested().notify(&());
+        !std::mem::take(&mut *self.close_canceled.lock())
+    }
     /// Signals the application to exit
     pub fn exit(&self) {
         *self.exit.lock() = true;
     }
+    /// Signals the application to exit with an exit code
+    ///
+    /// A non-zero code makes `run` return an [`AppExitError`] carrying it.
+    pub fn exit_with(&self, code: i32) {
+        *self.exit_code.lock() = code;
+        self.exit();
+    }
+    /// Returns the code passed to [`App::exit_with`], 0 by default
+    pub fn exit_code(&self) -> i32 {
+        *self.exit_code.lock()
+    }
+    /// Returns the event loop control flow the app is currently using
+    pub fn control_flow(&self) -> ControlFlow {
+        *self.control_flow.lock()
+    }
+    /// Sets the event loop control flow
+    ///
+    /// `ControlFlow::Poll` (the default) redraws continuously. With
+    /// `ControlFlow::Wait` the app idles until an event arrives or a redraw
+    /// is requested via [`App::request_redraw`].
+    pub fn set_control_flow(&self, control_flow: ControlFlow) {
+        *self.control_flow.lock() = control_flow;
+    }
+    /// Requests that a new frame be drawn at the end of the current loop iteration
+    ///
+    /// This is only needed when not polling, since `ControlFlow::Poll`
+    /// redraws every iteration anyway.
+    pub fn request_redraw(&self) {
+        *self.redraw_requested.lock() = true;
+    }
+}
+
+/// Error returned by `run` when the app exited with a non-zero code
+///
+/// ```ignore
+/// fn main() {
+///     if let Err(e) = my_app::run() {
+///         let code = e.downcast_ref::<AppExitError>().map_or(1, |e| e.code);
+///         std::process::exit(code);
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AppExitError {
+    /// The code passed to `App::exit_with`
+    pub code: i32,
+}
+impl std::fmt::Display for AppExitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Application exited with code {}", self.code)
+    }
 }
+impl std::error::Error for AppExitError {}
 
 /// Shared reference to an App instance, allowing multiple owners
 #[derive(Clone, educe::Educe)]
@@ -0,0 +1,103 @@
+//! Threaded update support
+//!
+//! When `AppClientInfo::update_thread_rate` is set, `AppClient::update` runs on
+//! a dedicated thread at a fixed rate, while `AppClient::render` stays on the
+//! event loop thread. `AppClient` already requires `Send + Sync`, so the same
+//! client instance is shared by both threads; any state touched by both
+//! `update` and `render` must be synchronized by the client.
+//!
+//! `StateHandoff` provides a simple way to do this: the update thread
+//! publishes complete snapshots, and the render thread reads the latest one.
+//!
+//! Input events are still published on the event loop thread, so their
+//! subscribers run there. `INPUT` can be polled from both threads; its
+//! pressed flags, e.g. `Input::was_key_pressed`, then last one update tick
+//! instead of one frame, so every press is seen by exactly one `update`.
+
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+/// Double-buffered handoff of snapshots from a producer to a consumer thread
+///
+/// The producer builds a new value and publishes it in one step, so the
+/// consumer always observes a complete snapshot and never blocks on the
+/// producer for longer than a pointer swap.
+pub struct StateHandoff<T> {
+    latest: Mutex<Option<Arc<T>>>,
+}
+impl<T> StateHandoff<T> {
+    /// Creates an empty handoff
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            latest: Mutex::new(None),
+        }
+    }
+    /// Publishes a new snapshot, replacing the previous one
+    #[inline]
+    pub fn publish(&self, value: T) {
+        *self.latest.lock() = Some(Arc::new(value));
+    }
+    /// Returns the most recently published snapshot, if any
+    #[inline]
+    pub fn latest(&self) -> Option<Arc<T>> {
+        self.latest.lock().clone()
+    }
+}
+impl<T> Default for StateHandoff<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Spawns the thread calling `AppClient::update` at `rate` updates per second
+///
+/// The thread stops once the application is asked to exit. `rate` must
+/// have been checked by `AppClientInfo::validate`.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn spawn_update_thread(
+    app: super::SharedApp,
+    rate: f32,
+) -> Option<std::thread::JoinHandle<()>> {
+    use web_time::{Duration, Instant};
+
+    use crate::events::EVENTS;
+
+    let step = Duration::from_secs_f32(1.0 / rate);
+    crate::INPUT.set_per_update_tick(true);
+    let result = std::thread::Builder::new()
+        .name(String::from("wgpu-engine update"))
+        .spawn(move || {
+            let mut last = Instant::now();
+            while !*app.exit.lock() {
+                let now = Instant::now();
+                // Honor the time scale and pausing like the frame delta does
+                let delta = (now - last).as_secs_f32() * crate::TIME.effective_scale();
+                last = now;
+                crate::INPUT.begin_update_tick();
+                app.run_fixed_updates(delta);
+                {
+                    phase_span!("update");
+                    EVENTS.update().notify(&());
+                    app.client.update(delta);
+                }
+                // Sleep for the remainder of the step, waking early on exit
+                let deadline = now + step;
+                while !*app.exit.lock() {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if remaining.is_zero() {
+                        break;
+                    }
+                    std::thread::park_timeout(remaining);
+                }
+            }
+        });
+    result
+        .inspect_err(|e| {
+            log::error!("Unable to spawn update thread: {e}");
+            crate::INPUT.set_per_update_tick(false);
+        })
+        .ok()
+}
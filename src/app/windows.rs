@@ -0,0 +1,294 @@
+//! Additional windows with their own client and surface
+
+use std::sync::Arc;
+
+use winit::event::WindowEvent;
+use winit::event_loop::{ActiveEventLoop, ControlFlow};
+use winit::window::{Window, WindowId};
+
+use crate::TIME;
+use crate::app::{App, AppClientInfo, SharedAppClient};
+use crate::events::EVENTS;
+use crate::gfx::{Color, GfxState, PassTargets, RenderConfig, Texture2D, begin_pass};
+
+/// A window opened with `App::open_window`
+///
+/// It shares the device and queue of the main `GfxState` but presents to a
+/// surface of its own, drawn by its own client.
+pub(crate) struct SecondaryWindow {
+    pub(crate) window: Arc<Window>,
+    pub(crate) client: SharedAppClient,
+    info: AppClientInfo,
+    render_config: RenderConfig,
+    surface: wgpu::Surface<'static>,
+    config: wgpu::SurfaceConfiguration,
+    depth_buffer: Option<Texture2D>,
+}
+impl SecondaryWindow {
+    pub(crate) fn new(
+        state: &GfxState,
+        window: Arc<Window>,
+        client: SharedAppClient,
+        info: AppClientInfo,
+    ) -> anyhow::Result<Self> {
+        let mut render_config = client.render_config();
+        if render_config.sample_count > 1 {
+            log::warn!("Multisampling is not supported in additional windows");
+            render_config.sample_count = 1;
+        }
+        let surface = state.create_surface(window.clone())?;
+        let size = window.inner_size();
+        let config = GfxState::initial_surface_config(
+            &surface,
+            &state.adapter,
+            size.width,
+            size.height,
+            render_config.color_format_override,
+            &info.surface_view_formats,
+            info.present_mode,
+        );
+        let mut secondary = Self {
+            window,
+            client,
+            info,
+            render_config,
+            surface,
+            config,
+            depth_buffer: None,
+        };
+        secondary.resize(state, size.width, size.height);
+        Ok(secondary)
+    }
+    #[inline]
+    pub(crate) fn id(&self) -> WindowId {
+        self.window.id()
+    }
+    /// Returns the render configuration the window's attachments follow
+    pub(crate) fn render_config(&self) -> RenderConfig {
+        self.render_config
+    }
+    /// Recreates the surface after the device of `state` was recreated
+    pub(crate) fn recreate_surface(&mut self, state: &GfxState) -> anyhow::Result<()> {
+        self.surface = state.create_surface(self.window.clone())?;
+        let size = self.window.inner_size();
+        self.resize(state, size.width, size.height);
+        Ok(())
+    }
+    pub(crate) fn resize(&mut self, state: &GfxState, width: u32, height: u32) {
+        if width == 0 || height == 0 {
+            return;
+        }
+        self.config.width = width;
+        self.config.height = height;
+        self.surface.configure(&state.device, &self.config);
+        self.depth_buffer =
+            GfxState::create_depth_buffer(&state.device, &self.render_config, (width, height));
+    }
+    /// Draws a frame with the window's client and presents it
+    ///
+    /// Like the main window the client must not lock the state here.
+    pub(crate) fn render(&mut self, state: &GfxState) {
+        let output = match self.surface.get_current_texture() {
+            Ok(output) => output,
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                let size = self.window.inner_size();
+                self.resize(state, size.width, size.height);
+                return;
+            }
+            Err(e) => {
+                log::error!("Unable to render window {:?}: {e}", self.id());
+                return;
+            }
+        };
+        let view = output.texture.create_view(&Default::default());
+        let linear_format = Some(output.texture.format().remove_srgb_suffix());
+        let linear_view = GfxState::create_surface_view(
+            &output.texture,
+            &self.config.view_formats,
+            linear_format,
+        )
+        .ok();
+        let mut encoder = state.create_encoder(Some("Window Render Encoder"));
+        self.client.encode(&mut encoder);
+        if !self.client.encode_frame(&mut encoder, &view) {
+            let targets = PassTargets {
+                color: &view,
+                color_load: wgpu::LoadOp::Clear(Color(self.info.clear_color).into()),
+                resolve_target: None,
+                depth: self.depth_buffer.as_ref().map(Texture2D::view),
+                depth_load: wgpu::LoadOp::Clear(1.0),
+            };
+            let mut render_pass = begin_pass(&mut encoder, &targets, Some("Window Render Pass"));
+            self.client.render(&mut render_pass);
+        }
+        self.client
+            .encode_surface(&mut encoder, &output.texture, linear_view.as_ref());
+        state.submit(encoder);
+        output.present();
+    }
+}
+
+/// Restores the previous current window when dropped, see `App::enter_window`
+pub(crate) struct CurrentWindowGuard<'a> {
+    app: &'a App,
+    previous: Option<WindowId>,
+}
+impl Drop for CurrentWindowGuard<'_> {
+    fn drop(&mut self) {
+        *self.app.current_window.lock() = self.previous;
+    }
+}
+
+impl App {
+    /// Opens an additional window drawn by `client`
+    ///
+    /// The window is created at the end of the current event loop iteration,
+    /// once the app is ready, using the window and surface fields of `info`.
+    /// It shares the device of the main `GfxState`, while `client` gets its
+    /// own `init`, `update`, `encode` and `render` calls and a surface
+    /// following its `render_config`, except that multisampling is not
+    /// supported. `fixed_update` is only called on the main client, and
+    /// `TIME.fixed_alpha()` refers to its steps. Closing the window drops it without exiting the app.
+    ///
+    /// Input events of all windows go to the same `EVENTS` publishers; use
+    /// [`App::current_window`] to tell them apart. Their logical positions
+    /// use the scale factor of the window they come from, while
+    /// `EVENTS.scale_factor()` stays the one of the main window. `app_client_as` always
+    /// refers to the main client, so additional clients should be kept by
+    /// whoever opened them.
+    ///
+    /// Additional windows are only supported on native platforms; on the
+    /// web this logs a warning.
+    pub fn open_window(&self, client: SharedAppClient, info: AppClientInfo) {
+        if cfg!(target_arch = "wasm32") {
+            log::warn!("Additional windows are not supported on the web");
+            return;
+        }
+        self.pending_windows.lock().push((client, info));
+    }
+    /// Returns the window the event or client callback being handled belongs to
+    ///
+    /// This is an additional window while its events are published or its
+    /// client runs, and the main window otherwise. `None` before the main
+    /// window is created.
+    pub fn current_window(&self) -> Option<WindowId> {
+        self.current_window.lock().or(*self.main_window.lock())
+    }
+    /// Makes `window` the current window until the guard is dropped
+    pub(crate) fn enter_window(&self, window: WindowId) -> CurrentWindowGuard<'_> {
+        let previous = self.current_window.lock().replace(window);
+        CurrentWindowGuard {
+            app: self,
+            previous,
+        }
+    }
+    /// Returns the render configuration of a window opened with [`App::open_window`]
+    ///
+    /// Clients of additional windows should build their pipelines from this
+    /// and [`App::window_surface_format`], e.g. in `init`. Neither may be
+    /// called from `AppClient::render` of an additional window.
+    pub fn window_render_config(&self, window: WindowId) -> Option<RenderConfig> {
+        let windows = self.windows.lock();
+        let secondary = windows.iter().find(|w| w.id() == window)?;
+        Some(secondary.render_config())
+    }
+    /// Returns the surface format of a window opened with [`App::open_window`]
+    pub fn window_surface_format(&self, window: WindowId) -> Option<wgpu::TextureFormat> {
+        let windows = self.windows.lock();
+        let secondary = windows.iter().find(|w| w.id() == window)?;
+        Some(secondary.config.format)
+    }
+
+    /// Returns the scale factor of `window`, the main window's if it is not an additional one
+    pub(crate) fn window_scale_factor(&self, window: WindowId) -> f64 {
+        let windows = self.windows.lock();
+        match windows.iter().find(|w| w.id() == window) {
+            Some(secondary) => secondary.window.scale_factor(),
+            None => EVENTS.scale_factor(),
+        }
+    }
+
+    /// Creates the windows requested with `open_window` and initializes their clients
+    pub(crate) fn create_pending_windows(&self, event_loop: &ActiveEventLoop) {
+        if !self.is_ready() {
+            return;
+        }
+        let pending = std::mem::take(&mut *self.pending_windows.lock());
+        for (client, info) in pending {
+            let attributes = super::handler::window_attributes(&info);
+            let created = event_loop
+                .create_window(attributes)
+                .map_err(anyhow::Error::from)
+                .and_then(|window| {
+                    let state = self.state.lock();
+                    let state = state.as_ref().unwrap();
+                    SecondaryWindow::new(state, Arc::new(window), client.clone(), info)
+                });
+            match created {
+                Ok(secondary) => {
+                    let id = secondary.id();
+                    self.windows.lock().push(secondary);
+                    let _current = self.enter_window(id);
+                    client.init();
+                }
+                Err(e) => log::error!("Unable to open window: {e:#}"),
+            }
+        }
+    }
+    /// Requests redraws of the additional windows
+    pub(crate) fn request_window_redraws(&self, redraw_requested: bool) {
+        if redraw_requested || self.control_flow() == ControlFlow::Poll {
+            for secondary in self.windows.lock().iter() {
+                secondary.window.request_redraw();
+            }
+        }
+    }
+    /// Recreates the surfaces of the additional windows after a device loss
+    pub(crate) fn recreate_window_surfaces(&self, state: &GfxState) {
+        for secondary in self.windows.lock().iter_mut() {
+            if let Err(e) = secondary.recreate_surface(state) {
+                log::error!("Unable to recreate window surface: {e:#}");
+            }
+        }
+    }
+    /// Handles the window specific events of additional windows
+    ///
+    /// Returns `true` if the event was consumed. Input events are left to
+    /// the shared handling of the main window.
+    pub(crate) fn handle_window_event(&self, window: WindowId, event: &WindowEvent) -> bool {
+        let client = {
+            let windows = self.windows.lock();
+            match windows.iter().find(|w| w.id() == window) {
+                Some(secondary) => secondary.client.clone(),
+                None => return false,
+            }
+        };
+        match event {
+            WindowEvent::CloseRequested => {
+                self.windows.lock().retain(|w| w.id() != window);
+                self.focused.lock().remove(&window);
+            }
+            WindowEvent::Resized(size) => {
+                let state = self.state.lock();
+                if let Some(state) = state.as_ref() {
+                    let mut windows = self.windows.lock();
+                    if let Some(secondary) = windows.iter_mut().find(|w| w.id() == window) {
+                        secondary.resize(state, size.width, size.height);
+                    }
+                }
+            }
+            WindowEvent::RedrawRequested => {
+                client.update(TIME.frame_delta());
+                let state = self.state.lock();
+                if let Some(state) = state.as_ref() {
+                    let mut windows = self.windows.lock();
+                    if let Some(secondary) = windows.iter_mut().find(|w| w.id() == window) {
+                        secondary.render(state);
+                    }
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+}
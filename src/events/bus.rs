@@ -0,0 +1,166 @@
+//! Publishers for user defined event types
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use parking_lot::Mutex;
+
+use crate::observer::{Publisher, SubscribeQueue, Subscriber, SubscriptionHandle};
+
+use super::{EventPublisher, MutEventPublisher};
+
+/// Type erased access to the publishers of an `EventBus`
+trait AnyPublisher: Send + Sync {
+    fn maintain(&self);
+    fn set_catch_panics(&self, enabled: bool);
+    fn into_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync>;
+}
+impl<T: 'static> AnyPublisher for MutEventPublisher<T> {
+    fn maintain(&self) {
+        self.lock().maintain();
+    }
+    fn set_catch_panics(&self, enabled: bool) {
+        self.lock().set_catch_panics(enabled);
+    }
+    fn into_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync> {
+        self
+    }
+}
+
+/// One publisher per event type, created on first use
+///
+/// Any `'static` type can be an event, so game logic can define its own
+/// events without touching the built-in ones. `EVENTS.bus()` is a global
+/// bus maintained every frame like the other `EVENTS` publishers.
+///
+/// ```ignore
+/// struct PlayerDied { score: u32 }
+///
+/// EVENTS.bus().subscribe(FnSubscriber::new(|event: &PlayerDied| {
+///     log::info!("Final score: {}", event.score);
+///     Subscription::Keep
+/// }).boxed());
+/// EVENTS.bus().publish(&PlayerDied { score: 42 });
+/// ```
+#[derive(Default)]
+pub struct EventBus {
+    publishers: Mutex<HashMap<TypeId, Arc<dyn AnyPublisher>>>,
+    catch_panics: AtomicBool,
+}
+impl EventBus {
+    /// Creates a bus without any publishers
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Returns the publisher of `T`, creating it if needed
+    fn publisher<T: 'static>(&self) -> Arc<MutEventPublisher<T>> {
+        let publisher = self
+            .publishers
+            .lock()
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| {
+                let mut publisher: EventPublisher<T> = Publisher::new();
+                publisher.set_catch_panics(self.catch_panics.load(Ordering::Relaxed));
+                Arc::new(Mutex::new(publisher))
+            })
+            .clone();
+        publisher.into_any().downcast().unwrap()
+    }
+    /// Subscribes a listener to events of type `T`
+    ///
+    /// See [`Publisher::subscribe`].
+    pub fn subscribe<T: 'static>(&self, listener: Box<dyn Subscriber<Data = T>>) -> u64 {
+        self.publisher::<T>().lock().subscribe(listener)
+    }
+    /// Subscribes a listener to events of type `T` for as long as the handle is alive
+    ///
+    /// See [`Publisher::subscribe_scoped`].
+    pub fn subscribe_scoped<T: 'static>(
+        &self,
+        listener: Box<dyn Subscriber<Data = T>>,
+    ) -> SubscriptionHandle {
+        self.publisher::<T>().lock().subscribe_scoped(listener)
+    }
+    /// Returns a queue for subscribing to events of type `T` from within handlers
+    ///
+    /// See [`Publisher::subscribe_queue`].
+    pub fn subscribe_queue<T: 'static>(&self) -> SubscribeQueue<Box<dyn Subscriber<Data = T>>> {
+        self.publisher::<T>().lock().subscribe_queue()
+    }
+    /// Removes a subscriber of events of type `T` by its registration ID
+    pub fn unsubscribe<T: 'static>(&self, listener_id: u64) {
+        self.publisher::<T>().lock().unsubscribe(listener_id);
+    }
+    /// Returns the number of subscribers of events of type `T`
+    pub fn subscriber_count<T: 'static>(&self) -> usize {
+        self.publisher::<T>().lock().len()
+    }
+    /// Notifies all subscribers of events of type `T`
+    ///
+    /// The publisher of `T` stays locked while its subscribers run, so they
+    /// may publish other event types but not `T` itself. Publishers of other
+    /// types are not affected.
+    pub fn publish<T: 'static>(&self, event: &T) {
+        self.publisher::<T>().lock().notify(event);
+    }
+    /// Maintains the publishers of all event types
+    ///
+    /// See [`Publisher::maintain`].
+    pub fn maintain(&self) {
+        let publishers: Vec<_> = self.publishers.lock().values().cloned().collect();
+        for publisher in publishers {
+            publisher.maintain();
+        }
+    }
+    /// Enables or disables panic isolation for all event types, including later ones
+    ///
+    /// See [`Publisher::set_catch_panics`].
+    pub fn set_catch_panics(&self, enabled: bool) {
+        self.catch_panics.store(enabled, Ordering::Relaxed);
+        let publishers: Vec<_> = self.publishers.lock().values().cloned().collect();
+        for publisher in publishers {
+            publisher.set_catch_panics(enabled);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::AtomicU32;
+
+    use super::*;
+    use crate::observer::{FnSubscriber, Subscription};
+
+    struct PlayerDied {
+        score: u32,
+    }
+    struct LevelLoaded;
+
+    #[test]
+    fn publishes_by_type() {
+        let bus = EventBus::new();
+        let score = Arc::new(AtomicU32::new(0));
+        {
+            let score = score.clone();
+            bus.subscribe(
+                FnSubscriber::new(move |event: &PlayerDied| {
+                    score.fetch_add(event.score, Ordering::SeqCst);
+                    Subscription::Keep
+                })
+                .boxed(),
+            );
+        }
+        bus.subscribe(FnSubscriber::once(|_: &LevelLoaded| {}).boxed());
+
+        bus.publish(&LevelLoaded);
+        bus.publish(&PlayerDied { score: 2 });
+        bus.publish(&PlayerDied { score: 40 });
+        assert_eq!(score.load(Ordering::SeqCst), 42);
+
+        bus.maintain();
+        assert_eq!(bus.subscriber_count::<PlayerDied>(), 1);
+        assert_eq!(bus.subscriber_count::<LevelLoaded>(), 0);
+    }
+}
@@ -1,23 +1,72 @@
-use super::{KeyCode, MouseButton};
+use serde::{Deserialize, Serialize};
+
+use super::{KeyCode, ModifiersState, MouseButton, TouchPhase};
 
 /// Data for mouse movement events
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MouseMoveData {
-    /// Current mouse position in window coordinates
+    /// Current mouse position in window coordinates (physical pixels)
     pub position: glam::Vec2,
+    /// Current mouse position in logical pixels, i.e. `position / scale_factor`
+    pub logical_position: glam::Vec2,
     /// Delta movement since last mouse event
     pub delta: glam::Vec2,
 }
 
+/// Data for touch events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TouchData {
+    /// Identifies the finger for as long as it touches the screen
+    pub id: u64,
+    /// Whether the touch started, moved, ended or was cancelled
+    pub phase: TouchPhase,
+    /// Touch position in window coordinates (physical pixels)
+    pub position: glam::Vec2,
+    /// Touch position in logical pixels, i.e. `position / scale_factor`
+    pub logical_position: glam::Vec2,
+}
+
+/// Data for scale factor change events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScaleFactorData {
+    /// The new ratio of physical to logical pixels
+    pub scale_factor: f64,
+}
+
+/// Data for window resize events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResizeData {
+    /// New width of the surface in physical pixels
+    pub width: u32,
+    /// New height of the surface in physical pixels
+    pub height: u32,
+    /// New size in logical pixels, i.e. the physical size / scale_factor
+    pub logical_size: glam::Vec2,
+}
+
+/// Data for window focus events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FocusData {
+    /// True if the window gained focus, false if it lost it
+    pub focused: bool,
+}
+
+/// Data for suspend and resume events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuspendData {
+    /// True if the app was suspended, false if it was resumed
+    pub suspended: bool,
+}
+
 /// Data for mouse wheel scroll events
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MouseWheelData {
     /// Scroll delta (positive = scroll up/right)
     pub delta: glam::Vec2,
 }
 
 /// Data for mouse button press/release events
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MouseButtonData {
     /// Which mouse button was affected
     pub button: MouseButton,
@@ -26,12 +75,158 @@ pub struct MouseButtonData {
 }
 
 /// Data for keyboard press/release events
-#[derive(Debug, Clone)]
+///
+/// ```ignore
+/// if data.is_pressed && data.key_code == KeyCode::KeyS && data.modifiers.control_key() {
+///     save();
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KeyboardData {
-    /// The key that was pressed/released
+    /// The physical key that was pressed/released
+    ///
+    /// Named after the key at that position on a US layout, so `KeyW` is
+    /// the same key on AZERTY where it is labeled Z.
     pub key_code: KeyCode,
     /// True if pressed, false if released
     pub is_pressed: bool,
     /// True if this is a repeat event from holding the key
     pub is_repeat: bool,
+    /// Modifier keys held when the key was pressed/released
+    #[serde(default)]
+    pub modifiers: ModifiersState,
+    /// Platform specific scancode of the key
+    ///
+    /// Only reported on Windows, macOS, Linux and the BSDs.
+    #[serde(default)]
+    pub scancode: Option<u32>,
+}
+
+/// Data for text input events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextInputData {
+    /// The typed or composed text
+    pub text: String,
+    /// True while an IME composition is in progress
+    ///
+    /// The text then replaces the previously published preedit text and is
+    /// not final yet; an empty text ends the composition. Committed text
+    /// follows with `is_preedit` false.
+    pub is_preedit: bool,
+}
+
+/// Data for device lost events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceLostData {
+    /// Why the device was lost, as reported by wgpu
+    pub reason: String,
+    /// Driver or backend message describing the loss
+    pub message: String,
+}
+
+/// A gamepad button, named after its position on the controller
+///
+/// `South` is A on Xbox and Cross on PlayStation controllers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GamepadButton {
+    South,
+    East,
+    West,
+    North,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    LeftStick,
+    RightStick,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+    Mode,
+    /// A button without a standard meaning, by index
+    Other(u32),
+}
+impl GamepadButton {
+    /// Returns the button at `index` of the W3C standard gamepad mapping
+    pub fn from_standard_index(index: u32) -> Self {
+        const BUTTONS: [GamepadButton; 17] = [
+            GamepadButton::South,
+            GamepadButton::East,
+            GamepadButton::West,
+            GamepadButton::North,
+            GamepadButton::LeftBumper,
+            GamepadButton::RightBumper,
+            GamepadButton::LeftTrigger,
+            GamepadButton::RightTrigger,
+            GamepadButton::Select,
+            GamepadButton::Start,
+            GamepadButton::LeftStick,
+            GamepadButton::RightStick,
+            GamepadButton::DPadUp,
+            GamepadButton::DPadDown,
+            GamepadButton::DPadLeft,
+            GamepadButton::DPadRight,
+            GamepadButton::Mode,
+        ];
+        BUTTONS
+            .get(index as usize)
+            .copied()
+            .unwrap_or(Self::Other(index))
+    }
+}
+
+/// A gamepad axis
+///
+/// Stick axes range from -1.0 to 1.0, with positive values pointing
+/// right and down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    /// An axis without a standard meaning, by index
+    Other(u32),
+}
+impl GamepadAxis {
+    /// Returns the axis at `index` of the W3C standard gamepad mapping
+    pub fn from_standard_index(index: u32) -> Self {
+        match index {
+            0 => Self::LeftStickX,
+            1 => Self::LeftStickY,
+            2 => Self::RightStickX,
+            3 => Self::RightStickY,
+            _ => Self::Other(index),
+        }
+    }
+}
+
+/// What happened to a gamepad
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum GamepadEvent {
+    /// The gamepad was connected, or was already connected at startup
+    Connected,
+    /// The gamepad was disconnected
+    Disconnected,
+    /// A button was pressed, released or, for analog buttons, moved
+    Button {
+        button: GamepadButton,
+        /// How far the button is pressed, from 0.0 to 1.0
+        value: f32,
+        is_pressed: bool,
+    },
+    /// An axis moved
+    Axis { axis: GamepadAxis, value: f32 },
+}
+
+/// Data for gamepad events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GamepadData {
+    /// Identifies the gamepad while it is connected
+    pub gamepad_id: usize,
+    /// What happened
+    pub event: GamepadEvent,
 }
@@ -0,0 +1,106 @@
+//! Gamepad polling through the browser Gamepad API
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+
+use wasm_bindgen::JsCast;
+
+use super::{EVENTS, GamepadAxis, GamepadButton, GamepadData, GamepadEvent};
+
+/// Last published button and axis values of a connected gamepad
+#[derive(Default)]
+struct PadState {
+    buttons: Vec<(f32, bool)>,
+    axes: Vec<f32>,
+}
+
+thread_local! {
+    static PADS: RefCell<HashMap<u32, PadState>> = RefCell::new(HashMap::new());
+}
+
+/// Publishes the changes of all gamepads since the last call
+///
+/// The Gamepad API has no change events for buttons and axes, so this
+/// compares against the previous state once per frame.
+pub(crate) fn poll_gamepads() {
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+    let Ok(pads) = window.navigator().get_gamepads() else {
+        return;
+    };
+    let mut events = Vec::new();
+    PADS.with_borrow_mut(|known| {
+        let mut seen = HashSet::new();
+        // Disconnected slots are null
+        for pad in pads
+            .iter()
+            .filter_map(|p| p.dyn_into::<web_sys::Gamepad>().ok())
+        {
+            if !pad.connected() {
+                continue;
+            }
+            let id = pad.index();
+            seen.insert(id);
+            let gamepad_id = id as usize;
+            let state = known.entry(id).or_insert_with(|| {
+                events.push(GamepadData {
+                    gamepad_id,
+                    event: GamepadEvent::Connected,
+                });
+                PadState::default()
+            });
+            let buttons = pad
+                .buttons()
+                .iter()
+                .filter_map(|b| b.dyn_into::<web_sys::GamepadButton>().ok());
+            for (i, button) in buttons.enumerate() {
+                let current = (button.value() as f32, button.pressed());
+                if state.buttons.len() <= i {
+                    state.buttons.push((0.0, false));
+                }
+                if state.buttons[i] != current {
+                    state.buttons[i] = current;
+                    events.push(GamepadData {
+                        gamepad_id,
+                        event: GamepadEvent::Button {
+                            button: GamepadButton::from_standard_index(i as u32),
+                            value: current.0,
+                            is_pressed: current.1,
+                        },
+                    });
+                }
+            }
+            for (i, axis) in pad.axes().iter().enumerate() {
+                let value = axis.as_f64().unwrap_or_default() as f32;
+                if state.axes.len() <= i {
+                    state.axes.push(0.0);
+                }
+                if state.axes[i] != value {
+                    state.axes[i] = value;
+                    events.push(GamepadData {
+                        gamepad_id,
+                        event: GamepadEvent::Axis {
+                            axis: GamepadAxis::from_standard_index(i as u32),
+                            value,
+                        },
+                    });
+                }
+            }
+        }
+        known.retain(|id, _| {
+            let connected = seen.contains(id);
+            if !connected {
+                events.push(GamepadData {
+                    gamepad_id: *id as usize,
+                    event: GamepadEvent::Disconnected,
+                });
+            }
+            connected
+        });
+    });
+    // Publish after releasing the state so subscribers may poll again
+    for data in events {
+        EVENTS.gamepad().notify(&data);
+    }
+}
@@ -0,0 +1,171 @@
+//! Gamepad polling through gilrs on desktop platforms
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use super::{EVENTS, GamepadAxis, GamepadButton, GamepadData, GamepadEvent};
+
+/// The gilrs context and the last published button states of every gamepad
+struct Backend {
+    gilrs: gilrs::Gilrs,
+    buttons: HashMap<usize, HashMap<GamepadButton, (f32, bool)>>,
+}
+
+thread_local! {
+    /// `None` until the first poll, then `Some(None)` if gilrs is unavailable
+    static BACKEND: RefCell<Option<Option<Backend>>> = const { RefCell::new(None) };
+}
+
+/// Creates the gilrs context, reporting the gamepads connected at startup
+fn create_backend(events: &mut Vec<GamepadData>) -> Option<Backend> {
+    let gilrs = match gilrs::Gilrs::new() {
+        Ok(gilrs) => gilrs,
+        Err(gilrs::Error::NotImplemented(gilrs)) => {
+            log::warn!("Gamepads are not supported on this platform");
+            gilrs
+        }
+        Err(e) => {
+            log::error!("Unable to initialize gamepad support: {e}");
+            return None;
+        }
+    };
+    let mut buttons = HashMap::new();
+    for (id, _) in gilrs.gamepads() {
+        let gamepad_id = usize::from(id);
+        buttons.insert(gamepad_id, HashMap::new());
+        events.push(GamepadData {
+            gamepad_id,
+            event: GamepadEvent::Connected,
+        });
+    }
+    Some(Backend { gilrs, buttons })
+}
+
+/// Publishes the gamepad events received since the last call
+pub(crate) fn poll_gamepads() {
+    let mut events = Vec::new();
+    BACKEND.with_borrow_mut(|backend| {
+        let Some(backend) = backend.get_or_insert_with(|| create_backend(&mut events)) else {
+            return;
+        };
+        while let Some(gilrs::Event { id, event, .. }) = backend.gilrs.next_event() {
+            let gamepad_id = usize::from(id);
+            let event = match event {
+                gilrs::EventType::Connected => {
+                    // Gamepads reported at startup may be announced again
+                    if backend.buttons.insert(gamepad_id, HashMap::new()).is_some() {
+                        continue;
+                    }
+                    GamepadEvent::Connected
+                }
+                gilrs::EventType::Disconnected => {
+                    backend.buttons.remove(&gamepad_id);
+                    GamepadEvent::Disconnected
+                }
+                gilrs::EventType::ButtonPressed(button, code)
+                | gilrs::EventType::ButtonReleased(button, code)
+                | gilrs::EventType::ButtonChanged(button, _, code) => {
+                    let button = map_button(button, code.into_u32());
+                    let state = backend
+                        .buttons
+                        .entry(gamepad_id)
+                        .or_default()
+                        .entry(button)
+                        .or_default();
+                    let previous = *state;
+                    match event {
+                        gilrs::EventType::ButtonPressed(..) => state.1 = true,
+                        gilrs::EventType::ButtonReleased(..) => state.1 = false,
+                        gilrs::EventType::ButtonChanged(_, value, _) => state.0 = value,
+                        _ => unreachable!(),
+                    }
+                    if *state == previous {
+                        continue;
+                    }
+                    GamepadEvent::Button {
+                        button,
+                        value: state.0,
+                        is_pressed: state.1,
+                    }
+                }
+                gilrs::EventType::AxisChanged(axis, value, code) => {
+                    let (axis, value) = map_axis(axis, value, code.into_u32());
+                    GamepadEvent::Axis { axis, value }
+                }
+                _ => continue,
+            };
+            events.push(GamepadData { gamepad_id, event });
+        }
+    });
+    // Publish after releasing the backend so subscribers may poll again
+    for data in events {
+        EVENTS.gamepad().notify(&data);
+    }
+}
+
+/// Maps a gilrs button to the W3C standard layout, `code` for unknown buttons
+fn map_button(button: gilrs::Button, code: u32) -> GamepadButton {
+    use gilrs::Button as B;
+
+    match button {
+        B::South => GamepadButton::South,
+        B::East => GamepadButton::East,
+        B::West => GamepadButton::West,
+        B::North => GamepadButton::North,
+        // gilrs calls the bumpers the first pair of triggers
+        B::LeftTrigger => GamepadButton::LeftBumper,
+        B::RightTrigger => GamepadButton::RightBumper,
+        B::LeftTrigger2 => GamepadButton::LeftTrigger,
+        B::RightTrigger2 => GamepadButton::RightTrigger,
+        B::Select => GamepadButton::Select,
+        B::Start => GamepadButton::Start,
+        B::LeftThumb => GamepadButton::LeftStick,
+        B::RightThumb => GamepadButton::RightStick,
+        B::DPadUp => GamepadButton::DPadUp,
+        B::DPadDown => GamepadButton::DPadDown,
+        B::DPadLeft => GamepadButton::DPadLeft,
+        B::DPadRight => GamepadButton::DPadRight,
+        B::Mode => GamepadButton::Mode,
+        B::C | B::Z | B::Unknown => GamepadButton::Other(code),
+    }
+}
+
+/// Maps a gilrs axis to the W3C standard layout, where Y points down
+fn map_axis(axis: gilrs::Axis, value: f32, code: u32) -> (GamepadAxis, f32) {
+    use gilrs::Axis as A;
+
+    match axis {
+        A::LeftStickX => (GamepadAxis::LeftStickX, value),
+        A::LeftStickY => (GamepadAxis::LeftStickY, -value),
+        A::RightStickX => (GamepadAxis::RightStickX, value),
+        A::RightStickY => (GamepadAxis::RightStickY, -value),
+        A::LeftZ | A::RightZ | A::DPadX | A::DPadY | A::Unknown => {
+            (GamepadAxis::Other(code), value)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn maps_to_standard_layout() {
+        assert_eq!(
+            map_button(gilrs::Button::LeftTrigger, 0),
+            GamepadButton::LeftBumper
+        );
+        assert_eq!(
+            map_button(gilrs::Button::RightTrigger2, 0),
+            GamepadButton::RightTrigger
+        );
+        assert_eq!(
+            map_button(gilrs::Button::Unknown, 7),
+            GamepadButton::Other(7)
+        );
+        assert_eq!(
+            map_axis(gilrs::Axis::LeftStickY, 0.5, 0),
+            (GamepadAxis::LeftStickY, -0.5)
+        );
+    }
+}
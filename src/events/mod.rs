@@ -7,13 +7,31 @@ use std::sync::LazyLock;
 
 use parking_lot::{Mutex, MutexGuard};
 
-pub use winit::event::MouseButton;
-pub use winit::keyboard::KeyCode;
+pub use winit::event::{MouseButton, TouchPhase};
+pub use winit::keyboard::{KeyCode, ModifiersState};
 
 use crate::observer::{FnSubscriber, Priority, Publisher, Subscriber, Subscription};
 
+mod bus;
 mod data;
+#[cfg(target_arch = "wasm32")]
+mod gamepad;
+#[cfg(all(
+    feature = "gamepad",
+    not(any(target_arch = "wasm32", target_os = "android", target_os = "ios"))
+))]
+mod gamepad_native;
+mod record;
+pub use bus::*;
 pub use data::*;
+#[cfg(target_arch = "wasm32")]
+pub(crate) use gamepad::*;
+#[cfg(all(
+    feature = "gamepad",
+    not(any(target_arch = "wasm32", target_os = "android", target_os = "ios"))
+))]
+pub(crate) use gamepad_native::*;
+pub use record::*;
 
 /// Global event system instance
 ///
@@ -21,6 +39,7 @@ pub use data::*;
 /// Publishers allow subscribing to various input and lifecycle events.
 pub static EVENTS: LazyLock<Events> = LazyLock::new(|| {
     let events = Events {
+        ready: Mutex::new(Publisher::new()),
         start_of_frame: Mutex::new(Publisher::new()),
         update: Mutex::new(Publisher::new()),
         mouse_move: Mutex::new(Publisher::new()),
@@ -28,7 +47,19 @@ pub static EVENTS: LazyLock<Events> = LazyLock::new(|| {
         mouse_button: Mutex::new(Publisher::new()),
         keyboard: Mutex::new(Publisher::new()),
         end_of_frame: Mutex::new(Publisher::new()),
+        scale_factor_changed: Mutex::new(Publisher::new()),
+        device_lost: Mutex::new(Publisher::new()),
+        gamepad: Mutex::new(Publisher::new()),
+        resize: Mutex::new(Publisher::new()),
+        focus: Mutex::new(Publisher::new()),
+        suspend: Mutex::new(Publisher::new()),
+        close_requested: Mutex::new(Publisher::new()),
+        touch: Mutex::new(Publisher::new()),
+        text_input: Mutex::new(Publisher::new()),
+        bus: EventBus::new(),
         last_mouse_position: Mutex::new(None),
+        scale_factor: Mutex::new(1.0),
+        modifiers: Mutex::new(ModifiersState::empty()),
     };
     events.init();
     events
@@ -44,6 +75,8 @@ type GuardEventPublisher<'a, Data> = MutexGuard<'a, EventPublisher<Data>>;
 /// This struct provides access to publishers for various application events
 /// including input events and frame lifecycle events.
 pub struct Events {
+    /// Published once when the graphics state has been created
+    ready: MutEventPublisher<()>,
     /// Published at the start of each frame
     start_of_frame: MutEventPublisher<()>,
     /// Published during the update phase of each frame
@@ -58,9 +91,33 @@ pub struct Events {
     keyboard: MutEventPublisher<KeyboardData>,
     /// Published at the end of each frame
     end_of_frame: MutEventPublisher<()>,
+    /// Published when the window's scale factor changes
+    scale_factor_changed: MutEventPublisher<ScaleFactorData>,
+    /// Published after the device was lost and recreated
+    device_lost: MutEventPublisher<DeviceLostData>,
+    /// Published when a gamepad connects, disconnects or changes
+    gamepad: MutEventPublisher<GamepadData>,
+    /// Published after the surface was resized
+    resize: MutEventPublisher<ResizeData>,
+    /// Published when the window gains or loses focus
+    focus: MutEventPublisher<FocusData>,
+    /// Published when the app is suspended or resumed
+    suspend: MutEventPublisher<SuspendData>,
+    /// Published when the user asks to close the window
+    close_requested: MutEventPublisher<()>,
+    /// Published when a finger touches, moves on or leaves the screen
+    touch: MutEventPublisher<TouchData>,
+    /// Published when text is typed or composed with an IME
+    text_input: MutEventPublisher<TextInputData>,
+    /// Publishers of user defined event types
+    bus: EventBus,
 
     /// Cached last mouse position for delta calculation
     last_mouse_position: Mutex<Option<glam::Vec2>>,
+    /// Cached scale factor of the window
+    scale_factor: Mutex<f64>,
+    /// Cached modifier keys held in the window
+    modifiers: Mutex<ModifiersState>,
 }
 impl Events {
     /// Initializes the event system with necessary subscriptions
@@ -80,6 +137,7 @@ impl Events {
         );
     }
     pub(crate) fn maintain(&self) {
+        self.ready().maintain();
         self.start_of_frame().maintain();
         self.update().maintain();
         self.mouse_move().maintain();
@@ -87,6 +145,39 @@ impl Events {
         self.mouse_button().maintain();
         self.keyboard().maintain();
         self.end_of_frame().maintain();
+        self.scale_factor_changed().maintain();
+        self.device_lost().maintain();
+        self.gamepad().maintain();
+        self.resize().maintain();
+        self.focus().maintain();
+        self.suspend().maintain();
+        self.close_requested().maintain();
+        self.touch().maintain();
+        self.text_input().maintain();
+        self.bus.maintain();
+    }
+    /// Enables or disables panic isolation for all event publishers
+    ///
+    /// See [`Publisher::set_catch_panics`] for the tradeoffs involved.
+    pub fn set_catch_panics(&self, enabled: bool) {
+        self.ready().set_catch_panics(enabled);
+        self.start_of_frame().set_catch_panics(enabled);
+        self.update().set_catch_panics(enabled);
+        self.mouse_move().set_catch_panics(enabled);
+        self.mouse_wheel().set_catch_panics(enabled);
+        self.mouse_button().set_catch_panics(enabled);
+        self.keyboard().set_catch_panics(enabled);
+        self.end_of_frame().set_catch_panics(enabled);
+        self.scale_factor_changed().set_catch_panics(enabled);
+        self.device_lost().set_catch_panics(enabled);
+        self.gamepad().set_catch_panics(enabled);
+        self.resize().set_catch_panics(enabled);
+        self.focus().set_catch_panics(enabled);
+        self.suspend().set_catch_panics(enabled);
+        self.close_requested().set_catch_panics(enabled);
+        self.touch().set_catch_panics(enabled);
+        self.text_input().set_catch_panics(enabled);
+        self.bus.set_catch_panics(enabled);
     }
     /// Returns the last known mouse position
     ///
@@ -94,6 +185,35 @@ impl Events {
     pub(crate) fn last_mouse_position(&self) -> glam::Vec2 {
         self.last_mouse_position.lock().unwrap_or(glam::Vec2::ZERO)
     }
+    /// Returns the ratio of physical to logical pixels of the window
+    ///
+    /// Returns 1.0 until the window has been created.
+    pub fn scale_factor(&self) -> f64 {
+        *self.scale_factor.lock()
+    }
+    /// Returns the modifier keys currently held
+    ///
+    /// Empty while the window is unfocused.
+    pub fn modifiers(&self) -> ModifiersState {
+        *self.modifiers.lock()
+    }
+    pub(crate) fn set_modifiers(&self, modifiers: ModifiersState) {
+        *self.modifiers.lock() = modifiers;
+    }
+    pub(crate) fn set_scale_factor(&self, scale_factor: f64) {
+        *self.scale_factor.lock() = scale_factor;
+    }
+
+    /// Returns the ready event publisher
+    ///
+    /// Published exactly once, right after the window and graphics state
+    /// have been created, so `app().state()` is guaranteed to be `Some` in
+    /// its subscribers. It fires before the first frame and therefore before
+    /// `AppClient::init`; subscribe before running the app, or check
+    /// `App::is_ready` when subscribing later.
+    pub fn ready(&self) -> GuardEventPublisher<'_, ()> {
+        self.ready.lock()
+    }
 
     /// Returns the start of frame event publisher
     pub fn start_of_frame(&self) -> GuardEventPublisher<'_, ()> {
@@ -129,4 +249,100 @@ impl Events {
     pub fn end_of_frame(&self) -> GuardEventPublisher<'_, ()> {
         self.end_of_frame.lock()
     }
+
+    /// Returns the scale factor changed event publisher
+    ///
+    /// The cached [`Events::scale_factor`] is already updated when this is
+    /// published. A resize of the window usually follows.
+    pub fn scale_factor_changed(&self) -> GuardEventPublisher<'_, ScaleFactorData> {
+        self.scale_factor_changed.lock()
+    }
+
+    /// Returns the device lost event publisher
+    ///
+    /// Published after the graphics device was lost, e.g. by a driver reset,
+    /// and `GfxState::recreate_device` has rebuilt it. All GPU resources the
+    /// client created from the old device are invalid and must be recreated
+    /// from `app().state()` in the subscriber. If the device cannot be
+    /// recreated the app exits with code 1 instead.
+    pub fn device_lost(&self) -> GuardEventPublisher<'_, DeviceLostData> {
+        self.device_lost.lock()
+    }
+
+    /// Returns the gamepad event publisher
+    ///
+    /// Gamepads are polled at the start of every frame: through gilrs on
+    /// desktop platforms with the default `gamepad` feature, and through
+    /// the Gamepad API on the web, where browsers only report a gamepad
+    /// after one of its buttons was pressed. Gamepads connected at startup
+    /// are reported as connected on the first frame. Elsewhere, publish
+    /// `GamepadData` from another library to feed subscribers.
+    pub fn gamepad(&self) -> GuardEventPublisher<'_, GamepadData> {
+        self.gamepad.lock()
+    }
+
+    /// Returns the resize event publisher
+    ///
+    /// Published after the surface was reconfigured to the new window size,
+    /// without holding the state lock, so subscribers can recreate size
+    /// dependent resources from `app().state()`. Minimizing the window to a
+    /// zero size is not published.
+    pub fn resize(&self) -> GuardEventPublisher<'_, ResizeData> {
+        self.resize.lock()
+    }
+
+    /// Returns the focus event publisher
+    ///
+    /// `App::is_focused` is already updated when this is published.
+    pub fn focus(&self) -> GuardEventPublisher<'_, FocusData> {
+        self.focus.lock()
+    }
+
+    /// Returns the suspend event publisher
+    ///
+    /// Published right after `AppClient::on_suspend` and `AppClient::on_resume`,
+    /// with `App::is_suspended` already updated. Nothing is rendered while
+    /// suspended, and the surface has been recreated when resuming.
+    pub fn suspend(&self) -> GuardEventPublisher<'_, SuspendData> {
+        self.suspend.lock()
+    }
+
+    /// Returns the close requested event publisher
+    ///
+    /// Published when the user clicks the close button of the window or
+    /// similar. The app exits unless canceled: a subscriber can call
+    /// `App::cancel_close` to keep it running, e.g. to ask about unsaved
+    /// changes first.
+    pub fn close_requested(&self) -> GuardEventPublisher<'_, ()> {
+        self.close_requested.lock()
+    }
+
+    /// Returns the touch event publisher
+    ///
+    /// Every finger has its own id, so multi-touch gestures can be tracked
+    /// by id. `INPUT.touches()` holds the positions of all fingers currently
+    /// on the screen.
+    pub fn touch(&self) -> GuardEventPublisher<'_, TouchData> {
+        self.touch.lock()
+    }
+
+    /// Returns the text input event publisher
+    ///
+    /// Carries the characters produced by key presses, after the keyboard
+    /// layout, modifiers and dead keys were applied, e.g. `"@"` for
+    /// Shift+2 on a US layout. Control characters such as backspace and
+    /// enter are left out; use the keyboard event for those. Composing
+    /// text with an IME, e.g. for Chinese or Japanese, additionally
+    /// requires `window::set_ime_allowed(true)`.
+    pub fn text_input(&self) -> GuardEventPublisher<'_, TextInputData> {
+        self.text_input.lock()
+    }
+
+    /// Returns the bus of user defined events
+    ///
+    /// Unlike the other publishers it is not locked as a whole, so events
+    /// of one type can be published from subscribers of another.
+    pub fn bus(&self) -> &EventBus {
+        &self.bus
+    }
 }
@@ -0,0 +1,287 @@
+//! Recording and replaying of input events
+//!
+//! A `Recorder` captures the input events published on `EVENTS` together
+//! with the frame they occurred in. The resulting `Recording` can be
+//! serialized to JSON and later re-injected with a `Player`, which publishes
+//! every event again at the same frame offset. Replaying by frame rather than
+//! by wall-clock time keeps the replay deterministic regardless of frame rate.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    EVENTS, GamepadData, KeyboardData, MouseButtonData, MouseMoveData, MouseWheelData,
+    ScaleFactorData, TextInputData, TouchData,
+};
+use crate::TIME;
+use crate::observer::{FnSubscriber, Subscriber, Subscription};
+
+/// An input event captured by a `Recorder`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum RecordedEvent {
+    MouseMove(MouseMoveData),
+    MouseWheel(MouseWheelData),
+    MouseButton(MouseButtonData),
+    Keyboard(KeyboardData),
+    ScaleFactorChanged(ScaleFactorData),
+    Gamepad(GamepadData),
+    Touch(TouchData),
+    TextInput(TextInputData),
+}
+impl RecordedEvent {
+    /// Publishes the event on the matching `EVENTS` publisher
+    pub fn publish(&self) {
+        match self {
+            Self::MouseMove(data) => EVENTS.mouse_move().notify(data),
+            Self::MouseWheel(data) => EVENTS.mouse_wheel().notify(data),
+            Self::MouseButton(data) => EVENTS.mouse_button().notify(data),
+            Self::Keyboard(data) => EVENTS.keyboard().notify(data),
+            Self::ScaleFactorChanged(data) => {
+                EVENTS.set_scale_factor(data.scale_factor);
+                EVENTS.scale_factor_changed().notify(data)
+            }
+            Self::Gamepad(data) => EVENTS.gamepad().notify(data),
+            Self::Touch(data) => EVENTS.touch().notify(data),
+            Self::TextInput(data) => EVENTS.text_input().notify(data),
+        }
+    }
+}
+
+/// A recorded event together with when it happened
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimedEvent {
+    /// Frames since the recording started
+    pub frame: u64,
+    /// Seconds since the recording started
+    pub time: f32,
+    /// The event itself
+    pub event: RecordedEvent,
+}
+
+/// A sequence of recorded input events
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Recording {
+    /// The events in the order they were published
+    pub events: Vec<TimedEvent>,
+}
+impl Recording {
+    /// Serializes the recording to JSON
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+    /// Deserializes a recording from JSON
+    pub fn from_json(json: &str) -> anyhow::Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+    /// Saves the recording as JSON to `path`
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> anyhow::Result<()> {
+        std::fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+    /// Loads a recording saved with [`Recording::save`]
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: impl AsRef<std::path::Path>) -> anyhow::Result<Self> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+}
+
+/// Records all input events published on `EVENTS`
+///
+/// Recording starts when the recorder is created and ends with
+/// [`Recorder::finish`] or when it is dropped. The internal subscribers
+/// remove themselves with the next event after that.
+pub struct Recorder {
+    events: Arc<Mutex<Vec<TimedEvent>>>,
+    active: Arc<AtomicBool>,
+}
+impl Recorder {
+    /// Starts recording input events
+    pub fn start() -> Self {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let active = Arc::new(AtomicBool::new(true));
+        let start_frame = TIME.frame_count();
+        let start_time = TIME.running_time();
+
+        // Creates a subscriber appending events converted by `f`
+        fn record<T: Clone + Send + 'static>(
+            events: &Arc<Mutex<Vec<TimedEvent>>>,
+            active: &Arc<AtomicBool>,
+            start: (u64, f32),
+            f: fn(T) -> RecordedEvent,
+        ) -> Box<dyn Subscriber<Data = T>> {
+            let events = events.clone();
+            let active = active.clone();
+            FnSubscriber::new(move |data: &T| {
+                if !active.load(Ordering::Relaxed) {
+                    return Subscription::Unsubscribe;
+                }
+                events.lock().push(TimedEvent {
+                    frame: TIME.frame_count() - start.0,
+                    time: TIME.running_time() - start.1,
+                    event: f(data.clone()),
+                });
+                Subscription::Keep
+            })
+            .boxed()
+        }
+        let start = (start_frame, start_time);
+        EVENTS
+            .mouse_move()
+            .subscribe(record(&events, &active, start, RecordedEvent::MouseMove));
+        EVENTS
+            .mouse_wheel()
+            .subscribe(record(&events, &active, start, RecordedEvent::MouseWheel));
+        EVENTS.mouse_button().subscribe(record(
+            &events,
+            &active,
+            start,
+            RecordedEvent::MouseButton,
+        ));
+        EVENTS
+            .keyboard()
+            .subscribe(record(&events, &active, start, RecordedEvent::Keyboard));
+        EVENTS.scale_factor_changed().subscribe(record(
+            &events,
+            &active,
+            start,
+            RecordedEvent::ScaleFactorChanged,
+        ));
+        EVENTS
+            .gamepad()
+            .subscribe(record(&events, &active, start, RecordedEvent::Gamepad));
+        EVENTS
+            .touch()
+            .subscribe(record(&events, &active, start, RecordedEvent::Touch));
+        EVENTS
+            .text_input()
+            .subscribe(record(&events, &active, start, RecordedEvent::TextInput));
+        Self { events, active }
+    }
+    /// Returns the number of events recorded so far
+    pub fn len(&self) -> usize {
+        self.events.lock().len()
+    }
+    /// Returns `true` if no events have been recorded yet
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Stops recording and returns the recorded events
+    pub fn finish(self) -> Recording {
+        self.active.store(false, Ordering::Relaxed);
+        Recording {
+            events: std::mem::take(&mut *self.events.lock()),
+        }
+    }
+}
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        self.active.store(false, Ordering::Relaxed);
+    }
+}
+
+/// Events queued by players during `start_of_frame`, see [`publish_replayed_events`]
+static DUE_EVENTS: Mutex<Vec<RecordedEvent>> = Mutex::new(Vec::new());
+
+/// Publishes the events that became due for replay this frame
+///
+/// Called by the app loop right after `start_of_frame` was published, so
+/// replayed events are not published from within its subscribers.
+pub(crate) fn publish_replayed_events() {
+    let due = std::mem::take(&mut *DUE_EVENTS.lock());
+    for event in due {
+        event.publish();
+    }
+}
+
+/// Replays a `Recording` into the `EVENTS` publishers
+///
+/// Events are published at the start of the frame matching their recorded
+/// frame offset, counted from the frame [`Player::play`] was called in,
+/// right after `Events::start_of_frame`.
+pub struct Player {
+    finished: Arc<AtomicBool>,
+}
+impl Player {
+    /// Starts replaying `recording`
+    pub fn play(recording: Recording) -> Self {
+        let finished = Arc::new(AtomicBool::new(recording.events.is_empty()));
+        let start_frame = TIME.frame_count();
+        let events = Mutex::new(recording.events.into_iter().peekable());
+        {
+            let finished = finished.clone();
+            EVENTS.start_of_frame().subscribe(
+                FnSubscriber::new(move |_: &()| {
+                    if finished.load(Ordering::Relaxed) {
+                        return Subscription::Unsubscribe;
+                    }
+                    let frame = TIME.frame_count() - start_frame;
+                    let mut events = events.lock();
+                    let mut due = DUE_EVENTS.lock();
+                    while let Some(timed) = events.next_if(|e| e.frame <= frame) {
+                        due.push(timed.event);
+                    }
+                    if events.peek().is_none() {
+                        finished.store(true, Ordering::Relaxed);
+                        return Subscription::Unsubscribe;
+                    }
+                    Subscription::Keep
+                })
+                .boxed(),
+            );
+        }
+        Self { finished }
+    }
+    /// Returns `true` once all events have been published
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::Relaxed)
+    }
+    /// Stops the replay, dropping the remaining events
+    pub fn stop(&self) {
+        self.finished.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn recording_json_round_trip() {
+        let recording = Recording {
+            events: vec![TimedEvent {
+                frame: 3,
+                time: 0.05,
+                event: RecordedEvent::Keyboard(KeyboardData {
+                    key_code: super::super::KeyCode::KeyW,
+                    is_pressed: true,
+                    is_repeat: false,
+                    modifiers: super::super::ModifiersState::CONTROL,
+                    scancode: None,
+                }),
+            }],
+        };
+        let loaded = Recording::from_json(&recording.to_json().unwrap()).unwrap();
+        assert_eq!(loaded.events.len(), 1);
+        assert_eq!(loaded.events[0].frame, 3);
+        let RecordedEvent::Keyboard(data) = &loaded.events[0].event else {
+            panic!("Wrong event type");
+        };
+        assert_eq!(data.key_code, super::super::KeyCode::KeyW);
+        assert!(data.modifiers.control_key());
+    }
+
+    #[test]
+    fn keyboard_without_modifiers_loads() {
+        let json = r#"{"events":[{"frame":0,"time":0.0,"event":{"Keyboard":{"key_code":"KeyA","is_pressed":true,"is_repeat":false}}}]}"#;
+        let loaded = Recording::from_json(json).unwrap();
+        let RecordedEvent::Keyboard(data) = &loaded.events[0].event else {
+            panic!("Wrong event type");
+        };
+        assert!(data.modifiers.is_empty());
+        assert_eq!(data.scancode, None);
+    }
+}
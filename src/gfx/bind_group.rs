@@ -0,0 +1,130 @@
+//! Bind group helpers
+
+use crate::gfx::Sampler;
+
+/// Builds a bind group together with its layout
+///
+/// Every entry is added to the layout and the group at once, with binding
+/// indices assigned sequentially from 0, so the two can't drift apart.
+///
+/// ```ignore
+/// let (layout, group) = gfx::BindGroupBuilder::new()
+///     .label("Material")
+///     .uniform(&params, wgpu::ShaderStages::FRAGMENT)
+///     .textured(texture.view(), &sampler, wgpu::ShaderStages::FRAGMENT)
+///     .build(&state.device);
+/// ```
+#[derive(Default)]
+pub struct BindGroupBuilder<'a> {
+    label: Option<&'a str>,
+    layout_entries: Vec<wgpu::BindGroupLayoutEntry>,
+    entries: Vec<wgpu::BindGroupEntry<'a>>,
+}
+impl<'a> BindGroupBuilder<'a> {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Sets the debug label of the layout and the group
+    pub fn label(mut self, label: &'a str) -> Self {
+        self.label = Some(label);
+        self
+    }
+    /// Adds an entry with an arbitrary binding type at the next binding index
+    pub fn entry(
+        mut self,
+        ty: wgpu::BindingType,
+        resource: wgpu::BindingResource<'a>,
+        visibility: wgpu::ShaderStages,
+    ) -> Self {
+        let binding = self.entries.len() as u32;
+        self.layout_entries.push(wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility,
+            ty,
+            count: None,
+        });
+        self.entries
+            .push(wgpu::BindGroupEntry { binding, resource });
+        self
+    }
+    /// Adds a whole buffer as a uniform buffer binding
+    pub fn uniform(self, buffer: &'a wgpu::Buffer, visibility: wgpu::ShaderStages) -> Self {
+        self.entry(
+            wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            buffer.as_entire_binding(),
+            visibility,
+        )
+    }
+    /// Adds a whole buffer as a storage buffer binding
+    pub fn storage(
+        self,
+        buffer: &'a wgpu::Buffer,
+        read_only: bool,
+        visibility: wgpu::ShaderStages,
+    ) -> Self {
+        self.entry(
+            wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            buffer.as_entire_binding(),
+            visibility,
+        )
+    }
+    /// Adds a filterable float 2D texture binding
+    ///
+    /// Use [`BindGroupBuilder::entry`] for other sample types or dimensions.
+    pub fn texture(self, view: &'a wgpu::TextureView, visibility: wgpu::ShaderStages) -> Self {
+        self.entry(
+            wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            wgpu::BindingResource::TextureView(view),
+            visibility,
+        )
+    }
+    /// Adds a filtering sampler binding
+    pub fn sampler(self, sampler: &'a wgpu::Sampler, visibility: wgpu::ShaderStages) -> Self {
+        self.entry(
+            wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+            wgpu::BindingResource::Sampler(sampler),
+            visibility,
+        )
+    }
+    /// Adds a filterable float 2D texture followed by the sampler reading it
+    ///
+    /// The sampler binding type follows [`Sampler::binding_type`].
+    pub fn textured(
+        self,
+        view: &'a wgpu::TextureView,
+        sampler: &'a Sampler,
+        visibility: wgpu::ShaderStages,
+    ) -> Self {
+        self.texture(view, visibility).entry(
+            wgpu::BindingType::Sampler(sampler.binding_type()),
+            wgpu::BindingResource::Sampler(sampler),
+            visibility,
+        )
+    }
+    /// Creates the layout and a bind group using it
+    pub fn build(self, device: &wgpu::Device) -> (wgpu::BindGroupLayout, wgpu::BindGroup) {
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: self.label,
+            entries: &self.layout_entries,
+        });
+        let group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: self.label,
+            layout: &layout,
+            entries: &self.entries,
+        });
+        (layout, group)
+    }
+}
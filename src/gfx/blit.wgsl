@@ -0,0 +1,29 @@
+// Fullscreen blit shader
+//
+// Draws a single triangle covering the whole viewport and samples
+// the source texture with normalized coordinates.
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) tex_coords: vec2f,
+};
+
+@vertex
+fn vs_fullscreen(@builtin(vertex_index) index: u32) -> VertexOutput {
+    // Vertices at (-1, -1), (3, -1) and (-1, 3) cover the viewport
+    let uv = vec2f(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: VertexOutput;
+    out.clip_position = vec4f(uv * 2.0 - 1.0, 0.0, 1.0);
+    out.tex_coords = vec2f(uv.x, 1.0 - uv.y);
+    return out;
+}
+
+@group(0) @binding(0)
+var t_source: texture_2d<f32>;
+@group(0) @binding(1)
+var s_source: sampler;
+
+@fragment
+fn fs_blit(in: VertexOutput) -> @location(0) vec4f {
+    return textureSample(t_source, s_source, in.tex_coords);
+}
@@ -64,4 +64,43 @@ impl<T: bytemuck::Pod + bytemuck::Zeroable> BytemuckBuffer<T> {
     pub fn count(&self) -> u32 {
         (self.size() / std::mem::size_of::<T>() as wgpu::BufferAddress) as u32
     }
+    /// Copies the buffer back to the CPU
+    ///
+    /// The buffer must have `COPY_SRC` usage. Waits for all submitted work,
+    /// so results of a compute pass are visible. A staging buffer is created
+    /// on every call.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn read(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> anyhow::Result<Vec<T>> {
+        pollster::block_on(self.read_async(device, queue))
+    }
+    /// Async variant of [`BytemuckBuffer::read`], available on all targets
+    pub async fn read_async(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> anyhow::Result<Vec<T>> {
+        let bytes = crate::gfx::read_buffer_async(device, queue, &self.buf).await?;
+        Ok(Self::decode(&bytes))
+    }
+    /// Copies `bytes` into a correctly aligned `Vec`, dropping a trailing partial element
+    fn decode(bytes: &[u8]) -> Vec<T> {
+        let size = std::mem::size_of::<T>();
+        let count = bytes.len() / size;
+        let mut data = vec![T::zeroed(); count];
+        bytemuck::cast_slice_mut(&mut data).copy_from_slice(&bytes[..count * size]);
+        data
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn decode_unaligned_bytes() {
+        let values = [1u32, 0xdead_beef, 7];
+        let mut bytes = vec![0u8];
+        bytes.extend_from_slice(bytemuck::cast_slice(&values));
+        assert_eq!(BytemuckBuffer::<u32>::decode(&bytes[1..]), values);
+    }
 }
@@ -0,0 +1,191 @@
+use crate::gfx::BytemuckBuffer;
+
+/// A `BytemuckBuffer` that grows when written past its capacity
+///
+/// Growing allocates a new `wgpu::Buffer` of at least twice the capacity and
+/// copies the old contents over on the GPU. Bind groups referencing the old
+/// buffer must then be rebuilt; compare [`DynamicBuffer::generation`] with
+/// the generation they were created at to find out.
+///
+/// ```ignore
+/// if particles.write(&state.device, &state.queue, 0, &data) {
+///     bind_group = make_bind_group(&particles);
+/// }
+/// ```
+#[derive(educe::Educe)]
+#[educe(Deref)]
+pub struct DynamicBuffer<T: bytemuck::Pod + bytemuck::Zeroable> {
+    #[educe(Deref)]
+    buf: BytemuckBuffer<T>,
+    len: u64,
+    usage: wgpu::BufferUsages,
+    generation: u64,
+}
+
+impl<T: bytemuck::Pod + bytemuck::Zeroable> DynamicBuffer<T> {
+    /// Creates an empty buffer with room for `capacity` elements
+    ///
+    /// `COPY_SRC` and `COPY_DST` are added to `usage`, since growing copies
+    /// the buffer. The capacity is rounded up so the buffer size is a
+    /// multiple of `wgpu::COPY_BUFFER_ALIGNMENT`.
+    pub fn new(
+        device: &wgpu::Device,
+        capacity: u64,
+        usage: wgpu::BufferUsages,
+        label: Option<&str>,
+    ) -> Self {
+        let usage = usage | wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST;
+        Self {
+            buf: BytemuckBuffer::new(device, aligned_capacity::<T>(capacity.max(1)), usage, label),
+            len: 0,
+            usage,
+            generation: 0,
+        }
+    }
+    /// Returns the number of elements written so far
+    #[inline]
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// Returns the number of elements that fit without growing
+    #[inline]
+    pub fn capacity(&self) -> u64 {
+        self.buf.count() as u64
+    }
+    /// Returns how often the underlying buffer was replaced
+    #[inline]
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+    /// Forgets the contents without shrinking the buffer
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+    /// Returns the slice of the buffer holding the written elements
+    pub fn used_slice(&self) -> wgpu::BufferSlice<'_> {
+        let size = std::mem::size_of::<T>() as wgpu::BufferAddress;
+        self.buf.slice(..self.len * size)
+    }
+    /// Writes `data` starting at element `index`, growing the buffer if needed
+    ///
+    /// The length becomes at least `index + data.len()`.
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the buffer was replaced.
+    pub fn write(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        index: u64,
+        data: &[T],
+    ) -> bool {
+        let end = index + data.len() as u64;
+        let grown = self.grow_to(device, queue, end);
+        let size = std::mem::size_of::<T>() as wgpu::BufferAddress;
+        self.buf.write(queue, index * size, data);
+        self.len = self.len.max(end);
+        grown
+    }
+    /// Appends `data` after the written elements, see [`DynamicBuffer::write`]
+    pub fn extend(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, data: &[T]) -> bool {
+        self.write(device, queue, self.len, data)
+    }
+    /// Makes sure `additional` more elements fit without growing
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the buffer was replaced.
+    pub fn reserve(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, additional: u64) -> bool {
+        self.grow_to(device, queue, self.len + additional)
+    }
+
+    fn grow_to(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, required: u64) -> bool {
+        let capacity = self.capacity();
+        if required <= capacity {
+            return false;
+        }
+        let new = BytemuckBuffer::new(
+            device,
+            aligned_capacity::<T>(grown_capacity(capacity, required)),
+            self.usage,
+            self.buf.label(),
+        );
+        if self.len > 0 {
+            let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Dynamic Buffer Grow Encoder"),
+            });
+            let size = copy_size::<T>(self.len);
+            encoder.copy_buffer_to_buffer(&self.buf, 0, &new, 0, size);
+            queue.submit(Some(encoder.finish()));
+        }
+        self.buf = new;
+        self.generation += 1;
+        true
+    }
+}
+
+/// Doubles `capacity` until `required` elements fit
+fn grown_capacity(capacity: u64, required: u64) -> u64 {
+    required.max(capacity * 2)
+}
+
+/// Rounds `capacity` up until its size in bytes is a multiple of
+/// `wgpu::COPY_BUFFER_ALIGNMENT`
+fn aligned_capacity<T>(capacity: u64) -> u64 {
+    let size = std::mem::size_of::<T>() as u64;
+    let mut capacity = capacity;
+    while !(capacity * size).is_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT) {
+        capacity += 1;
+    }
+    capacity
+}
+
+/// Returns the size of the copy of `len` elements when growing
+///
+/// Rounded up to `wgpu::COPY_BUFFER_ALIGNMENT`, which stays in bounds as
+/// the capacity is aligned the same way.
+fn copy_size<T>(len: u64) -> wgpu::BufferAddress {
+    let size = len * std::mem::size_of::<T>() as wgpu::BufferAddress;
+    wgpu::util::align_to(size, wgpu::COPY_BUFFER_ALIGNMENT)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn capacity_doubles() {
+        assert_eq!(grown_capacity(4, 5), 8);
+        assert_eq!(grown_capacity(4, 20), 20);
+        assert_eq!(grown_capacity(1, 2), 2);
+    }
+
+    #[test]
+    fn copies_stay_aligned() {
+        assert_eq!(aligned_capacity::<u8>(1), 4);
+        assert_eq!(aligned_capacity::<u16>(3), 4);
+        assert_eq!(aligned_capacity::<[u8; 3]>(5), 8);
+        assert_eq!(aligned_capacity::<u32>(3), 3);
+        assert_eq!(copy_size::<u8>(3), 4);
+        assert_eq!(copy_size::<u16>(3), 8);
+        assert_eq!(copy_size::<u32>(3), 12);
+    }
+
+    #[test]
+    fn grows_byte_buffer() {
+        let (device, queue) = crate::gfx::noop_device();
+        let mut buf = DynamicBuffer::<u8>::new(&device, 3, wgpu::BufferUsages::VERTEX, None);
+        assert_eq!(buf.capacity(), 4);
+        assert!(!buf.write(&device, &queue, 0, &[1, 2, 3, 4]));
+        assert!(buf.extend(&device, &queue, &[5, 6, 7, 8]));
+        assert_eq!(buf.len(), 8);
+        assert_eq!(buf.capacity(), 8);
+        assert_eq!(buf.generation(), 1);
+        device.poll(wgpu::PollType::Wait).unwrap();
+    }
+}
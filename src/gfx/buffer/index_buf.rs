@@ -4,16 +4,69 @@ use super::BytemuckBuffer;
 
 pub trait IndexType: Pod + Zeroable {
     fn index_format() -> wgpu::IndexFormat;
+    /// Converts a `u32` index into this index type
+    ///
+    /// # Panics
+    /// Panics if the index does not fit into this type
+    fn from_u32(index: u32) -> Self;
+    /// Converts this index into a `u32`
+    fn to_u32(self) -> u32;
 }
 impl IndexType for u16 {
     fn index_format() -> wgpu::IndexFormat {
         wgpu::IndexFormat::Uint16
     }
+    fn from_u32(index: u32) -> Self {
+        u16::try_from(index).expect("Index does not fit into u16, use u32 indices instead")
+    }
+    fn to_u32(self) -> u32 {
+        self as u32
+    }
 }
 impl IndexType for u32 {
     fn index_format() -> wgpu::IndexFormat {
         wgpu::IndexFormat::Uint32
     }
+    fn from_u32(index: u32) -> Self {
+        index
+    }
+    fn to_u32(self) -> u32 {
+        self
+    }
+}
+
+/// Maximum number of quads `quad_indices` can address with `u16` indices
+pub const MAX_U16_QUADS: u32 = (u16::MAX as u32 + 1) / 4;
+
+/// Generates the indices of `quad_count` quads, two triangles each
+///
+/// Quad `i` uses vertices `4 * i..4 * i + 4` in the order top-right, top-left,
+/// bottom-left, bottom-right (counter-clockwise), expanded to
+/// `[0, 1, 2, 2, 3, 0]`.
+///
+/// # Panics
+///
+/// Panics if `quad_count` exceeds `MAX_U16_QUADS`; use [`quad_indices_u32`]
+/// for larger batches.
+pub fn quad_indices(quad_count: u32) -> Vec<u16> {
+    expand_quads(quad_count)
+}
+
+/// Generates the indices of `quad_count` quads as `u32`, see [`quad_indices`]
+pub fn quad_indices_u32(quad_count: u32) -> Vec<u32> {
+    expand_quads(quad_count)
+}
+
+fn expand_quads<T: IndexType>(quad_count: u32) -> Vec<T> {
+    if T::index_format() == wgpu::IndexFormat::Uint16 {
+        assert!(
+            quad_count <= MAX_U16_QUADS,
+            "{quad_count} quads exceed the u16 index range ({MAX_U16_QUADS} quads), use u32 indices instead"
+        );
+    }
+    (0..quad_count)
+        .flat_map(|quad| [0, 1, 2, 2, 3, 0].map(|i| T::from_u32(quad * 4 + i)))
+        .collect()
 }
 
 #[derive(educe::Educe)]
@@ -47,7 +100,37 @@ impl<T: IndexType> IndexBuffer<T> {
             label,
         ))
     }
+    /// Creates an index buffer for `quad_count` quads, see [`quad_indices`]
+    pub fn new_quads(
+        device: &wgpu::Device,
+        quad_count: u32,
+        extra_usages: wgpu::BufferUsages,
+        label: Option<&str>,
+    ) -> Self {
+        Self::new_filled(device, &expand_quads(quad_count), extra_usages, label)
+    }
     pub fn index_format(&self) -> wgpu::IndexFormat {
         T::index_format()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn quads() {
+        assert_eq!(quad_indices(2), vec![0, 1, 2, 2, 3, 0, 4, 5, 6, 6, 7, 4]);
+        assert_eq!(quad_indices(MAX_U16_QUADS).last(), Some(&(u16::MAX - 3)));
+        assert_eq!(
+            quad_indices_u32(MAX_U16_QUADS + 1).len(),
+            6 * (MAX_U16_QUADS as usize + 1)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn quads_u16_overflow() {
+        quad_indices(MAX_U16_QUADS + 1);
+    }
+}
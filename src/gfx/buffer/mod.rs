@@ -1,9 +1,17 @@
 mod bytemuck_buf;
+mod dynamic_buf;
 mod index_buf;
+mod instance_buf;
+mod ring_buf;
+mod storage_buf;
 mod uniform_buf;
 mod vertex_buf;
 
 pub use bytemuck_buf::*;
+pub use dynamic_buf::*;
 pub use index_buf::*;
+pub use instance_buf::*;
+pub use ring_buf::*;
+pub use storage_buf::*;
 pub use uniform_buf::*;
 pub use vertex_buf::*;
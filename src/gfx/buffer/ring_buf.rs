@@ -0,0 +1,193 @@
+use std::collections::VecDeque;
+
+/// A GPU buffer sub-allocated in a ring for transient per-frame data
+///
+/// Each frame, data is appended after the previous allocations, wrapping
+/// around to the start of the buffer when the end is reached. Call
+/// [`RingBuffer::begin_frame`] once at the start of every frame.
+///
+/// # Reuse guarantee
+///
+/// Memory allocated during a frame is not handed out again until
+/// `begin_frame` has been called `frames_in_flight` more times. As long as
+/// `frames_in_flight` is larger than the surface's maximum frame latency
+/// (i.e. at least 3 for the default latency of 2), the GPU has finished
+/// reading an allocation before it is overwritten.
+#[derive(educe::Educe)]
+#[educe(Deref)]
+pub struct RingBuffer {
+    #[educe(Deref)]
+    buf: wgpu::Buffer,
+    label: Option<String>,
+    alloc: RingAllocator,
+}
+
+impl RingBuffer {
+    /// Creates a ring buffer
+    ///
+    /// # Arguments
+    /// * `device` - The device to create the buffer with
+    /// * `capacity` - Size of the whole ring in bytes
+    /// * `alignment` - Alignment of every allocation in bytes, e.g.
+    ///   `min_uniform_buffer_offset_alignment` for uniform data
+    /// * `frames_in_flight` - Number of frames an allocation is kept alive
+    /// * `usage` - Buffer usage, `COPY_DST` is always added
+    /// * `label` - Optional debug label
+    pub fn new(
+        device: &wgpu::Device,
+        capacity: wgpu::BufferAddress,
+        alignment: wgpu::BufferAddress,
+        frames_in_flight: usize,
+        usage: wgpu::BufferUsages,
+        label: Option<&str>,
+    ) -> Self {
+        let buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label,
+            size: capacity,
+            usage: usage | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        Self {
+            buf,
+            label: label.map(String::from),
+            alloc: RingAllocator::new(capacity, alignment, frames_in_flight),
+        }
+    }
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+    /// Starts a new frame, releasing the allocations of the oldest frame
+    pub fn begin_frame(&mut self) {
+        self.alloc.begin_frame();
+    }
+    /// Returns the number of bytes currently held by in-flight frames
+    #[inline]
+    pub fn used(&self) -> wgpu::BufferAddress {
+        self.alloc.used
+    }
+    /// Reserves `size` bytes for the current frame
+    ///
+    /// # Returns
+    ///
+    /// Returns the buffer and the offset of the reserved range, or `None` if
+    /// the ring is too full to fit `size` bytes.
+    pub fn alloc(
+        &mut self,
+        size: wgpu::BufferAddress,
+    ) -> Option<(&wgpu::Buffer, wgpu::BufferAddress)> {
+        let offset = self.alloc.alloc(size)?;
+        Some((&self.buf, offset))
+    }
+    /// Allocates space for `data` and uploads it
+    ///
+    /// # Returns
+    ///
+    /// Returns the buffer and the offset `data` was written to, or `None` if
+    /// the ring is too full.
+    pub fn push<T: bytemuck::Pod>(
+        &mut self,
+        queue: &wgpu::Queue,
+        data: &[T],
+    ) -> Option<(&wgpu::Buffer, wgpu::BufferAddress)> {
+        let bytes: &[u8] = bytemuck::cast_slice(data);
+        // Writes must be a multiple of `COPY_BUFFER_ALIGNMENT`
+        let size = bytes.len() as wgpu::BufferAddress;
+        let padded = size.next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT);
+        let offset = self.alloc.alloc(padded)?;
+        if padded == size {
+            queue.write_buffer(&self.buf, offset, bytes);
+        } else {
+            let mut padded_bytes = bytes.to_vec();
+            padded_bytes.resize(padded as usize, 0);
+            queue.write_buffer(&self.buf, offset, &padded_bytes);
+        }
+        Some((&self.buf, offset))
+    }
+}
+
+/// Offset bookkeeping of a `RingBuffer`, independent of the GPU buffer
+#[derive(Debug)]
+struct RingAllocator {
+    capacity: u64,
+    alignment: u64,
+    frames_in_flight: usize,
+    /// Offset of the next allocation (before alignment)
+    head: u64,
+    /// Bytes held by in-flight frames, including alignment and wrap padding
+    used: u64,
+    /// Bytes held by each in-flight frame, the current frame last
+    frames: VecDeque<u64>,
+}
+impl RingAllocator {
+    fn new(capacity: u64, alignment: u64, frames_in_flight: usize) -> Self {
+        assert!(
+            frames_in_flight > 0,
+            "A ring buffer needs at least one frame"
+        );
+        assert!(alignment > 0, "Ring buffer alignment must not be zero");
+        Self {
+            capacity,
+            alignment,
+            frames_in_flight,
+            head: 0,
+            used: 0,
+            frames: VecDeque::from([0]),
+        }
+    }
+    fn begin_frame(&mut self) {
+        self.frames.push_back(0);
+        if self.frames.len() > self.frames_in_flight {
+            self.used -= self.frames.pop_front().unwrap();
+        }
+    }
+    fn alloc(&mut self, size: u64) -> Option<u64> {
+        let mut offset = self.head.next_multiple_of(self.alignment);
+        let mut consumed = offset - self.head;
+        if offset + size > self.capacity {
+            // Skip the rest of the buffer and wrap around to the start
+            consumed = self.capacity - self.head;
+            offset = 0;
+        }
+        consumed += size;
+        if consumed > self.capacity - self.used {
+            return None;
+        }
+        self.head = offset + size;
+        self.used += consumed;
+        *self.frames.back_mut().unwrap() += consumed;
+        Some(offset)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn alloc_aligns_and_wraps() {
+        let mut ring = RingAllocator::new(64, 16, 2);
+        assert_eq!(ring.alloc(10), Some(0));
+        assert_eq!(ring.alloc(10), Some(16));
+        assert_eq!(ring.alloc(20), Some(32));
+        // Only 12 bytes left at the end and nothing free at the start
+        assert_eq!(ring.alloc(16), None);
+
+        ring.begin_frame();
+        ring.begin_frame();
+        // The first frame is released, the allocation wraps to the start
+        assert_eq!(ring.alloc(16), Some(0));
+    }
+
+    #[test]
+    fn reuse_after_frames_in_flight() {
+        let mut ring = RingAllocator::new(32, 4, 3);
+        assert_eq!(ring.alloc(32), Some(0));
+        ring.begin_frame();
+        assert_eq!(ring.alloc(4), None);
+        ring.begin_frame();
+        assert_eq!(ring.alloc(4), None);
+        ring.begin_frame();
+        assert_eq!(ring.used, 0);
+        assert_eq!(ring.alloc(4), Some(0));
+    }
+}
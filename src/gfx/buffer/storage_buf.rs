@@ -0,0 +1,110 @@
+use std::marker::PhantomData;
+
+use encase::ShaderType;
+use encase::internal::{CreateFrom, ReadFrom, WriteInto};
+
+/// A storage buffer holding a value encoded with `encase`
+///
+/// `T` may end in a runtime-sized array (e.g. `Vec<f32>`), in which case the
+/// buffer is sized for the value passed to `new`. The buffer always has
+/// `COPY_DST` and `COPY_SRC` usage, so it can be written from the CPU and
+/// read back with [`StorageBuffer::read_back`].
+#[derive(educe::Educe)]
+#[educe(Deref)]
+pub struct StorageBuffer<T: ShaderType + WriteInto> {
+    #[educe(Deref)]
+    buf: wgpu::Buffer,
+    label: Option<String>,
+    _data: PhantomData<T>,
+}
+
+impl<T: ShaderType + WriteInto> StorageBuffer<T> {
+    pub fn new(
+        device: &wgpu::Device,
+        data: &T,
+        extra_usage: wgpu::BufferUsages,
+        label: Option<&str>,
+    ) -> Self {
+        use wgpu::util::{BufferInitDescriptor, DeviceExt};
+        let buf = device.create_buffer_init(&BufferInitDescriptor {
+            label,
+            contents: &Self::encode(data),
+            usage: extra_usage
+                | wgpu::BufferUsages::STORAGE
+                | wgpu::BufferUsages::COPY_DST
+                | wgpu::BufferUsages::COPY_SRC,
+        });
+        Self {
+            buf,
+            label: label.map(String::from),
+            _data: Default::default(),
+        }
+    }
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+    pub fn write(&self, queue: &wgpu::Queue, offset: wgpu::BufferAddress, data: &T) {
+        let requested_sz: u64 = ShaderType::size(data).into();
+        assert!(
+            (requested_sz + offset) <= self.size(),
+            "Requested an out-of-bounds write for buffer: {}",
+            self.label().unwrap_or("<NO NAME>")
+        );
+        queue.write_buffer(&self.buf, offset, &Self::encode(data));
+    }
+    fn encode(data: &T) -> Vec<u8> {
+        let mut buffer_writer = encase::StorageBuffer::new(Vec::<u8>::new());
+        buffer_writer.write(data).unwrap();
+        buffer_writer.into_inner()
+    }
+}
+
+impl<T: ShaderType + WriteInto + CreateFrom> StorageBuffer<T> {
+    /// Copies the buffer back to the CPU and decodes it
+    ///
+    /// Waits for all submitted work, so results of a compute pass are
+    /// visible. A staging buffer is created on every call.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn read_back(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> anyhow::Result<T> {
+        pollster::block_on(self.read_back_async(device, queue))
+    }
+    /// Async variant of [`StorageBuffer::read_back`], available on all targets
+    pub async fn read_back_async(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> anyhow::Result<T> {
+        let bytes = crate::gfx::read_buffer_async(device, queue, &self.buf).await?;
+        Ok(encase::StorageBuffer::new(bytes).create()?)
+    }
+}
+
+impl<T: ShaderType + WriteInto + ReadFrom> StorageBuffer<T> {
+    /// Like [`StorageBuffer::read_back`], but decodes into an existing value
+    ///
+    /// Runtime-sized arrays in `value` are resized to the buffer contents.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn read_back_into(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        value: &mut T,
+    ) -> anyhow::Result<()> {
+        let bytes = crate::gfx::read_buffer(device, queue, &self.buf)?;
+        Ok(encase::StorageBuffer::new(bytes).read(value)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn runtime_sized_round_trip() {
+        let data = vec![1.0f32, 2.5, -3.0];
+        let bytes = StorageBuffer::<Vec<f32>>::encode(&data);
+        assert_eq!(bytes.len(), 12);
+        let decoded: Vec<f32> = encase::StorageBuffer::new(bytes).create().unwrap();
+        assert_eq!(decoded, data);
+    }
+}
@@ -1,11 +1,15 @@
 use std::marker::PhantomData;
 
+use parking_lot::Mutex;
+
 #[derive(educe::Educe)]
 #[educe(Deref)]
 pub struct UniformBuffer<T: encase::ShaderType + encase::internal::WriteInto> {
     #[educe(Deref)]
     buf: wgpu::Buffer,
     label: Option<String>,
+    /// Offset and encoded bytes of the last write, used by `write_if_changed`
+    last_written: Mutex<Option<(wgpu::BufferAddress, Vec<u8>)>>,
     _data: PhantomData<T>,
 }
 
@@ -17,9 +21,7 @@ impl<T: encase::ShaderType + encase::internal::WriteInto> UniformBuffer<T> {
         label: Option<&str>,
     ) -> Self {
         use wgpu::util::{BufferInitDescriptor, DeviceExt};
-        let mut buffer_writer = encase::UniformBuffer::new(Vec::<u8>::new());
-        buffer_writer.write(data).unwrap();
-        let buffer_data = buffer_writer.into_inner();
+        let buffer_data = Self::encode(data);
         let buf = device.create_buffer_init(&BufferInitDescriptor {
             label,
             contents: &buffer_data,
@@ -28,6 +30,7 @@ impl<T: encase::ShaderType + encase::internal::WriteInto> UniformBuffer<T> {
         Self {
             buf,
             label: label.map(String::from),
+            last_written: Mutex::new(Some((0, buffer_data))),
             _data: Default::default(),
         }
     }
@@ -35,6 +38,35 @@ impl<T: encase::ShaderType + encase::internal::WriteInto> UniformBuffer<T> {
         self.label.as_deref()
     }
     pub fn write(&self, queue: &wgpu::Queue, offset: wgpu::BufferAddress, data: &T) {
+        let buffer_data = self.encode_checked(offset, data);
+        queue.write_buffer(&self.buf, offset, &buffer_data);
+        *self.last_written.lock() = Some((offset, buffer_data));
+    }
+    /// Writes `data` to the buffer unless it is identical to the last write
+    ///
+    /// The encoded bytes of the last write are cached, so an unchanged value
+    /// at the same offset skips the upload entirely.
+    ///
+    /// Returns `true` if the data was written.
+    pub fn write_if_changed(
+        &self,
+        queue: &wgpu::Queue,
+        offset: wgpu::BufferAddress,
+        data: &T,
+    ) -> bool {
+        let buffer_data = self.encode_checked(offset, data);
+        let mut last_written = self.last_written.lock();
+        if let Some((last_offset, last_data)) = last_written.as_ref()
+            && *last_offset == offset
+            && *last_data == buffer_data
+        {
+            return false;
+        }
+        queue.write_buffer(&self.buf, offset, &buffer_data);
+        *last_written = Some((offset, buffer_data));
+        true
+    }
+    fn encode_checked(&self, offset: wgpu::BufferAddress, data: &T) -> Vec<u8> {
         let requested_sz: u64 = encase::ShaderType::size(data).into();
         let sz = self.size();
         assert!(
@@ -42,10 +74,11 @@ impl<T: encase::ShaderType + encase::internal::WriteInto> UniformBuffer<T> {
             "Requested an out-of-bounds write for buffer: {}",
             self.label().unwrap_or("<NO NAME>")
         );
-
+        Self::encode(data)
+    }
+    fn encode(data: &T) -> Vec<u8> {
         let mut buffer_writer = encase::UniformBuffer::new(Vec::<u8>::new());
         buffer_writer.write(data).unwrap();
-        let buffer_data = buffer_writer.into_inner();
-        queue.write_buffer(&self.buf, offset, &buffer_data);
+        buffer_writer.into_inner()
     }
 }
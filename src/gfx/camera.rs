@@ -0,0 +1,162 @@
+//! Cameras and their view and projection matrices
+
+use encase::ShaderType;
+
+/// How a camera projects the view space onto the screen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    /// Perspective projection with a vertical field of view in radians
+    Perspective { fov_y: f32 },
+    /// Orthographic projection showing `height` world units vertically
+    Orthographic { height: f32 },
+}
+
+/// A camera looking from `position` at `target`
+///
+/// The matrices use a right-handed coordinate system and the 0..1 depth
+/// range of wgpu.
+///
+/// ```ignore
+/// let mut camera = gfx::Camera::perspective(60f32.to_radians(), 16.0 / 9.0, 0.1, 100.0);
+/// camera.look_at(glam::vec3(0.0, 2.0, 5.0), glam::Vec3::ZERO);
+/// EVENTS.resize().subscribe(FnSubscriber::new(move |data: &ResizeData| {
+///     camera.set_aspect_ratio(data.width as f32 / data.height as f32);
+///     Subscription::Keep
+/// }).boxed());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    /// Position of the eye
+    pub position: glam::Vec3,
+    /// Point the camera looks at
+    pub target: glam::Vec3,
+    /// Up direction of the view
+    pub up: glam::Vec3,
+    /// Width divided by height of the viewport
+    pub aspect_ratio: f32,
+    /// Distance of the near clipping plane
+    pub near: f32,
+    /// Distance of the far clipping plane
+    pub far: f32,
+    /// Perspective or orthographic projection
+    pub projection: Projection,
+}
+impl Camera {
+    /// Creates a perspective camera at the origin looking down `-Z`
+    pub fn perspective(fov_y: f32, aspect_ratio: f32, near: f32, far: f32) -> Self {
+        Self::new(Projection::Perspective { fov_y }, aspect_ratio, near, far)
+    }
+    /// Creates an orthographic camera at the origin looking down `-Z`
+    ///
+    /// `height` is the number of world units visible vertically; the
+    /// visible width follows from the aspect ratio.
+    pub fn orthographic(height: f32, aspect_ratio: f32, near: f32, far: f32) -> Self {
+        Self::new(Projection::Orthographic { height }, aspect_ratio, near, far)
+    }
+    fn new(projection: Projection, aspect_ratio: f32, near: f32, far: f32) -> Self {
+        Self {
+            position: glam::Vec3::ZERO,
+            target: glam::Vec3::NEG_Z,
+            up: glam::Vec3::Y,
+            aspect_ratio,
+            near,
+            far,
+            projection,
+        }
+    }
+    /// Moves the camera to `position` and points it at `target`
+    pub fn look_at(&mut self, position: glam::Vec3, target: glam::Vec3) {
+        self.position = position;
+        self.target = target;
+    }
+    /// Sets the aspect ratio, e.g. from the size in a `ResizeData`
+    ///
+    /// Non-positive ratios, as reported for minimized windows, are ignored.
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: f32) {
+        if aspect_ratio > 0.0 && aspect_ratio.is_finite() {
+            self.aspect_ratio = aspect_ratio;
+        }
+    }
+    /// Returns the world to view space matrix
+    #[inline]
+    pub fn view(&self) -> glam::Mat4 {
+        glam::Mat4::look_at_rh(self.position, self.target, self.up)
+    }
+    /// Returns the view to clip space matrix
+    pub fn projection(&self) -> glam::Mat4 {
+        match self.projection {
+            Projection::Perspective { fov_y } => {
+                glam::Mat4::perspective_rh(fov_y, self.aspect_ratio, self.near, self.far)
+            }
+            Projection::Orthographic { height } => {
+                let half_height = height * 0.5;
+                let half_width = half_height * self.aspect_ratio;
+                glam::Mat4::orthographic_rh(
+                    -half_width,
+                    half_width,
+                    -half_height,
+                    half_height,
+                    self.near,
+                    self.far,
+                )
+            }
+        }
+    }
+    /// Returns the combined world to clip space matrix
+    #[inline]
+    pub fn view_proj(&self) -> glam::Mat4 {
+        self.projection() * self.view()
+    }
+    /// Returns the data to upload into a `UniformBuffer<CameraUniform>`
+    #[inline]
+    pub fn uniform(&self) -> CameraUniform {
+        CameraUniform {
+            view_proj: self.view_proj(),
+            position: self.position,
+        }
+    }
+}
+
+/// Camera data as seen by shaders
+///
+/// Matches this WGSL struct:
+///
+/// ```wgsl
+/// struct Camera {
+///     view_proj: mat4x4<f32>,
+///     position: vec3<f32>,
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, ShaderType)]
+pub struct CameraUniform {
+    pub view_proj: glam::Mat4,
+    pub position: glam::Vec3,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn target_projects_to_the_center() {
+        for mut camera in [
+            Camera::perspective(1.0, 2.0, 0.1, 100.0),
+            Camera::orthographic(10.0, 2.0, 0.1, 100.0),
+        ] {
+            camera.look_at(glam::vec3(3.0, 4.0, 5.0), glam::vec3(1.0, 0.0, -1.0));
+            let clip = camera.view_proj() * glam::vec4(1.0, 0.0, -1.0, 1.0);
+            let ndc = clip.truncate() / clip.w;
+            assert!(ndc.x.abs() < 1e-5 && ndc.y.abs() < 1e-5);
+            assert!((0.0..=1.0).contains(&ndc.z));
+        }
+    }
+
+    #[test]
+    fn orthographic_width_follows_aspect_ratio() {
+        let mut camera = Camera::orthographic(2.0, 1.0, 0.1, 10.0);
+        camera.set_aspect_ratio(2.0);
+        camera.set_aspect_ratio(0.0);
+        let clip = camera.view_proj() * glam::vec4(2.0, 1.0, -1.0, 1.0);
+        assert!((clip.x - 1.0).abs() < 1e-5 && (clip.y - 1.0).abs() < 1e-5);
+    }
+}
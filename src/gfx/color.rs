@@ -0,0 +1,195 @@
+//! Color utilities
+//!
+//! This module provides a small RGBA color type with named constants,
+//! hex parsing and sRGB/linear conversions.
+
+/// An RGBA color with each component in range [0.0, 1.0]
+///
+/// This is a thin wrapper around `glam::Vec4` and dereferences to it, so it
+/// can be used anywhere a raw color vector is expected.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(educe::Educe)]
+#[educe(Deref, DerefMut)]
+pub struct Color(pub glam::Vec4);
+
+impl Color {
+    pub const WHITE: Self = Self::rgb(1.0, 1.0, 1.0);
+    pub const BLACK: Self = Self::rgb(0.0, 0.0, 0.0);
+    pub const RED: Self = Self::rgb(1.0, 0.0, 0.0);
+    pub const GREEN: Self = Self::rgb(0.0, 1.0, 0.0);
+    pub const BLUE: Self = Self::rgb(0.0, 0.0, 1.0);
+    pub const YELLOW: Self = Self::rgb(1.0, 1.0, 0.0);
+    pub const CYAN: Self = Self::rgb(0.0, 1.0, 1.0);
+    pub const MAGENTA: Self = Self::rgb(1.0, 0.0, 1.0);
+    pub const TRANSPARENT: Self = Self::rgba(0.0, 0.0, 0.0, 0.0);
+
+    /// Creates a color from red, green, blue and alpha components
+    #[inline]
+    pub const fn rgba(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self(glam::Vec4::new(r, g, b, a))
+    }
+
+    /// Creates a fully opaque color from red, green and blue components
+    #[inline]
+    pub const fn rgb(r: f32, g: f32, b: f32) -> Self {
+        Self::rgba(r, g, b, 1.0)
+    }
+
+    /// Parses a color from a hex string
+    ///
+    /// Accepts `RRGGBB` and `RRGGBBAA`, optionally prefixed with `#`.
+    /// The components are returned as written, without any color space conversion.
+    ///
+    /// # Returns
+    ///
+    /// Returns `None` if the string is not a valid hex color.
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+        if !(hex.len() == 6 || hex.len() == 8) || !hex.is_ascii() {
+            return None;
+        }
+        let channel = |i: usize| {
+            u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+                .ok()
+                .map(|c| c as f32 / 255.0)
+        };
+        let alpha = if hex.len() == 8 { channel(3)? } else { 1.0 };
+        Some(Self::rgba(channel(0)?, channel(1)?, channel(2)?, alpha))
+    }
+
+    /// Converts an sRGB encoded color to linear space
+    ///
+    /// The alpha component is left untouched.
+    #[inline]
+    pub fn srgb_to_linear(color: glam::Vec4) -> glam::Vec4 {
+        fn convert(c: f32) -> f32 {
+            if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        }
+        glam::vec4(
+            convert(color.x),
+            convert(color.y),
+            convert(color.z),
+            color.w,
+        )
+    }
+
+    /// Converts a linear color to sRGB encoding
+    ///
+    /// The alpha component is left untouched.
+    #[inline]
+    pub fn linear_to_srgb(color: glam::Vec4) -> glam::Vec4 {
+        fn convert(c: f32) -> f32 {
+            if c <= 0.0031308 {
+                c * 12.92
+            } else {
+                1.055 * c.powf(1.0 / 2.4) - 0.055
+            }
+        }
+        glam::vec4(
+            convert(color.x),
+            convert(color.y),
+            convert(color.z),
+            color.w,
+        )
+    }
+
+    /// Returns this color converted from sRGB to linear space
+    #[inline]
+    pub fn to_linear(self) -> Self {
+        Self(Self::srgb_to_linear(self.0))
+    }
+
+    /// Returns this color converted from linear space to sRGB
+    #[inline]
+    pub fn to_srgb(self) -> Self {
+        Self(Self::linear_to_srgb(self.0))
+    }
+
+    /// Returns the color as an `[r, g, b, a]` array, e.g. for vertex colors
+    #[inline]
+    pub fn to_array(self) -> [f32; 4] {
+        self.0.to_array()
+    }
+}
+
+impl Default for Color {
+    #[inline]
+    fn default() -> Self {
+        Self::WHITE
+    }
+}
+
+impl From<glam::Vec4> for Color {
+    #[inline]
+    fn from(value: glam::Vec4) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Color> for glam::Vec4 {
+    #[inline]
+    fn from(value: Color) -> Self {
+        value.0
+    }
+}
+
+impl From<Color> for wgpu::Color {
+    #[inline]
+    fn from(value: Color) -> Self {
+        wgpu::Color {
+            r: value.x as f64,
+            g: value.y as f64,
+            b: value.z as f64,
+            a: value.w as f64,
+        }
+    }
+}
+
+/// Extension trait adding color space conversions to raw `glam::Vec4` colors
+pub trait ColorExt {
+    /// Converts an sRGB encoded color to linear space, see [`Color::srgb_to_linear`]
+    fn srgb_to_linear(self) -> Self;
+    /// Converts a linear color to sRGB encoding, see [`Color::linear_to_srgb`]
+    fn linear_to_srgb(self) -> Self;
+}
+
+impl ColorExt for glam::Vec4 {
+    #[inline]
+    fn srgb_to_linear(self) -> Self {
+        Color::srgb_to_linear(self)
+    }
+    #[inline]
+    fn linear_to_srgb(self) -> Self {
+        Color::linear_to_srgb(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn from_hex() {
+        let color = Color::from_hex("#ff8800").unwrap();
+        assert_eq!(color, Color::rgb(1.0, 136.0 / 255.0, 0.0));
+
+        let color = Color::from_hex("00ff0080").unwrap();
+        assert_eq!(color, Color::rgba(0.0, 1.0, 0.0, 128.0 / 255.0));
+
+        assert!(Color::from_hex("#ff88").is_none());
+        assert!(Color::from_hex("#gg8800").is_none());
+    }
+
+    #[test]
+    fn srgb_linear_round_trip() {
+        let srgb = glam::vec4(0.5, 0.25, 1.0, 0.5);
+        let linear = srgb.srgb_to_linear();
+        assert!((linear.x - 0.21404).abs() < 1e-4);
+        assert_eq!(linear.w, 0.5);
+        assert!(linear.linear_to_srgb().abs_diff_eq(srgb, 1e-5));
+    }
+}
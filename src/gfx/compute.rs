@@ -0,0 +1,152 @@
+//! Compute pipeline construction and dispatch
+
+use std::collections::HashMap;
+
+use crate::gfx::{DEFAULT_COMPUTE_ENTRY, check_entry_point, check_overrides, constant_pairs};
+
+/// Builds a `wgpu::ComputePipeline`, the compute counterpart of
+/// [`RenderPipelineBuilder`](crate::gfx::RenderPipelineBuilder)
+///
+/// The `cs_main` entry point is used unless another is given.
+///
+/// ```ignore
+/// let pipeline = gfx::ComputePipelineBuilder::new(&state.device, &module, &[&layout])
+///     .label("perlin.wgsl Pipeline")
+///     .build();
+/// ```
+pub struct ComputePipelineBuilder<'a> {
+    device: &'a wgpu::Device,
+    module: &'a wgpu::ShaderModule,
+    bind_group_layouts: &'a [&'a wgpu::BindGroupLayout],
+    label: Option<&'a str>,
+    entry_point: &'a str,
+    constants: HashMap<String, f64>,
+    push_constant_ranges: Vec<wgpu::PushConstantRange>,
+}
+impl<'a> ComputePipelineBuilder<'a> {
+    /// Creates a builder for a pipeline running `module`
+    ///
+    /// # Arguments
+    /// * `device` - The device to create the pipeline with
+    /// * `module` - The shader module containing the entry point
+    /// * `bind_group_layouts` - The bind group layouts, in group index order
+    pub fn new(
+        device: &'a wgpu::Device,
+        module: &'a wgpu::ShaderModule,
+        bind_group_layouts: &'a [&'a wgpu::BindGroupLayout],
+    ) -> Self {
+        Self {
+            device,
+            module,
+            bind_group_layouts,
+            label: None,
+            entry_point: DEFAULT_COMPUTE_ENTRY,
+            constants: HashMap::new(),
+            push_constant_ranges: Vec::new(),
+        }
+    }
+    /// Sets the debug label of the pipeline and its layout
+    pub fn label(mut self, label: &'a str) -> Self {
+        self.label = Some(label);
+        self
+    }
+    /// Sets the name of the compute entry point
+    pub fn entry_point(mut self, entry_point: &'a str) -> Self {
+        self.entry_point = entry_point;
+        self
+    }
+    /// Sets a pipeline-overridable constant, see [`constant_pairs`]
+    pub fn constant(mut self, name: &str, value: f64) -> Self {
+        self.constants.insert(name.to_owned(), value);
+        self
+    }
+    /// Declares a push constant range of the compute stage
+    ///
+    /// Requires `GfxState::supports_push_constants`.
+    pub fn push_constants(mut self, range: std::ops::Range<u32>) -> Self {
+        self.push_constant_ranges.push(wgpu::PushConstantRange {
+            stages: wgpu::ShaderStages::COMPUTE,
+            range,
+        });
+        self
+    }
+    /// Checks the entry point and constants against the shader source, then
+    /// builds the pipeline
+    ///
+    /// # Arguments
+    /// * `source` - The preprocessed WGSL of `module`
+    ///
+    /// # Returns
+    ///
+    /// Returns an error if `source` cannot be parsed, lacks the compute entry
+    /// point, or declares no `override` for a constant.
+    pub fn build_checked(self, source: &str) -> anyhow::Result<wgpu::ComputePipeline> {
+        let module = naga::front::wgsl::parse_str(source)?;
+        check_entry_point(&module, naga::ShaderStage::Compute, self.entry_point)?;
+        check_overrides(&module, &self.constants)?;
+        Ok(self.build())
+    }
+    /// Creates the pipeline layout and the pipeline without further checks
+    pub fn build(self) -> wgpu::ComputePipeline {
+        let layout = self
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: self.label,
+                bind_group_layouts: self.bind_group_layouts,
+                push_constant_ranges: &self.push_constant_ranges,
+            });
+        let constants = constant_pairs(&self.constants);
+        self.device
+            .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: self.label,
+                layout: Some(&layout),
+                module: self.module,
+                entry_point: Some(self.entry_point),
+                compilation_options: wgpu::PipelineCompilationOptions {
+                    constants: &constants,
+                    ..Default::default()
+                },
+                cache: None,
+            })
+    }
+}
+
+/// Records a compute pass running `pipeline` over `workgroups`
+///
+/// The bind groups are set in order, starting at group index 0.
+pub fn dispatch(
+    encoder: &mut wgpu::CommandEncoder,
+    pipeline: &wgpu::ComputePipeline,
+    bind_groups: &[&wgpu::BindGroup],
+    workgroups: (u32, u32, u32),
+) {
+    let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+        label: Some("Compute Pass"),
+        timestamp_writes: None,
+    });
+    cpass.set_pipeline(pipeline);
+    for (i, group) in bind_groups.iter().enumerate() {
+        cpass.set_bind_group(i as u32, *group, &[]);
+    }
+    cpass.dispatch_workgroups(workgroups.0, workgroups.1, workgroups.2);
+}
+
+/// Returns the number of workgroups of `local_size` invocations needed to cover `size`
+///
+/// `local_size` is the matching `@workgroup_size` dimension of the shader.
+#[inline]
+pub const fn workgroups_for(size: u32, local_size: u32) -> u32 {
+    size.div_ceil(local_size)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn workgroups_round_up() {
+        assert_eq!(workgroups_for(1024, 8), 128);
+        assert_eq!(workgroups_for(1025, 8), 129);
+        assert_eq!(workgroups_for(0, 64), 0);
+    }
+}
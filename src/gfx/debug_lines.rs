@@ -0,0 +1,257 @@
+//! Immediate-mode line drawing for debugging
+//!
+//! `DebugLines` collects colored line segments, e.g. normals, bounding boxes
+//! and rays, and draws them unlit on top of the scene.
+
+use std::mem::size_of;
+
+use crate::gfx::{
+    Color, DynamicBuffer, RenderConfig, RenderPipeline, RenderPipelineBuilder, ShaderCode,
+    UniformBuffer, Vertex, VertexInfo, VertexInfoObj, make_shader_module,
+};
+
+/// Endpoint of a debug line
+#[repr(C)]
+#[derive(Clone, Copy)]
+#[derive(bytemuck::Pod, bytemuck::Zeroable)]
+struct LineVertex {
+    position: [f32; 3],
+    color: [f32; 4],
+}
+impl Vertex for LineVertex {
+    fn info() -> VertexInfoObj {
+        struct Info;
+        impl VertexInfo for Info {
+            fn describe(&self) -> wgpu::VertexBufferLayout<'_> {
+                const ATTRS: &[wgpu::VertexAttribute] = &wgpu::vertex_attr_array![
+                    0 => Float32x3,  // position
+                    1 => Float32x4,  // color
+                ];
+                wgpu::VertexBufferLayout {
+                    array_stride: size_of::<LineVertex>() as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: ATTRS,
+                }
+            }
+            fn shader_code(&self) -> ShaderCode {
+                const CODE: &str = r#"
+                struct VertexBuf {
+                    @location(0) position: vec3f,
+                    @location(1) color: vec4f,
+                };
+                "#;
+                ShaderCode(CODE.into())
+            }
+        }
+        Box::new(Info)
+    }
+}
+
+/// Collects line segments every frame and draws them in one call
+///
+/// Lines are added with the `draw_*` methods in world space, uploaded with
+/// [`DebugLines::upload`] and drawn by [`DebugLines::draw`]. Uploading also
+/// starts a new set of lines, so every line is drawn for one frame only.
+/// Like `SpriteBatch`, call `upload` from `update` since the device and
+/// queue are not available from `AppClient::render`.
+///
+/// Lines are depth tested against the scene when the render pass has a
+/// depth buffer, but do not write depth themselves.
+///
+/// ```ignore
+/// lines.set_view_proj(&state.queue, camera.view_proj());
+/// lines.draw_aabb(bounds.min, bounds.max, gfx::Color::GREEN);
+/// lines.upload(&state.device, &state.queue);
+/// // In render:
+/// lines.draw(rpass);
+/// ```
+pub struct DebugLines {
+    pipeline: RenderPipeline,
+    view_proj: UniformBuffer<glam::Mat4>,
+    bind_group: wgpu::BindGroup,
+    vertices: DynamicBuffer<LineVertex>,
+    pending: Vec<LineVertex>,
+}
+impl DebugLines {
+    /// Creates the line pipeline for the main render pass
+    ///
+    /// # Arguments
+    /// * `device` - The device to create the pipeline and buffers with
+    /// * `format` - The format of the color target, usually the surface format
+    /// * `config` - The attachments of the render pass the lines are drawn in
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat, config: &RenderConfig) -> Self {
+        let view_proj = UniformBuffer::new(
+            device,
+            &glam::Mat4::IDENTITY,
+            wgpu::BufferUsages::COPY_DST,
+            Some("Debug Lines Uniform"),
+        );
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Debug Lines Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Debug Lines Group"),
+            layout: &layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: view_proj.as_entire_binding(),
+            }],
+        });
+
+        let vertex_info = LineVertex::info();
+        let module = make_shader_module(
+            device,
+            include_str!("debug_lines.wgsl"),
+            vertex_info.as_ref(),
+            None,
+            Some("debug_lines.wgsl"),
+        )
+        .expect("Invalid directive in debug_lines.wgsl");
+        let layouts = [&layout];
+        let mut builder =
+            RenderPipelineBuilder::new(device, &module, vertex_info.as_ref(), format, &layouts)
+                .label("debug_lines.wgsl Pipeline")
+                .render_config(config)
+                .topology(wgpu::PrimitiveTopology::LineList)
+                .cull_mode(None)
+                .blend(Some(wgpu::BlendState::ALPHA_BLENDING));
+        if let Some(depth_format) = config.depth_format {
+            builder = builder.depth(depth_format, false, wgpu::CompareFunction::LessEqual);
+        }
+        let pipeline = builder.build();
+
+        let vertices = DynamicBuffer::new(
+            device,
+            1024,
+            wgpu::BufferUsages::VERTEX,
+            Some("Debug Line Vertices"),
+        );
+        Self {
+            pipeline,
+            view_proj,
+            bind_group,
+            vertices,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Sets the matrix transforming world space to clip space, e.g. `Camera::view_proj`
+    pub fn set_view_proj(&self, queue: &wgpu::Queue, view_proj: glam::Mat4) {
+        self.view_proj.write_if_changed(queue, 0, &view_proj);
+    }
+
+    /// Adds a line from `a` to `b`
+    ///
+    /// `color` is linear, like the vertex colors.
+    pub fn draw_line(&mut self, a: glam::Vec3, b: glam::Vec3, color: Color) {
+        let color = color.to_array();
+        self.pending.extend([a, b].map(|p| LineVertex {
+            position: p.to_array(),
+            color,
+        }));
+    }
+    /// Adds a line from `origin` along `direction`, including its length
+    pub fn draw_ray(&mut self, origin: glam::Vec3, direction: glam::Vec3, color: Color) {
+        self.draw_line(origin, origin + direction, color);
+    }
+    /// Adds the twelve edges of an axis aligned box
+    pub fn draw_aabb(&mut self, min: glam::Vec3, max: glam::Vec3, color: Color) {
+        for (a, b) in aabb_edges(min, max) {
+            self.draw_line(a, b, color);
+        }
+    }
+    /// Adds the X, Y and Z axes of `transform` in red, green and blue
+    pub fn draw_axes(&mut self, transform: glam::Mat4, length: f32) {
+        let origin = transform.transform_point3(glam::Vec3::ZERO);
+        let axes = [
+            (glam::Vec3::X, Color::RED),
+            (glam::Vec3::Y, Color::GREEN),
+            (glam::Vec3::Z, Color::BLUE),
+        ];
+        for (axis, color) in axes {
+            let end = transform.transform_point3(axis * length);
+            self.draw_line(origin, end, color);
+        }
+    }
+
+    /// Returns the number of lines added since the last upload
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.pending.len() / 2
+    }
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+    /// Uploads the added lines for [`DebugLines::draw`] and starts a new set
+    pub fn upload(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        self.vertices.clear();
+        self.vertices.extend(device, queue, &self.pending);
+        self.pending.clear();
+    }
+    /// Draws the lines of the last [`DebugLines::upload`]
+    pub fn draw(&self, rpass: &mut wgpu::RenderPass<'_>) {
+        if self.vertices.is_empty() {
+            return;
+        }
+        rpass.set_pipeline(&self.pipeline);
+        rpass.set_bind_group(0, &self.bind_group, &[]);
+        rpass.set_vertex_buffer(0, self.vertices.used_slice());
+        rpass.draw(0..self.vertices.len() as u32, 0..1);
+    }
+}
+
+/// Returns the edges of the box spanned by `min` and `max`
+fn aabb_edges(min: glam::Vec3, max: glam::Vec3) -> [(glam::Vec3, glam::Vec3); 12] {
+    let corner = |i: usize| {
+        glam::vec3(
+            if i & 1 == 0 { min.x } else { max.x },
+            if i & 2 == 0 { min.y } else { max.y },
+            if i & 4 == 0 { min.z } else { max.z },
+        )
+    };
+    // Corners differing in exactly one bit share an edge
+    const EDGES: [(usize, usize); 12] = [
+        (0, 1),
+        (2, 3),
+        (4, 5),
+        (6, 7),
+        (0, 2),
+        (1, 3),
+        (4, 6),
+        (5, 7),
+        (0, 4),
+        (1, 5),
+        (2, 6),
+        (3, 7),
+    ];
+    EDGES.map(|(a, b)| (corner(a), corner(b)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn aabb_edges_are_axis_aligned() {
+        let (min, max) = (glam::vec3(-1.0, 0.0, 2.0), glam::vec3(1.0, 3.0, 4.0));
+        let edges = aabb_edges(min, max);
+        for (a, b) in edges {
+            let delta = (b - a).abs();
+            let axes = delta.cmpgt(glam::Vec3::ZERO).bitmask().count_ones();
+            assert_eq!(axes, 1);
+        }
+        let length: f32 = edges.iter().map(|(a, b)| a.distance(*b)).sum();
+        assert_eq!(length, 4.0 * (2.0 + 3.0 + 2.0));
+    }
+}
@@ -0,0 +1,26 @@
+// Debug line shader
+//
+// Draws unlit colored lines in world space.
+
+/// @include "struct/VertexBuf"
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) color: vec4f,
+};
+
+@group(0) @binding(0)
+var<uniform> view_proj: mat4x4f;
+
+@vertex
+fn vs_main(in: VertexBuf) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = view_proj * vec4f(in.position, 1.0);
+    out.color = in.color;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    return in.color;
+}
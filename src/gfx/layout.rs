@@ -0,0 +1,108 @@
+//! Host-shareable memory layout helpers
+//!
+//! WGSL lays out uniform and storage data with stricter alignment rules than
+//! Rust. The most common pitfall is `vec3<f32>`: it is 12 bytes large but
+//! 16-byte aligned, so
+//!
+//! - `struct { a: vec3f, b: f32 }` is 16 bytes (`b` fills the padding),
+//! - `struct { a: vec3f, b: vec3f }` is 32 bytes (4 padding bytes after `a`),
+//! - `mat3x3f` stores each column as a padded `vec4f`, 48 bytes in total.
+//!
+//! Types deriving `encase::ShaderType` get this right automatically, but a
+//! `#[repr(C)]` struct with `[f32; 3]` fields uploaded through `bytemuck` does
+//! not. Use `check_layout` to compare the computed layout against the WGSL
+//! declaration, and the padding helpers below for manually laid out data.
+
+/// The size and alignment of a type as laid out in GPU memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShaderLayout {
+    /// Minimum size in bytes (the per-element size for runtime-sized arrays)
+    pub size: u64,
+    /// Required alignment in bytes
+    pub alignment: u64,
+}
+
+/// Returns the GPU memory layout `encase` computes for `T`
+#[inline]
+pub fn shader_layout<T: encase::ShaderType>() -> ShaderLayout {
+    ShaderLayout {
+        size: T::min_size().get(),
+        alignment: T::METADATA.alignment().get(),
+    }
+}
+
+/// Logs the GPU memory layout of `T` next to its Rust layout
+///
+/// This only does something in debug builds, and is meant for comparing a
+/// uniform struct against its WGSL declaration while debugging garbage values.
+#[inline]
+pub fn check_layout<T: encase::ShaderType>() {
+    #[cfg(debug_assertions)]
+    {
+        let layout = shader_layout::<T>();
+        log::info!(
+            "Layout of {}: shader size {} bytes, alignment {} bytes (Rust size {} bytes, alignment {} bytes)",
+            std::any::type_name::<T>(),
+            layout.size,
+            layout.alignment,
+            std::mem::size_of::<T>(),
+            std::mem::align_of::<T>(),
+        );
+    }
+}
+
+/// Pads a `Vec3` to the 16 bytes a WGSL `vec3<f32>` occupies in a buffer
+///
+/// `w` is written into the padding, which is handy for packing an extra
+/// scalar next to the vector.
+#[inline]
+pub fn padded_vec3(v: glam::Vec3, w: f32) -> [f32; 4] {
+    v.extend(w).to_array()
+}
+
+/// Converts a `Mat3` into the column layout of a WGSL `mat3x3<f32>`
+///
+/// Each column is padded to 16 bytes, resulting in 48 bytes in total.
+#[inline]
+pub fn padded_mat3(m: glam::Mat3) -> [[f32; 4]; 3] {
+    [
+        padded_vec3(m.x_axis, 0.0),
+        padded_vec3(m.y_axis, 0.0),
+        padded_vec3(m.z_axis, 0.0),
+    ]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(encase::ShaderType)]
+    struct Vec3Scalar {
+        a: glam::Vec3,
+        b: f32,
+    }
+
+    #[derive(encase::ShaderType)]
+    struct Vec3Vec3 {
+        a: glam::Vec3,
+        b: glam::Vec3,
+    }
+
+    #[test]
+    fn vec3_padding() {
+        let layout = shader_layout::<Vec3Scalar>();
+        assert_eq!(
+            layout,
+            ShaderLayout {
+                size: 16,
+                alignment: 16
+            }
+        );
+        assert_eq!(shader_layout::<Vec3Vec3>().size, 32);
+        assert_eq!(shader_layout::<glam::Mat3>().size, 48);
+        assert_eq!(
+            std::mem::size_of_val(&padded_mat3(glam::Mat3::IDENTITY)),
+            48
+        );
+    }
+}
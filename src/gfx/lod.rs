@@ -0,0 +1,86 @@
+//! Distance based level-of-detail selection
+
+/// A set of level-of-detail variants of an object, usually `Mesh`es
+///
+/// Each level starts at a distance threshold in world units and is used
+/// until the threshold of the next level. The first level starts at
+/// distance zero. A level without a mesh culls the object: once the camera
+/// is farther away than its threshold, [`Lod::select`] returns `None`.
+///
+/// ```ignore
+/// let lod = gfx::Lod::new(high)
+///     .with_level(20.0, medium)
+///     .with_level(60.0, low)
+///     .with_cull(150.0);
+/// if let Some(mesh) = lod.select(camera_pos, object_pos) {
+///     mesh.bind(rpass);
+///     mesh.draw(0..1, rpass);
+/// }
+/// ```
+pub struct Lod<T> {
+    /// Levels sorted by their starting distance
+    levels: Vec<(f32, Option<T>)>,
+}
+impl<T> Lod<T> {
+    /// Creates a LOD set whose most detailed level is `mesh`
+    pub fn new(mesh: T) -> Self {
+        Self {
+            levels: vec![(0.0, Some(mesh))],
+        }
+    }
+    /// Adds a level used from `distance` world units onward
+    pub fn with_level(mut self, distance: f32, mesh: T) -> Self {
+        self.insert(distance, Some(mesh));
+        self
+    }
+    /// Culls the object from `distance` world units onward
+    pub fn with_cull(mut self, distance: f32) -> Self {
+        self.insert(distance, None);
+        self
+    }
+    /// Returns the level for the given distance in world units
+    pub fn select_by_distance(&self, distance: f32) -> Option<&T> {
+        let index = self
+            .levels
+            .partition_point(|(start, _)| *start <= distance)
+            .saturating_sub(1);
+        self.levels[index].1.as_ref()
+    }
+    /// Returns the level for an object at `object_pos` seen from `camera_pos`
+    ///
+    /// Returns `None` if the object is beyond the cull distance.
+    pub fn select(&self, camera_pos: glam::Vec3, object_pos: glam::Vec3) -> Option<&T> {
+        self.select_by_distance(camera_pos.distance(object_pos))
+    }
+
+    fn insert(&mut self, distance: f32, mesh: Option<T>) {
+        assert!(
+            distance.is_finite() && distance >= 0.0,
+            "LOD distance must be finite and non-negative, got {distance}"
+        );
+        let index = self.levels.partition_point(|(start, _)| *start <= distance);
+        self.levels.insert(index, (distance, mesh));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn selects_by_distance() {
+        let lod = Lod::new("high")
+            .with_cull(100.0)
+            .with_level(10.0, "medium")
+            .with_level(50.0, "low");
+        assert_eq!(lod.select_by_distance(0.0), Some(&"high"));
+        assert_eq!(lod.select_by_distance(9.9), Some(&"high"));
+        assert_eq!(lod.select_by_distance(10.0), Some(&"medium"));
+        assert_eq!(lod.select_by_distance(75.0), Some(&"low"));
+        assert_eq!(lod.select_by_distance(100.0), None);
+        assert_eq!(
+            lod.select(glam::Vec3::ZERO, glam::Vec3::new(0.0, 0.0, -20.0)),
+            Some(&"medium")
+        );
+    }
+}
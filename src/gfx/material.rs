@@ -0,0 +1,28 @@
+//! Per-mesh bind groups
+
+/// A bind group together with the layout it was created against
+///
+/// Attached to a `Mesh` with [`Mesh::with_material`](crate::gfx::Mesh::with_material),
+/// the material is bound by [`Mesh::render_with`](crate::gfx::Mesh::render_with)
+/// so meshes with different textures or uniforms can be drawn in one loop.
+#[derive(Debug)]
+pub struct Material {
+    layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+}
+impl Material {
+    /// Creates a material from a bind group created with `layout`
+    pub fn new(layout: wgpu::BindGroupLayout, bind_group: wgpu::BindGroup) -> Self {
+        Self { layout, bind_group }
+    }
+    /// Returns the layout pipelines drawing this material must use at its group index
+    #[inline]
+    pub fn layout(&self) -> &wgpu::BindGroupLayout {
+        &self.layout
+    }
+    /// Returns the bind group of the material
+    #[inline]
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+}
@@ -1,22 +1,82 @@
-use crate::gfx::{IndexBuffer, IndexType, Vertex, VertexBuffer};
+use crate::gfx::{
+    IndexBuffer, IndexType, InstanceBuffer, Material, Vertex, VertexBuffer, VertexInfo,
+    VertexInfoObj, record_draw,
+};
 
 use std::ops::Range;
+use std::sync::Arc;
 
 pub struct Mesh<V: Vertex, I: IndexType = u32> {
     vertices: VertexBuffer<V>,
     indices: Option<IndexBuffer<I>>,
+    /// Layout of the vertex data, used to build matching pipelines
+    info: VertexInfoObj,
+    /// Bind group set by `render_with`, shared between meshes
+    material: Option<Arc<Material>>,
 }
 impl<V: Vertex, I: IndexType> Mesh<V, I> {
     #[inline]
     pub fn new(vertices: VertexBuffer<V>, indices: Option<IndexBuffer<I>>) -> Self {
-        Self { vertices, indices }
+        Self::with_info(vertices, indices, V::info())
+    }
+    /// Creates a mesh whose vertices follow `info` rather than `V::info()`
+    ///
+    /// This is meant for layouts assembled at runtime with
+    /// `VertexLayoutBuilder`, typically storing the interleaved vertex data
+    /// as `f32`s. The vertex count is derived from the layout's stride.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the stride of `info` is not a multiple of the size of `V`.
+    pub fn with_info(
+        vertices: VertexBuffer<V>,
+        indices: Option<IndexBuffer<I>>,
+        info: VertexInfoObj,
+    ) -> Self {
+        let stride = info.describe().array_stride;
+        assert!(
+            stride > 0 && stride.is_multiple_of(size_of::<V>() as u64),
+            "Vertex stride {stride} is not a multiple of the vertex element size"
+        );
+        Self {
+            vertices,
+            indices,
+            info,
+            material: None,
+        }
+    }
+    /// Returns this mesh with `material` attached, see [`Mesh::render_with`]
+    #[inline]
+    pub fn with_material(mut self, material: Arc<Material>) -> Self {
+        self.material = Some(material);
+        self
+    }
+    /// Replaces the material of the mesh
+    #[inline]
+    pub fn set_material(&mut self, material: Option<Arc<Material>>) {
+        self.material = material;
+    }
+    /// Returns the material of the mesh, if any
+    #[inline]
+    pub fn material(&self) -> Option<&Arc<Material>> {
+        self.material.as_ref()
+    }
+    /// Returns the layout of the vertex data
+    #[inline]
+    pub fn info(&self) -> &dyn VertexInfo {
+        self.info.as_ref()
+    }
+    /// Returns the number of vertices
+    #[inline]
+    pub fn vertex_count(&self) -> u32 {
+        (self.vertices.size() / self.info.describe().array_stride) as u32
     }
     #[inline]
     pub fn count(&self) -> u32 {
         if let Some(indices) = self.indices.as_ref() {
             indices.count()
         } else {
-            self.vertices.count()
+            self.vertex_count()
         }
     }
     #[inline]
@@ -26,12 +86,38 @@ impl<V: Vertex, I: IndexType> Mesh<V, I> {
         }
         rpass.set_vertex_buffer(0, self.vertices.slice(..));
     }
+    /// Binds the mesh and `instances` as the per-instance vertex buffer at slot 1
+    ///
+    /// The pipeline must declare the instance layout, see
+    /// `RenderPipelineBuilder::instance_info`. Follow with `draw(0..count, rpass)`
+    /// to draw the first `count` instances.
+    #[inline]
+    pub fn bind_instanced<T: bytemuck::Pod>(
+        &self,
+        rpass: &mut wgpu::RenderPass<'_>,
+        instances: &InstanceBuffer<T>,
+    ) {
+        self.bind(rpass);
+        rpass.set_vertex_buffer(1, instances.slice(..));
+    }
     #[inline]
     pub fn draw(&self, instances: Range<u32>, rpass: &mut wgpu::RenderPass<'_>) {
+        record_draw(self.count(), instances.len() as u32);
         if self.indices.is_some() {
             rpass.draw_indexed(0..self.count(), 0, instances);
         } else {
             rpass.draw(0..self.count(), instances);
         }
     }
+    /// Binds the material at bind group index `group`, then binds and draws the mesh
+    ///
+    /// Meshes without a material are drawn with whatever is bound at
+    /// `group`, just like `bind` followed by `draw`.
+    pub fn render_with(&self, rpass: &mut wgpu::RenderPass<'_>, group: u32, instances: Range<u32>) {
+        if let Some(material) = self.material.as_ref() {
+            rpass.set_bind_group(group, material.bind_group(), &[]);
+        }
+        self.bind(rpass);
+        self.draw(instances, rpass);
+    }
 }
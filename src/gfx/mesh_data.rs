@@ -0,0 +1,235 @@
+//! CPU-side mesh representation
+//!
+//! `MeshData` holds vertices and indices in regular `Vec`s so geometry can be
+//! built, transformed and combined before it is uploaded to the GPU as a `Mesh`.
+
+use crate::gfx::{IndexBuffer, IndexType, Mesh, Vertex, Vertex3D, VertexBuffer};
+
+/// Geometry stored in CPU memory, decoupled from any GPU buffers
+///
+/// # Type Parameters
+/// * `V` - The vertex type
+/// * `I` - The index type (defaults to `u32`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeshData<V: Vertex, I: IndexType = u32> {
+    /// The vertices of the mesh
+    pub vertices: Vec<V>,
+    /// Optional indices into `vertices`, three per triangle
+    pub indices: Option<Vec<I>>,
+}
+
+impl<V: Vertex, I: IndexType> MeshData<V, I> {
+    /// Creates mesh data from vertices and optional indices
+    #[inline]
+    pub fn new(vertices: Vec<V>, indices: Option<Vec<I>>) -> Self {
+        Self { vertices, indices }
+    }
+
+    /// Returns the number of elements that would be drawn
+    ///
+    /// This is the index count for indexed meshes and the vertex count otherwise.
+    #[inline]
+    pub fn count(&self) -> usize {
+        match self.indices.as_ref() {
+            Some(indices) => indices.len(),
+            None => self.vertices.len(),
+        }
+    }
+
+    /// Returns the vertex indices of each triangle
+    ///
+    /// Non-indexed meshes are treated as a sequential triangle list.
+    pub fn triangles(&self) -> Vec<[usize; 3]> {
+        match self.indices.as_ref() {
+            Some(indices) => indices
+                .chunks_exact(3)
+                .map(|t| [t[0], t[1], t[2]].map(|i| i.to_u32() as usize))
+                .collect(),
+            None => (0..self.vertices.len() / 3)
+                .map(|t| [t * 3, t * 3 + 1, t * 3 + 2])
+                .collect(),
+        }
+    }
+
+    /// Appends the geometry of `other` to this mesh
+    ///
+    /// The indices of `other` are offset so they keep referring to the same
+    /// vertices. If only one of the meshes is indexed, sequential indices are
+    /// generated for the other one.
+    pub fn merge(&mut self, other: MeshData<V, I>) {
+        let base = self.vertices.len() as u32;
+        if self.indices.is_some() || other.indices.is_some() {
+            let other_len = other.vertices.len() as u32;
+            let indices = self
+                .indices
+                .get_or_insert_with(|| (0..base).map(I::from_u32).collect());
+            match other.indices {
+                Some(other_indices) => indices.extend(
+                    other_indices
+                        .into_iter()
+                        .map(|i| I::from_u32(base + i.to_u32())),
+                ),
+                None => indices.extend((base..base + other_len).map(I::from_u32)),
+            }
+        }
+        self.vertices.extend(other.vertices);
+    }
+
+    /// Uploads the geometry to the GPU, creating a `Mesh`
+    ///
+    /// # Arguments
+    /// * `device` - The device to create the buffers with
+    /// * `label` - Optional debug label used for the vertex and index buffers
+    pub fn upload(&self, device: &wgpu::Device, label: Option<&str>) -> Mesh<V, I> {
+        let vertices =
+            VertexBuffer::new_filled(device, &self.vertices, wgpu::BufferUsages::empty(), label);
+        let indices = self.indices.as_ref().map(|indices| {
+            IndexBuffer::new_filled(device, indices, wgpu::BufferUsages::empty(), label)
+        });
+        Mesh::new(vertices, indices)
+    }
+}
+
+impl<I: IndexType> MeshData<Vertex3D, I> {
+    /// Transforms all positions and normals by the given matrix
+    ///
+    /// Normals are transformed by the inverse transpose so they stay
+    /// perpendicular under non-uniform scaling.
+    pub fn transform(&mut self, matrix: glam::Mat4) {
+        let normal_matrix = glam::Mat3::from_mat4(matrix).inverse().transpose();
+        for v in self.vertices.iter_mut() {
+            v.position = matrix
+                .transform_point3(glam::Vec3::from(v.position))
+                .to_array();
+            v.normal = (normal_matrix * glam::Vec3::from(v.normal))
+                .normalize_or_zero()
+                .to_array();
+        }
+    }
+
+    /// Recomputes the vertex normals from the triangle geometry, see [`compute_normals`]
+    pub fn compute_normals(&mut self) {
+        compute_normals(
+            &mut self.vertices,
+            self.indices.as_deref().unwrap_or_default(),
+        );
+    }
+}
+
+/// Computes smooth vertex normals from the triangle geometry
+///
+/// Face normals are weighted by triangle area, so vertices shared between
+/// triangles receive smooth normals. An empty `indices` slice treats the
+/// vertices as a sequential triangle list.
+///
+/// # Panics
+///
+/// Panics if an index is out of bounds.
+pub fn compute_normals<I: IndexType>(vertices: &mut [Vertex3D], indices: &[I]) {
+    let triangles: Vec<[usize; 3]> = if indices.is_empty() {
+        (0..vertices.len() / 3)
+            .map(|t| [t * 3, t * 3 + 1, t * 3 + 2])
+            .collect()
+    } else {
+        indices
+            .chunks_exact(3)
+            .map(|t| [t[0], t[1], t[2]].map(|i| i.to_u32() as usize))
+            .collect()
+    };
+    let mut normals = vec![glam::Vec3::ZERO; vertices.len()];
+    for [a, b, c] in triangles {
+        let pa = glam::Vec3::from(vertices[a].position);
+        let pb = glam::Vec3::from(vertices[b].position);
+        let pc = glam::Vec3::from(vertices[c].position);
+        // The cross product length is twice the triangle area
+        let face = (pb - pa).cross(pc - pa);
+        normals[a] += face;
+        normals[b] += face;
+        normals[c] += face;
+    }
+    for (v, n) in vertices.iter_mut().zip(normals) {
+        v.normal = n.normalize_or_zero().to_array();
+    }
+}
+
+/// Merges several meshes into one, see [`MeshData::merge`]
+pub fn merge_meshes<V: Vertex, I: IndexType>(
+    meshes: impl IntoIterator<Item = MeshData<V, I>>,
+) -> MeshData<V, I> {
+    let mut out = MeshData::new(Vec::new(), None);
+    for mesh in meshes {
+        out.merge(mesh);
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn vertex(x: f32, y: f32) -> Vertex3D {
+        Vertex3D {
+            position: [x, y, 0.0],
+            ..Default::default()
+        }
+    }
+
+    fn triangle() -> Vec<Vertex3D> {
+        vec![vertex(0.0, 0.0), vertex(1.0, 0.0), vertex(0.0, 1.0)]
+    }
+
+    #[test]
+    fn merge_offsets_indices() {
+        let mut a: MeshData<Vertex3D, u16> = MeshData::new(triangle(), None);
+        let b = MeshData::new(triangle(), Some(vec![0, 1, 2]));
+        a.merge(b);
+        assert_eq!(a.vertices.len(), 6);
+        assert_eq!(a.indices, Some(vec![0, 1, 2, 3, 4, 5]));
+
+        let merged = merge_meshes([
+            MeshData::<Vertex3D>::new(triangle(), None),
+            MeshData::new(triangle(), None),
+        ]);
+        assert_eq!(merged.vertices.len(), 6);
+        assert_eq!(merged.indices, None);
+    }
+
+    #[test]
+    fn transform_and_normals() {
+        let mut mesh: MeshData<Vertex3D> = MeshData::new(triangle(), None);
+        mesh.compute_normals();
+        assert!(mesh.vertices.iter().all(|v| v.normal == [0.0, 0.0, 1.0]));
+
+        mesh.transform(glam::Mat4::from_translation(glam::vec3(1.0, 2.0, 3.0)));
+        assert_eq!(mesh.vertices[1].position, [2.0, 2.0, 3.0]);
+        assert_eq!(mesh.vertices[1].normal, [0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn shared_vertices_get_smooth_normals() {
+        // Two triangles folded 90 degrees along the shared edge 0-1
+        let mut vertices = vec![
+            Vertex3D {
+                position: [0.0, 0.0, 0.0],
+                ..Default::default()
+            },
+            Vertex3D {
+                position: [1.0, 0.0, 0.0],
+                ..Default::default()
+            },
+            Vertex3D {
+                position: [0.0, 1.0, 0.0],
+                ..Default::default()
+            },
+            Vertex3D {
+                position: [0.0, 0.0, 1.0],
+                ..Default::default()
+            },
+        ];
+        compute_normals(&mut vertices, &[0u16, 1, 2, 1, 0, 3]);
+        let n = glam::Vec3::from(vertices[0].normal);
+        assert!(n.abs_diff_eq(glam::vec3(0.0, 1.0, 1.0).normalize(), 1e-6));
+        assert_eq!(vertices[2].normal, [0.0, 0.0, 1.0]);
+        assert_eq!(vertices[3].normal, [0.0, 1.0, 0.0]);
+    }
+}
@@ -1,13 +1,92 @@
+mod bind_group;
+mod blit;
 mod buffer;
+mod camera;
+mod color;
+mod compute;
+mod debug_lines;
+mod layout;
+mod lod;
+mod material;
 mod mesh;
+mod mesh_data;
+mod obj;
+mod orbit;
+mod pass;
+mod pipeline;
+mod post;
+mod readback;
+mod render_config;
+mod render_graph;
+mod sampler;
+mod scene;
 mod shader;
+#[cfg(not(target_arch = "wasm32"))]
+mod shader_watch;
+mod shadow;
+mod sprite;
 mod state;
+mod state_options;
+mod stats;
+mod storage_texture;
 mod texture;
+mod texture_cube;
+mod transform;
 mod vertex;
+mod vertex_check;
+mod vertex_layout;
 
+pub use bind_group::*;
+pub use blit::*;
 pub use buffer::*;
+pub use camera::*;
+pub use color::*;
+pub use compute::*;
+pub use debug_lines::*;
+pub use layout::*;
+pub use lod::*;
+pub use material::*;
 pub use mesh::*;
+pub use mesh_data::*;
+pub use obj::*;
+pub use orbit::*;
+pub use pass::*;
+pub use pipeline::*;
+pub use post::*;
+pub use readback::*;
+pub use render_config::*;
+pub use render_graph::*;
+pub use sampler::*;
+pub use scene::*;
 pub use shader::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub use shader_watch::*;
+pub use shadow::*;
+pub use sprite::*;
 pub use state::*;
+pub use state_options::*;
+pub use stats::*;
+pub use storage_texture::*;
 pub use texture::*;
+pub use texture_cube::*;
+pub use transform::*;
 pub use vertex::*;
+pub use vertex_check::*;
+pub use vertex_layout::*;
+
+/// Creates a device on the noop backend, which needs no GPU
+///
+/// Command encoders need the returned queue to be alive.
+#[cfg(test)]
+pub(crate) fn noop_device() -> (wgpu::Device, wgpu::Queue) {
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+        backends: wgpu::Backends::NOOP,
+        backend_options: wgpu::BackendOptions {
+            noop: wgpu::NoopBackendOptions { enable: true },
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    let adapter = pollster::block_on(instance.request_adapter(&Default::default())).unwrap();
+    pollster::block_on(adapter.request_device(&Default::default())).unwrap()
+}
@@ -0,0 +1,207 @@
+//! Wavefront OBJ loading
+//!
+//! Only geometry is read: positions, texture coordinates, normals and faces.
+//! Materials, groups and smoothing groups are ignored.
+
+use std::collections::HashMap;
+
+use anyhow::{Context, bail};
+
+use crate::gfx::{Mesh, MeshData, Vertex3D};
+
+/// Parses OBJ source into mesh data
+///
+/// Polygons are triangulated as fans. Vertices are shared between faces
+/// that reference the same position, texture coordinate and normal. Faces
+/// without normals get flat normals and vertices of their own. Texture
+/// coordinates are flipped vertically, since OBJ places the origin at the
+/// bottom left and wgpu at the top left. Vertex colors (`v x y z r g b`) are
+/// read when present.
+pub fn parse_obj(src: &str) -> anyhow::Result<MeshData<Vertex3D>> {
+    let mut positions: Vec<[f32; 3]> = Vec::new();
+    let mut colors: Vec<Option<[f32; 3]>> = Vec::new();
+    let mut tex_coords: Vec<[f32; 2]> = Vec::new();
+    let mut normals: Vec<[f32; 3]> = Vec::new();
+
+    let mut vertices: Vec<Vertex3D> = Vec::new();
+    let mut indices: Vec<u32> = Vec::new();
+    let mut shared: HashMap<(usize, Option<usize>, usize), u32> = HashMap::new();
+
+    for (n, line) in src.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut words = line.split_whitespace();
+        let Some(keyword) = words.next() else {
+            continue;
+        };
+        let context = || format!("Invalid OBJ at line {}: {}", n + 1, line.trim());
+        match keyword {
+            "v" => {
+                let values = parse_floats(words).with_context(context)?;
+                match values.as_slice() {
+                    [x, y, z] | [x, y, z, _] => {
+                        positions.push([*x, *y, *z]);
+                        colors.push(None);
+                    }
+                    [x, y, z, r, g, b] => {
+                        positions.push([*x, *y, *z]);
+                        colors.push(Some([*r, *g, *b]));
+                    }
+                    _ => bail!("{}", context()),
+                }
+            }
+            "vt" => {
+                let values = parse_floats(words).with_context(context)?;
+                match values.as_slice() {
+                    [u] => tex_coords.push([*u, 1.0]),
+                    [u, v] | [u, v, _] => tex_coords.push([*u, 1.0 - v]),
+                    _ => bail!("{}", context()),
+                }
+            }
+            "vn" => {
+                let values = parse_floats(words).with_context(context)?;
+                match values.as_slice() {
+                    [x, y, z] => normals.push([*x, *y, *z]),
+                    _ => bail!("{}", context()),
+                }
+            }
+            "f" => {
+                let corners = words
+                    .map(|w| parse_corner(w, positions.len(), tex_coords.len(), normals.len()))
+                    .collect::<anyhow::Result<Vec<_>>>()
+                    .with_context(context)?;
+                if corners.len() < 3 {
+                    bail!("{}: faces need at least three vertices", context());
+                }
+                let has_normals = corners.iter().all(|c| c.2.is_some());
+                for i in 1..corners.len() - 1 {
+                    let triangle = [corners[0], corners[i], corners[i + 1]];
+                    let flat_normal = (!has_normals).then(|| {
+                        let [a, b, c] = triangle.map(|c| glam::Vec3::from(positions[c.0]));
+                        (b - a).cross(c - a).normalize_or_zero().to_array()
+                    });
+                    for (p, t, vn) in triangle {
+                        let vertex = || Vertex3D {
+                            position: positions[p],
+                            normal: vn.map(|vn| normals[vn]).or(flat_normal).unwrap(),
+                            tex_coords: t.map(|t| tex_coords[t]).unwrap_or_default(),
+                            color: colors[p].map_or([1.0; 4], |[r, g, b]| [r, g, b, 1.0]),
+                        };
+                        let index = match vn {
+                            Some(vn) => *shared.entry((p, t, vn)).or_insert_with(|| {
+                                vertices.push(vertex());
+                                vertices.len() as u32 - 1
+                            }),
+                            None => {
+                                vertices.push(vertex());
+                                vertices.len() as u32 - 1
+                            }
+                        };
+                        indices.push(index);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(MeshData::new(vertices, Some(indices)))
+}
+
+/// Parses OBJ data and uploads it as a mesh
+///
+/// Takes the file contents, so it works with bytes fetched on the web as
+/// well as with files embedded via `include_bytes!`.
+pub fn load_obj(
+    device: &wgpu::Device,
+    bytes: &[u8],
+    label: Option<&str>,
+) -> anyhow::Result<Mesh<Vertex3D, u32>> {
+    let src = std::str::from_utf8(bytes).context("OBJ data is not valid UTF-8")?;
+    Ok(parse_obj(src)?.upload(device, label))
+}
+
+/// Reads an OBJ file from disk and uploads it as a mesh, see [`load_obj`]
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_obj_file(
+    device: &wgpu::Device,
+    path: impl AsRef<std::path::Path>,
+) -> anyhow::Result<Mesh<Vertex3D, u32>> {
+    let path = path.as_ref();
+    let bytes =
+        std::fs::read(path).with_context(|| format!("Unable to read {}", path.display()))?;
+    let label = path.to_string_lossy();
+    load_obj(device, &bytes, Some(&label))
+}
+
+fn parse_floats<'a>(words: impl Iterator<Item = &'a str>) -> anyhow::Result<Vec<f32>> {
+    Ok(words.map(str::parse).collect::<Result<_, _>>()?)
+}
+
+/// Parses a face corner (`v`, `v/vt`, `v//vn` or `v/vt/vn`) into 0-based indices
+fn parse_corner(
+    word: &str,
+    positions: usize,
+    tex_coords: usize,
+    normals: usize,
+) -> anyhow::Result<(usize, Option<usize>, Option<usize>)> {
+    let mut parts = word.split('/');
+    let position = resolve_index(parts.next().unwrap_or_default(), positions)?;
+    let tex_coord = match parts.next() {
+        None | Some("") => None,
+        Some(t) => Some(resolve_index(t, tex_coords)?),
+    };
+    let normal = match parts.next() {
+        None | Some("") => None,
+        Some(n) => Some(resolve_index(n, normals)?),
+    };
+    Ok((position, tex_coord, normal))
+}
+
+/// Converts a 1-based or negative (relative to the end) OBJ index
+fn resolve_index(word: &str, len: usize) -> anyhow::Result<usize> {
+    let index: i64 = word.parse()?;
+    let resolved = if index < 0 {
+        len as i64 + index
+    } else {
+        index - 1
+    };
+    if !(0..len as i64).contains(&resolved) {
+        bail!("Index {index} is out of range");
+    }
+    Ok(resolved as usize)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn quad_with_normals_shares_vertices() {
+        let src = "
+            # A unit quad
+            v 0 0 0
+            v 1 0 0
+            v 1 1 0
+            v 0 1 0
+            vt 0 0
+            vt 1 1
+            vn 0 0 1
+            f 1/1/1 2/1/1 3/2/1 4/2/1
+        ";
+        let mesh = parse_obj(src).unwrap();
+        assert_eq!(mesh.vertices.len(), 4);
+        assert_eq!(mesh.indices, Some(vec![0, 1, 2, 0, 2, 3]));
+        assert_eq!(mesh.vertices[0].tex_coords, [0.0, 1.0]);
+        assert_eq!(mesh.vertices[2].tex_coords, [1.0, 0.0]);
+    }
+
+    #[test]
+    fn flat_normals_and_negative_indices() {
+        let src = "v 0 0 0\nv 0 1 0\nv 0 0 1 1 0 0\nf -3 -2 -1\n";
+        let mesh = parse_obj(src).unwrap();
+        assert_eq!(mesh.vertices.len(), 3);
+        assert!(mesh.vertices.iter().all(|v| v.normal == [1.0, 0.0, 0.0]));
+        assert_eq!(mesh.vertices[2].color, [1.0, 0.0, 0.0, 1.0]);
+
+        assert!(parse_obj("v 0 0 0\nf 1 2 3\n").is_err());
+    }
+}
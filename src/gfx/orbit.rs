@@ -0,0 +1,217 @@
+//! Mouse driven orbit camera controller
+
+use std::f32::consts::FRAC_PI_2;
+use std::sync::{Arc, Weak};
+
+use parking_lot::Mutex;
+
+use crate::EVENTS;
+use crate::events::{MouseButton, MouseButtonData, MouseMoveData, MouseWheelData};
+use crate::gfx::Camera;
+use crate::observer::{FnSubscriber, Subscriber, Subscription};
+
+/// Rotates a camera around a target while a mouse button is dragged and
+/// dollies it in and out with the mouse wheel
+///
+/// The camera position is given by `yaw` around the Y axis, `pitch` above
+/// the XZ plane and `distance` from `target`. With zero yaw and pitch the
+/// camera sits on the `+Z` side of the target.
+///
+/// ```ignore
+/// let orbit = gfx::OrbitController::new(glam::Vec3::ZERO, 5.0).subscribe();
+/// // Every frame:
+/// orbit.lock().apply(&mut camera);
+/// ```
+#[derive(Debug, Clone)]
+pub struct OrbitController {
+    /// Point the camera orbits around and looks at
+    pub target: glam::Vec3,
+    /// Mouse button that rotates the camera while held, `Left` by default
+    pub button: MouseButton,
+    /// Rotation in radians per pixel of mouse movement
+    pub rotate_speed: f32,
+    /// Fraction of the distance traveled per unit of scrolling
+    pub zoom_speed: f32,
+    yaw: f32,
+    pitch: f32,
+    distance: f32,
+    min_distance: f32,
+    max_distance: f32,
+    dragging: bool,
+}
+impl OrbitController {
+    /// Largest pitch in radians, just short of the poles where the view would flip
+    pub const MAX_PITCH: f32 = FRAC_PI_2 - 0.01;
+
+    /// Creates a controller looking at `target` from `distance` units away
+    pub fn new(target: glam::Vec3, distance: f32) -> Self {
+        let mut controller = Self {
+            target,
+            button: MouseButton::Left,
+            rotate_speed: 0.005,
+            zoom_speed: 0.1,
+            yaw: 0.0,
+            pitch: 0.0,
+            distance: 0.0,
+            min_distance: 0.01,
+            max_distance: f32::INFINITY,
+            dragging: false,
+        };
+        controller.set_distance(distance);
+        controller
+    }
+    /// Returns the rotation around the Y axis in radians
+    #[inline]
+    pub fn yaw(&self) -> f32 {
+        self.yaw
+    }
+    pub fn set_yaw(&mut self, yaw: f32) {
+        self.yaw = yaw;
+    }
+    /// Returns the angle above the XZ plane in radians
+    #[inline]
+    pub fn pitch(&self) -> f32 {
+        self.pitch
+    }
+    /// Sets the pitch, clamped to `-MAX_PITCH..=MAX_PITCH`
+    pub fn set_pitch(&mut self, pitch: f32) {
+        self.pitch = pitch.clamp(-Self::MAX_PITCH, Self::MAX_PITCH);
+    }
+    /// Returns the distance between the camera and the target
+    #[inline]
+    pub fn distance(&self) -> f32 {
+        self.distance
+    }
+    /// Sets the distance, clamped to the distance limits
+    pub fn set_distance(&mut self, distance: f32) {
+        self.distance = distance.clamp(self.min_distance, self.max_distance);
+    }
+    /// Limits how close and how far the camera may get, 0.01 to infinity by default
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min` is not positive or greater than `max`.
+    pub fn set_distance_limits(&mut self, min: f32, max: f32) {
+        assert!(
+            min > 0.0 && min <= max,
+            "Invalid distance limits {min}..={max}"
+        );
+        self.min_distance = min;
+        self.max_distance = max;
+        self.set_distance(self.distance);
+    }
+    /// Rotates the camera by a mouse movement of `delta` pixels
+    pub fn rotate(&mut self, delta: glam::Vec2) {
+        self.yaw -= delta.x * self.rotate_speed;
+        self.set_pitch(self.pitch + delta.y * self.rotate_speed);
+    }
+    /// Dollies the camera towards the target, negative amounts move it away
+    pub fn zoom(&mut self, amount: f32) {
+        self.set_distance(self.distance * (1.0 - self.zoom_speed).powf(amount));
+    }
+    /// Returns the position of the camera
+    pub fn position(&self) -> glam::Vec3 {
+        let (sin_yaw, cos_yaw) = self.yaw.sin_cos();
+        let (sin_pitch, cos_pitch) = self.pitch.sin_cos();
+        let direction = glam::vec3(cos_pitch * sin_yaw, sin_pitch, cos_pitch * cos_yaw);
+        self.target + direction * self.distance
+    }
+    /// Returns the world to view space matrix
+    #[inline]
+    pub fn view(&self) -> glam::Mat4 {
+        glam::Mat4::look_at_rh(self.position(), self.target, glam::Vec3::Y)
+    }
+    /// Moves `camera` to the orbit position, looking at the target
+    pub fn apply(&self, camera: &mut Camera) {
+        camera.look_at(self.position(), self.target);
+        camera.up = glam::Vec3::Y;
+    }
+
+    /// Starts or stops dragging when `button` is pressed or released
+    pub fn handle_mouse_button(&mut self, data: &MouseButtonData) {
+        if data.button == self.button {
+            self.dragging = data.is_pressed;
+        }
+    }
+    /// Rotates the camera while dragging
+    pub fn handle_mouse_move(&mut self, data: &MouseMoveData) {
+        if self.dragging {
+            self.rotate(data.delta);
+        }
+    }
+    /// Dollies the camera by the vertical scroll amount
+    pub fn handle_mouse_wheel(&mut self, data: &MouseWheelData) {
+        self.zoom(data.delta.y);
+    }
+
+    /// Shares the controller and drives it with mouse events
+    ///
+    /// The subscribers only hold a weak reference and unsubscribe once the
+    /// returned controller is dropped.
+    pub fn subscribe(self) -> Arc<Mutex<Self>> {
+        let controller = Arc::new(Mutex::new(self));
+        EVENTS
+            .mouse_button()
+            .subscribe(weak_subscriber(&controller, Self::handle_mouse_button));
+        EVENTS
+            .mouse_move()
+            .subscribe(weak_subscriber(&controller, Self::handle_mouse_move));
+        EVENTS
+            .mouse_wheel()
+            .subscribe(weak_subscriber(&controller, Self::handle_mouse_wheel));
+        controller
+    }
+}
+
+/// Creates a subscriber calling `handler` for as long as `controller` is alive
+fn weak_subscriber<T: Send + 'static>(
+    controller: &Arc<Mutex<OrbitController>>,
+    handler: fn(&mut OrbitController, &T),
+) -> Box<dyn Subscriber<Data = T>> {
+    let controller: Weak<Mutex<OrbitController>> = Arc::downgrade(controller);
+    FnSubscriber::new(move |data: &T| match controller.upgrade() {
+        Some(controller) => {
+            handler(&mut controller.lock(), data);
+            Subscription::Keep
+        }
+        None => Subscription::Unsubscribe,
+    })
+    .boxed()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn drag_rotates_with_clamped_pitch() {
+        let mut orbit = OrbitController::new(glam::Vec3::ZERO, 5.0);
+        let moved = MouseMoveData {
+            position: glam::Vec2::ZERO,
+            logical_position: glam::Vec2::ZERO,
+            delta: glam::vec2(100.0, 10_000.0),
+        };
+        orbit.handle_mouse_move(&moved);
+        assert_eq!((orbit.yaw(), orbit.pitch()), (0.0, 0.0));
+
+        orbit.handle_mouse_button(&MouseButtonData {
+            button: MouseButton::Left,
+            is_pressed: true,
+        });
+        orbit.handle_mouse_move(&moved);
+        assert!(orbit.yaw() < 0.0);
+        assert_eq!(orbit.pitch(), OrbitController::MAX_PITCH);
+        assert!((orbit.position().length() - 5.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn zoom_respects_distance_limits() {
+        let mut orbit = OrbitController::new(glam::vec3(1.0, 0.0, 0.0), 5.0);
+        orbit.set_distance_limits(1.0, 10.0);
+        orbit.zoom(-100.0);
+        assert_eq!(orbit.distance(), 10.0);
+        orbit.zoom(100.0);
+        assert_eq!(orbit.distance(), 1.0);
+        assert_eq!(orbit.position(), glam::vec3(1.0, 0.0, 1.0));
+    }
+}
@@ -0,0 +1,177 @@
+//! Render pass helpers
+//!
+//! `PassTargets` describes the attachments of a render pass together with how
+//! each of them is loaded, so color and depth can be cleared independently.
+//!
+//! For example, an overlay pass drawn after the main pass keeps the color
+//! buffer but clears depth, so the overlay geometry always ends up on top:
+//!
+//! ```ignore
+//! let targets = gfx::PassTargets::new(&color_view)
+//!     .load_color()
+//!     .with_depth(depth.view())
+//!     .clear_depth(1.0);
+//! let mut rpass = gfx::begin_pass(&mut encoder, &targets, Some("Overlay Pass"));
+//! overlay.draw(&mut rpass);
+//! ```
+
+/// Color and depth attachments of a render pass along with their load operations
+#[derive(Clone)]
+pub struct PassTargets<'a> {
+    /// Color attachment view
+    pub color: &'a wgpu::TextureView,
+    /// How the color attachment is loaded at the start of the pass
+    pub color_load: wgpu::LoadOp<wgpu::Color>,
+    /// Optional multisample resolve target for the color attachment
+    pub resolve_target: Option<&'a wgpu::TextureView>,
+    /// Optional depth attachment view
+    pub depth: Option<&'a wgpu::TextureView>,
+    /// How the depth attachment is loaded at the start of the pass
+    pub depth_load: wgpu::LoadOp<f32>,
+}
+impl<'a> PassTargets<'a> {
+    /// Creates targets for a color attachment cleared to opaque black, without depth
+    #[inline]
+    pub fn new(color: &'a wgpu::TextureView) -> Self {
+        Self {
+            color,
+            color_load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+            resolve_target: None,
+            depth: None,
+            depth_load: wgpu::LoadOp::Clear(1.0),
+        }
+    }
+    /// Clears the color attachment to `color`
+    #[inline]
+    pub fn clear_color(self, color: impl Into<wgpu::Color>) -> Self {
+        Self {
+            color_load: wgpu::LoadOp::Clear(color.into()),
+            ..self
+        }
+    }
+    /// Keeps the existing contents of the color attachment
+    #[inline]
+    pub fn load_color(self) -> Self {
+        Self {
+            color_load: wgpu::LoadOp::Load,
+            ..self
+        }
+    }
+    /// Resolves the (multisampled) color attachment into `target`
+    #[inline]
+    pub fn with_resolve_target(self, target: &'a wgpu::TextureView) -> Self {
+        Self {
+            resolve_target: Some(target),
+            ..self
+        }
+    }
+    /// Adds a depth attachment, cleared to 1.0 unless changed
+    #[inline]
+    pub fn with_depth(self, depth: &'a wgpu::TextureView) -> Self {
+        Self {
+            depth: Some(depth),
+            ..self
+        }
+    }
+    /// Clears the depth attachment to `value`
+    #[inline]
+    pub fn clear_depth(self, value: f32) -> Self {
+        Self {
+            depth_load: wgpu::LoadOp::Clear(value),
+            ..self
+        }
+    }
+    /// Keeps the existing contents of the depth attachment
+    #[inline]
+    pub fn load_depth(self) -> Self {
+        Self {
+            depth_load: wgpu::LoadOp::Load,
+            ..self
+        }
+    }
+    /// Returns the operations applied to the color attachment
+    #[inline]
+    pub fn color_ops(&self) -> wgpu::Operations<wgpu::Color> {
+        wgpu::Operations {
+            load: self.color_load,
+            store: wgpu::StoreOp::Store,
+        }
+    }
+    /// Returns the operations applied to the depth attachment
+    #[inline]
+    pub fn depth_ops(&self) -> wgpu::Operations<f32> {
+        wgpu::Operations {
+            load: self.depth_load,
+            store: wgpu::StoreOp::Store,
+        }
+    }
+}
+
+/// Begins a render pass on `encoder` rendering into `targets`
+pub fn begin_pass<'e>(
+    encoder: &'e mut wgpu::CommandEncoder,
+    targets: &PassTargets<'_>,
+    label: Option<&str>,
+) -> wgpu::RenderPass<'e> {
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label,
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: targets.color,
+            depth_slice: None,
+            resolve_target: targets.resolve_target,
+            ops: targets.color_ops(),
+        })],
+        depth_stencil_attachment: targets.depth.map(|view| {
+            wgpu::RenderPassDepthStencilAttachment {
+                view,
+                depth_ops: Some(targets.depth_ops()),
+                stencil_ops: None,
+            }
+        }),
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gfx::noop_device;
+
+    fn view(device: &wgpu::Device, format: wgpu::TextureFormat) -> wgpu::TextureView {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d {
+                width: 4,
+                height: 4,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        texture.create_view(&Default::default())
+    }
+
+    #[test]
+    fn overlay_keeps_color_and_clears_depth() {
+        // Command encoders need the queue to be alive
+        let (device, _queue) = noop_device();
+        let color = view(&device, wgpu::TextureFormat::Rgba8Unorm);
+        let depth = view(&device, wgpu::TextureFormat::Depth32Float);
+        let targets = PassTargets::new(&color)
+            .load_color()
+            .with_depth(&depth)
+            .clear_depth(1.0);
+        assert_eq!(targets.color_ops().load, wgpu::LoadOp::Load);
+        assert_eq!(targets.depth_ops().load, wgpu::LoadOp::Clear(1.0));
+        assert!(targets.resolve_target.is_none());
+
+        let mut encoder = device.create_command_encoder(&Default::default());
+        drop(begin_pass(&mut encoder, &targets, Some("Overlay Pass")));
+        encoder.finish();
+    }
+}
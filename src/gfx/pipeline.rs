@@ -0,0 +1,319 @@
+//! Render pipeline construction
+
+use std::collections::HashMap;
+
+use crate::gfx::{
+    DEFAULT_FRAGMENT_ENTRY, DEFAULT_VERTEX_ENTRY, RenderConfig, VertexInfo, check_entry_point,
+    check_overrides, constant_pairs,
+};
+
+/// A `wgpu::RenderPipeline` that knows how many bind groups its layout expects
+///
+/// Created by [`RenderPipelineBuilder`] and dereferences to the wrapped
+/// pipeline, so it can be passed to `RenderPass::set_pipeline` directly.
+#[derive(educe::Educe)]
+#[educe(Deref)]
+pub struct RenderPipeline {
+    #[educe(Deref)]
+    pipeline: wgpu::RenderPipeline,
+    bind_group_count: usize,
+}
+impl RenderPipeline {
+    /// Returns the number of bind group layouts the pipeline was created with
+    #[inline]
+    pub fn bind_group_count(&self) -> usize {
+        self.bind_group_count
+    }
+    /// Sets the pipeline and all of its bind groups, starting at group index 0
+    ///
+    /// In debug builds the number of groups is checked against the number of
+    /// layouts, turning an opaque validation error into a clear panic.
+    ///
+    /// ```ignore
+    /// pipeline.bind_all(rpass, &bind_groups);
+    /// ```
+    ///
+    /// # Arguments
+    /// * `rpass` - The render pass to set the pipeline on
+    /// * `groups` - The bind groups, in group index order
+    ///
+    /// # Panics
+    /// Panics in debug builds if the number of groups differs from `bind_group_count`
+    pub fn bind_all<'g, I>(&self, rpass: &mut wgpu::RenderPass<'_>, groups: I)
+    where
+        I: IntoIterator<Item = &'g wgpu::BindGroup>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let groups = groups.into_iter();
+        debug_assert_eq!(
+            self.bind_group_count,
+            groups.len(),
+            "Bind group count mismatch: pipeline layout expects {} bind group(s), but {} were provided",
+            self.bind_group_count,
+            groups.len(),
+        );
+        rpass.set_pipeline(&self.pipeline);
+        for (i, group) in groups.enumerate() {
+            rpass.set_bind_group(i as u32, group, &[]);
+        }
+    }
+}
+
+/// Builds a [`RenderPipeline`] with one vertex buffer and one color target
+///
+/// An optional second, per-instance vertex buffer is added with
+/// [`RenderPipelineBuilder::instance_info`].
+///
+/// The defaults draw filled triangle lists with counter-clockwise front
+/// faces, back-face culling, no depth test, a single sample and `REPLACE`
+/// blending, using the `vs_main` and `fs_main` entry points.
+///
+/// ```ignore
+/// let pipeline = gfx::RenderPipelineBuilder::new(
+///     &state.device,
+///     &module,
+///     vertex_info.as_ref(),
+///     format,
+///     &[&layout],
+/// )
+/// .render_config(&state.render_config())
+/// .build();
+/// ```
+///
+/// [`RenderPipelineBuilder::build_checked`] additionally checks the builder
+/// against the shader source and returns an error instead of letting wgpu
+/// panic on a missing entry point or an unknown override constant.
+pub struct RenderPipelineBuilder<'a> {
+    device: &'a wgpu::Device,
+    module: &'a wgpu::ShaderModule,
+    vertex_info: &'a dyn VertexInfo,
+    instance_info: Option<&'a dyn VertexInfo>,
+    format: wgpu::TextureFormat,
+    bind_group_layouts: &'a [&'a wgpu::BindGroupLayout],
+    label: Option<&'a str>,
+    vertex_entry: &'a str,
+    fragment_entry: &'a str,
+    primitive: wgpu::PrimitiveState,
+    depth_stencil: Option<wgpu::DepthStencilState>,
+    multisample: wgpu::MultisampleState,
+    blend: Option<wgpu::BlendState>,
+    write_mask: wgpu::ColorWrites,
+    constants: HashMap<String, f64>,
+    push_constant_ranges: Vec<wgpu::PushConstantRange>,
+}
+impl<'a> RenderPipelineBuilder<'a> {
+    /// Creates a builder with the default state
+    ///
+    /// # Arguments
+    /// * `device` - The device to create the pipeline with
+    /// * `module` - The shader module containing both entry points
+    /// * `vertex_info` - The layout of the single vertex buffer
+    /// * `format` - The format of the color target, usually the surface format
+    /// * `bind_group_layouts` - The bind group layouts, in group index order
+    pub fn new(
+        device: &'a wgpu::Device,
+        module: &'a wgpu::ShaderModule,
+        vertex_info: &'a dyn VertexInfo,
+        format: wgpu::TextureFormat,
+        bind_group_layouts: &'a [&'a wgpu::BindGroupLayout],
+    ) -> Self {
+        Self {
+            device,
+            module,
+            vertex_info,
+            instance_info: None,
+            format,
+            bind_group_layouts,
+            label: None,
+            vertex_entry: DEFAULT_VERTEX_ENTRY,
+            fragment_entry: DEFAULT_FRAGMENT_ENTRY,
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                unclipped_depth: false,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            blend: Some(wgpu::BlendState::REPLACE),
+            write_mask: wgpu::ColorWrites::ALL,
+            constants: HashMap::new(),
+            push_constant_ranges: Vec::new(),
+        }
+    }
+    /// Sets the debug label of the pipeline and its layout
+    pub fn label(mut self, label: &'a str) -> Self {
+        self.label = Some(label);
+        self
+    }
+    /// Sets the names of the vertex and fragment entry points
+    pub fn entry_points(mut self, vertex: &'a str, fragment: &'a str) -> Self {
+        self.vertex_entry = vertex;
+        self.fragment_entry = fragment;
+        self
+    }
+    /// Adds a per-instance vertex buffer at slot 1 laid out as `info`
+    ///
+    /// Its shader locations must not overlap those of the vertex buffer.
+    pub fn instance_info(mut self, info: &'a dyn VertexInfo) -> Self {
+        self.instance_info = Some(info);
+        self
+    }
+    /// Sets the primitive topology, `TriangleList` by default
+    pub fn topology(mut self, topology: wgpu::PrimitiveTopology) -> Self {
+        self.primitive.topology = topology;
+        self
+    }
+    /// Sets the winding order of front faces, `Ccw` by default
+    pub fn front_face(mut self, front_face: wgpu::FrontFace) -> Self {
+        self.primitive.front_face = front_face;
+        self
+    }
+    /// Sets the faces to cull, `None` draws both sides
+    pub fn cull_mode(mut self, cull_mode: Option<wgpu::Face>) -> Self {
+        self.primitive.cull_mode = cull_mode;
+        self
+    }
+    /// Sets how polygons are rasterized, e.g. `Line` for wireframes
+    pub fn polygon_mode(mut self, polygon_mode: wgpu::PolygonMode) -> Self {
+        self.primitive.polygon_mode = polygon_mode;
+        self
+    }
+    /// Enables the depth test against a depth attachment of `format`
+    pub fn depth(
+        mut self,
+        format: wgpu::TextureFormat,
+        write: bool,
+        compare: wgpu::CompareFunction,
+    ) -> Self {
+        self.depth_stencil = Some(wgpu::DepthStencilState {
+            format,
+            depth_write_enabled: write,
+            depth_compare: compare,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        });
+        self
+    }
+    /// Sets the multisample state, which must match the render pass attachments
+    pub fn multisample(mut self, multisample: wgpu::MultisampleState) -> Self {
+        self.multisample = multisample;
+        self
+    }
+    /// Matches the depth and multisample state to the main render pass
+    pub fn render_config(mut self, config: &RenderConfig) -> Self {
+        self.depth_stencil = config.depth_stencil();
+        self.multisample = config.multisample();
+        self
+    }
+    /// Sets the blend state of the color target, `None` disables blending
+    pub fn blend(mut self, blend: Option<wgpu::BlendState>) -> Self {
+        self.blend = blend;
+        self
+    }
+    /// Sets the color channels the pipeline writes, all by default
+    pub fn write_mask(mut self, write_mask: wgpu::ColorWrites) -> Self {
+        self.write_mask = write_mask;
+        self
+    }
+    /// Sets a pipeline-overridable constant, see [`constant_pairs`]
+    pub fn constant(mut self, name: &str, value: f64) -> Self {
+        self.constants.insert(name.to_owned(), value);
+        self
+    }
+    /// Declares a push constant range visible to `stages`
+    ///
+    /// Requires `GfxState::supports_push_constants`.
+    pub fn push_constants(
+        mut self,
+        stages: wgpu::ShaderStages,
+        range: std::ops::Range<u32>,
+    ) -> Self {
+        self.push_constant_ranges
+            .push(wgpu::PushConstantRange { stages, range });
+        self
+    }
+    /// Checks the entry points and constants against the shader source, then
+    /// builds the pipeline
+    ///
+    /// # Arguments
+    /// * `source` - The preprocessed WGSL of `module`, see [`make_shader_code`](crate::gfx::make_shader_code)
+    ///
+    /// # Returns
+    ///
+    /// Returns an error if `source` cannot be parsed, lacks the vertex or
+    /// fragment entry point, or declares no `override` for a constant.
+    ///
+    /// With the `vertex-validation` feature, debug builds also compare the
+    /// vertex inputs to the vertex and instance layouts and log mismatches,
+    /// see [`vertex_input_mismatches`](crate::gfx::vertex_input_mismatches).
+    pub fn build_checked(self, source: &str) -> anyhow::Result<RenderPipeline> {
+        let module = naga::front::wgsl::parse_str(source)?;
+        check_entry_point(&module, naga::ShaderStage::Vertex, self.vertex_entry)?;
+        check_entry_point(&module, naga::ShaderStage::Fragment, self.fragment_entry)?;
+        check_overrides(&module, &self.constants)?;
+        #[cfg(all(feature = "vertex-validation", debug_assertions))]
+        {
+            let mut buffers = vec![self.vertex_info];
+            buffers.extend(self.instance_info);
+            let label = self.label.unwrap_or("<unnamed pipeline>");
+            let mismatches =
+                crate::gfx::check_vertex_inputs(&module, Some(self.vertex_entry), &buffers);
+            for mismatch in mismatches {
+                log::warn!("Vertex layout mismatch in {label}: {mismatch}");
+            }
+        }
+        Ok(self.build())
+    }
+    /// Creates the pipeline layout and the pipeline without further checks
+    pub fn build(self) -> RenderPipeline {
+        let layout = self
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: self.label,
+                bind_group_layouts: self.bind_group_layouts,
+                push_constant_ranges: &self.push_constant_ranges,
+            });
+        let mut buffers = vec![self.vertex_info.describe()];
+        buffers.extend(self.instance_info.map(|info| info.describe()));
+        let constants = constant_pairs(&self.constants);
+        let compilation_options = wgpu::PipelineCompilationOptions {
+            constants: &constants,
+            ..Default::default()
+        };
+        let pipeline = self
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: self.label,
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: self.module,
+                    entry_point: Some(self.vertex_entry),
+                    compilation_options: compilation_options.clone(),
+                    buffers: &buffers,
+                },
+                primitive: self.primitive,
+                depth_stencil: self.depth_stencil,
+                multisample: self.multisample,
+                fragment: Some(wgpu::FragmentState {
+                    module: self.module,
+                    entry_point: Some(self.fragment_entry),
+                    compilation_options,
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: self.format,
+                        blend: self.blend,
+                        write_mask: self.write_mask,
+                    })],
+                }),
+                multiview: None,
+                cache: None,
+            });
+        RenderPipeline {
+            pipeline,
+            bind_group_count: self.bind_group_layouts.len(),
+        }
+    }
+}
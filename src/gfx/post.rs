@@ -0,0 +1,136 @@
+//! Post-processing with ping-pong render targets
+//!
+//! A `PostChain` owns two color targets of the same size. The scene is
+//! rendered into [`PostChain::input`], then every [`PostChain::pass`] reads
+//! the current target and writes the other one before swapping them. Finally
+//! [`PostChain::present`] blits the result onto the frame.
+
+use crate::gfx::{Blitter, Texture2D};
+
+/// Two alternating color targets for chaining fullscreen effects
+pub struct PostChain {
+    format: wgpu::TextureFormat,
+    size: (u32, u32),
+    targets: [Texture2D; 2],
+    /// Blit bind groups sampling `targets[0]` and `targets[1]`
+    blit_groups: [wgpu::BindGroup; 2],
+    blitter: Blitter,
+    /// Index of the target holding the latest result
+    current: usize,
+}
+impl PostChain {
+    /// Creates a chain with targets of `format` and `size`
+    ///
+    /// `present_format` is the format of the render target passed to
+    /// [`PostChain::present`], usually the surface format.
+    pub fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        present_format: wgpu::TextureFormat,
+        size: (u32, u32),
+    ) -> Self {
+        let blitter = Blitter::new(device, present_format);
+        let targets = Self::create_targets(device, format, size);
+        let blit_groups = targets
+            .each_ref()
+            .map(|target| blitter.bind_group(device, target.view()));
+        Self {
+            format,
+            size,
+            targets,
+            blit_groups,
+            blitter,
+            current: 0,
+        }
+    }
+    fn create_targets(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        size: (u32, u32),
+    ) -> [Texture2D; 2] {
+        [Some("PostChain Target A"), Some("PostChain Target B")].map(|label| {
+            Texture2D::new_attachment(
+                device,
+                format,
+                size,
+                wgpu::TextureUsages::TEXTURE_BINDING,
+                label,
+            )
+        })
+    }
+    /// Recreates the targets if `size` differs from the current size
+    ///
+    /// Call this when the surface is resized. The contents are lost.
+    pub fn resize(&mut self, device: &wgpu::Device, size: (u32, u32)) {
+        if size == self.size || size.0 == 0 || size.1 == 0 {
+            return;
+        }
+        self.size = size;
+        self.targets = Self::create_targets(device, self.format, size);
+        self.blit_groups = self
+            .targets
+            .each_ref()
+            .map(|target| self.blitter.bind_group(device, target.view()));
+        self.current = 0;
+    }
+    /// Returns the format of the targets
+    #[inline]
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+    /// Returns the size of the targets
+    #[inline]
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+    /// Returns the target the scene should be rendered into
+    ///
+    /// This is the target holding the latest result, so it should only be
+    /// rendered into before the first [`PostChain::pass`] of a frame.
+    #[inline]
+    pub fn input(&self) -> &Texture2D {
+        &self.targets[self.current]
+    }
+    /// Runs a fullscreen pass reading the current target and writing the other
+    ///
+    /// `bind_group` receives the view of the current target and returns the
+    /// bind group set at index 0. The pipeline must render into `format()`
+    /// and draws three vertices without vertex buffers, so its vertex stage
+    /// has to generate a fullscreen triangle like `vs_fullscreen` in
+    /// `blit.wgsl`.
+    pub fn pass(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        pipeline: &wgpu::RenderPipeline,
+        bind_group: impl FnOnce(&wgpu::TextureView) -> wgpu::BindGroup,
+    ) {
+        let source = &self.targets[self.current];
+        let target = &self.targets[1 - self.current];
+        let bind_group = bind_group(source.view());
+        {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("PostChain Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: target.view(),
+                    depth_slice: None,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            rpass.set_pipeline(pipeline);
+            rpass.set_bind_group(0, &bind_group, &[]);
+            rpass.draw(0..3, 0..1);
+        }
+        self.current = 1 - self.current;
+    }
+    /// Blits the latest result over the whole render target of `rpass`
+    pub fn present(&self, rpass: &mut wgpu::RenderPass<'_>) {
+        self.blitter.blit(rpass, &self.blit_groups[self.current]);
+    }
+}
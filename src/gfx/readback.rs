@@ -0,0 +1,245 @@
+//! GPU to CPU readback helpers
+//!
+//! These functions copy GPU resources into CPU memory. The `_async` variants
+//! work on every target, including the web where blocking is not possible;
+//! the blocking variants are native only and wait for the copy to finish.
+//! Code shared between targets can use the async variants and block on them
+//! natively with `pollster`.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+
+use parking_lot::Mutex;
+
+/// Result of a `map_async` call shared with its callback
+#[derive(Default)]
+struct MapState {
+    result: Option<Result<(), wgpu::BufferAsyncError>>,
+    waker: Option<Waker>,
+}
+
+/// Future resolving once a buffer slice has been mapped
+struct MapFuture(Arc<Mutex<MapState>>);
+impl Future for MapFuture {
+    type Output = Result<(), wgpu::BufferAsyncError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.0.lock();
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Maps `slice` for reading and waits for the mapping to complete
+///
+/// On native the device is polled until the GPU is idle; on the web the
+/// browser resolves the mapping on its own.
+async fn map_read(device: &wgpu::Device, slice: wgpu::BufferSlice<'_>) -> anyhow::Result<()> {
+    let state = Arc::new(Mutex::new(MapState::default()));
+    {
+        let state = state.clone();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let mut state = state.lock();
+            state.result = Some(result);
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        });
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    device.poll(wgpu::PollType::Wait)?;
+    #[cfg(target_arch = "wasm32")]
+    let _ = device;
+    MapFuture(state).await?;
+    Ok(())
+}
+
+/// Copies a 2D texture into CPU memory as an RGBA8 image
+///
+/// The texture must have `COPY_SRC` usage and an 8-bit RGBA or BGRA format,
+/// e.g. a typical surface texture. BGRA data is swizzled to RGBA.
+///
+/// # Returns
+///
+/// Returns the image, or an error if the format is unsupported or mapping fails.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read_texture_rgba8(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+) -> anyhow::Result<image::RgbaImage> {
+    pollster::block_on(read_texture_rgba8_async(device, queue, texture))
+}
+
+/// Async variant of [`read_texture_rgba8`], available on all targets
+pub async fn read_texture_rgba8_async(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+) -> anyhow::Result<image::RgbaImage> {
+    use wgpu::TextureFormat as F;
+
+    let format = texture.format();
+    let is_bgra = match format {
+        F::Rgba8Unorm | F::Rgba8UnormSrgb => false,
+        F::Bgra8Unorm | F::Bgra8UnormSrgb => true,
+        _ => anyhow::bail!("Unsupported texture format for readback: {format:?}"),
+    };
+    let (width, height) = (texture.width(), texture.height());
+    // Rows in the staging buffer must be padded to the copy alignment
+    let unpadded_bytes_per_row = width * 4;
+    let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+        * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+    let staging = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Texture Readback"),
+        size: (padded_bytes_per_row * height) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Texture Readback Encoder"),
+    });
+    encoder.copy_texture_to_buffer(
+        wgpu::TexelCopyTextureInfo {
+            texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        wgpu::TexelCopyBufferInfo {
+            buffer: &staging,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_bytes_per_row),
+                rows_per_image: Some(height),
+            },
+        },
+        wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+    queue.submit(Some(encoder.finish()));
+
+    let slice = staging.slice(..);
+    map_read(device, slice).await?;
+
+    // Strip the row padding while copying into the image
+    let mut pixels = Vec::with_capacity((unpadded_bytes_per_row * height) as usize);
+    {
+        let data = slice.get_mapped_range();
+        for row in data.chunks_exact(padded_bytes_per_row as usize) {
+            pixels.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
+        }
+    }
+    staging.unmap();
+    if is_bgra {
+        pixels.chunks_exact_mut(4).for_each(|p| p.swap(0, 2));
+    }
+    image::RgbaImage::from_raw(width, height, pixels)
+        .ok_or_else(|| anyhow::anyhow!("Readback size mismatch"))
+}
+
+/// Creates a staging buffer for [`read_buffer_into`]
+///
+/// The buffer has `MAP_READ | COPY_DST` usage and can be reused for any
+/// readback of at most `size` bytes.
+pub fn create_readback_buffer(
+    device: &wgpu::Device,
+    size: wgpu::BufferAddress,
+    label: Option<&str>,
+) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label,
+        size,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+/// Copies the contents of a buffer into a newly allocated `Vec`
+///
+/// The buffer must have `COPY_SRC` usage. This creates a staging buffer on
+/// every call; use [`read_buffer_into`] for repeated readbacks.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read_buffer(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    src: &wgpu::Buffer,
+) -> anyhow::Result<Vec<u8>> {
+    pollster::block_on(read_buffer_async(device, queue, src))
+}
+
+/// Async variant of [`read_buffer`], available on all targets
+pub async fn read_buffer_async(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    src: &wgpu::Buffer,
+) -> anyhow::Result<Vec<u8>> {
+    let staging = create_readback_buffer(device, src.size(), Some("Buffer Readback"));
+    let mut data = vec![0; src.size() as usize];
+    read_buffer_into_async(device, queue, src, &staging, &mut data).await?;
+    Ok(data)
+}
+
+/// Copies the contents of a buffer into a caller-owned slice
+///
+/// `staging` is a buffer created with [`create_readback_buffer`] and is kept
+/// by the caller, so repeated readbacks don't allocate. The staging buffer
+/// is unmapped again before this returns.
+///
+/// # Panics
+///
+/// Panics if `dst` is not exactly as long as `src`, or if `staging` is
+/// smaller than `src`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read_buffer_into(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    src: &wgpu::Buffer,
+    staging: &wgpu::Buffer,
+    dst: &mut [u8],
+) -> anyhow::Result<()> {
+    pollster::block_on(read_buffer_into_async(device, queue, src, staging, dst))
+}
+
+/// Async variant of [`read_buffer_into`], available on all targets
+pub async fn read_buffer_into_async(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    src: &wgpu::Buffer,
+    staging: &wgpu::Buffer,
+    dst: &mut [u8],
+) -> anyhow::Result<()> {
+    let size = src.size();
+    assert_eq!(
+        dst.len() as u64,
+        size,
+        "Destination length does not match the buffer size"
+    );
+    assert!(
+        staging.size() >= size,
+        "Staging buffer is smaller than the source buffer"
+    );
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Buffer Readback Encoder"),
+    });
+    encoder.copy_buffer_to_buffer(src, 0, staging, 0, size);
+    queue.submit(Some(encoder.finish()));
+
+    let slice = staging.slice(..size);
+    map_read(device, slice).await?;
+    dst.copy_from_slice(&slice.get_mapped_range());
+    staging.unmap();
+    Ok(())
+}
@@ -0,0 +1,95 @@
+//! Declarative render target configuration
+//!
+//! A `RenderConfig` is returned by `AppClient::render_config` and describes
+//! the attachments of the main render pass. The engine creates its targets
+//! from it, and pipelines drawing into the main pass should build their
+//! depth and multisample state from the same config so they always match.
+
+/// Attachment requirements of the main render pass
+///
+/// The defaults are a single sample, no depth buffer and an automatically
+/// chosen (sRGB if available) surface format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderConfig {
+    /// Number of samples per pixel, 1 disables multisampling
+    ///
+    /// Lowered with a warning if the adapter does not support it, so
+    /// pipelines should use `GfxState::render_config` rather than the config
+    /// returned by the client.
+    pub sample_count: u32,
+    /// Format of the depth buffer, `None` renders without depth
+    ///
+    /// Depth is opt-in, so 2D and headless clients need no setting to go
+    /// without; depth-tested clients use `with_depth(Texture2D::DEPTH_FORMAT)`.
+    /// The engine recreates the buffer on resize and clears it to 1.0 every
+    /// frame unless `GfxState::preserve_depth` is set.
+    pub depth_format: Option<wgpu::TextureFormat>,
+    /// Surface format to use instead of the automatically chosen one
+    ///
+    /// Falls back to the automatic format with a warning if the surface
+    /// does not support it.
+    pub color_format_override: Option<wgpu::TextureFormat>,
+}
+impl RenderConfig {
+    /// Creates the default configuration
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            sample_count: 1,
+            depth_format: None,
+            color_format_override: None,
+        }
+    }
+    /// Sets the number of samples per pixel
+    #[inline]
+    pub const fn with_sample_count(self, sample_count: u32) -> Self {
+        Self {
+            sample_count,
+            ..self
+        }
+    }
+    /// Enables a depth buffer of the given format
+    #[inline]
+    pub const fn with_depth(self, format: wgpu::TextureFormat) -> Self {
+        Self {
+            depth_format: Some(format),
+            ..self
+        }
+    }
+    /// Requests a specific surface format
+    #[inline]
+    pub const fn with_color_format(self, format: wgpu::TextureFormat) -> Self {
+        Self {
+            color_format_override: Some(format),
+            ..self
+        }
+    }
+    /// Returns the multisample state pipelines must use to match the attachments
+    #[inline]
+    pub fn multisample(&self) -> wgpu::MultisampleState {
+        wgpu::MultisampleState {
+            count: self.sample_count,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        }
+    }
+    /// Returns a depth-tested, depth-writing state for the configured depth
+    /// format, or `None` when rendering without depth
+    #[inline]
+    pub fn depth_stencil(&self) -> Option<wgpu::DepthStencilState> {
+        self.depth_format.map(|format| wgpu::DepthStencilState {
+            format,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        })
+    }
+}
+
+impl Default for RenderConfig {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
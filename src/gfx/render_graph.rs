@@ -0,0 +1,278 @@
+//! Frame graphs of passes connected by intermediate textures
+//!
+//! A `RenderGraph` is a list of passes, each reading some textures and
+//! writing one. The graph allocates the intermediate textures at surface
+//! size, runs the passes so that every texture is written before it is read,
+//! and gives the pass writing [`GraphTexture::SURFACE`] the frame's view.
+//!
+//! ```ignore
+//! let mut graph = gfx::RenderGraph::new();
+//! let scene = graph.create_texture("Scene", wgpu::TextureFormat::Rgba16Float);
+//! graph.add_pass("Scene", &[], scene, move |pass| { /* write pass.output */ });
+//! let mut bind_group = None;
+//! graph.add_pass("Tonemap", &[scene], gfx::GraphTexture::SURFACE, move |pass| {
+//!     // Rebuild the bind group sampling the scene whenever it is recreated
+//!     if bind_group.as_ref().is_none_or(|(generation, _)| *generation != pass.generation) {
+//!         bind_group = Some((pass.generation, make_bind_group(pass.device, pass.inputs[0])));
+//!     }
+//!     /* sample pass.inputs[0] and write pass.output */
+//! });
+//! graph.compile()?;
+//! ```
+
+use anyhow::bail;
+
+use crate::gfx::Texture2D;
+
+/// Handle of a texture created with [`RenderGraph::create_texture`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GraphTexture(usize);
+impl GraphTexture {
+    /// The frame's surface view, which the final pass writes
+    pub const SURFACE: Self = Self(usize::MAX);
+}
+
+/// What a pass records its commands with, see [`RenderGraph::add_pass`]
+pub struct GraphPassContext<'a> {
+    /// The device, e.g. to create bind groups for the inputs
+    pub device: &'a wgpu::Device,
+    /// The frame's command encoder
+    pub encoder: &'a mut wgpu::CommandEncoder,
+    /// The input textures, in the order they were given to `add_pass`
+    pub inputs: &'a [&'a Texture2D],
+    /// The view of the output texture or the surface
+    pub output: &'a wgpu::TextureView,
+    /// Changes whenever the intermediate textures are recreated
+    ///
+    /// Resources referring to `inputs`, like bind groups, have to be rebuilt
+    /// when this differs from the value they were created with.
+    pub generation: u64,
+}
+
+/// Records a pass into its context
+type RecordFn = dyn FnMut(&mut GraphPassContext<'_>) + Send;
+
+struct GraphPass {
+    name: String,
+    inputs: Vec<GraphTexture>,
+    output: GraphTexture,
+    record: Box<RecordFn>,
+}
+
+struct GraphTextureDesc {
+    label: String,
+    format: wgpu::TextureFormat,
+    texture: Option<Texture2D>,
+}
+
+/// Passes connected by intermediate textures, ending at the surface
+///
+/// Return one from `AppClient::render_graph` to have `GfxState::render`
+/// execute it instead of the main render pass.
+#[derive(Default)]
+pub struct RenderGraph {
+    textures: Vec<GraphTextureDesc>,
+    passes: Vec<GraphPass>,
+    /// Pass indices in execution order, `None` until compiled
+    order: Option<Vec<usize>>,
+    size: (u32, u32),
+    generation: u64,
+}
+impl RenderGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Declares an intermediate texture of `format` at surface size
+    ///
+    /// The texture can be rendered into and sampled. It is allocated on the
+    /// first execution and recreated, losing its contents, when the surface
+    /// is resized.
+    pub fn create_texture(&mut self, label: &str, format: wgpu::TextureFormat) -> GraphTexture {
+        self.textures.push(GraphTextureDesc {
+            label: label.to_owned(),
+            format,
+            texture: None,
+        });
+        GraphTexture(self.textures.len() - 1)
+    }
+    /// Adds a pass reading `inputs` and writing `output`
+    ///
+    /// `record` runs every frame with a [`GraphPassContext`] holding the
+    /// frame's encoder, the input textures in the order of `inputs` and the
+    /// view of `output`. Since the textures are recreated on resize, bind
+    /// groups sampling them should be rebuilt when the context's
+    /// `generation` changes.
+    pub fn add_pass(
+        &mut self,
+        name: &str,
+        inputs: &[GraphTexture],
+        output: GraphTexture,
+        record: impl FnMut(&mut GraphPassContext<'_>) + Send + 'static,
+    ) {
+        self.passes.push(GraphPass {
+            name: name.to_owned(),
+            inputs: inputs.to_vec(),
+            output,
+            record: Box::new(record),
+        });
+        self.order = None;
+    }
+    /// Returns the number of passes
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.passes.len()
+    }
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.passes.is_empty()
+    }
+    /// Returns an intermediate texture, once it was allocated
+    pub fn texture(&self, handle: GraphTexture) -> Option<&Texture2D> {
+        self.textures.get(handle.0)?.texture.as_ref()
+    }
+    /// Returns the generation of the intermediate textures, see [`GraphPassContext::generation`]
+    #[inline]
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+    /// Orders the passes so that every texture is written before it is read
+    ///
+    /// Called by `execute` if needed; calling it right after building the
+    /// graph reports mistakes early.
+    ///
+    /// # Returns
+    ///
+    /// Returns an error if a texture is read but never written, written by
+    /// more than one pass, the passes depend on each other in a cycle, or
+    /// no pass writes the surface.
+    pub fn compile(&mut self) -> anyhow::Result<()> {
+        if self.order.is_none() {
+            let edges: Vec<_> = self
+                .passes
+                .iter()
+                .map(|pass| (pass.inputs.as_slice(), pass.output))
+                .collect();
+            let order = pass_order(&edges).map_err(|e| {
+                let names: Vec<_> = self.passes.iter().map(|p| p.name.as_str()).collect();
+                e.context(format!("Invalid render graph with passes {names:?}"))
+            })?;
+            self.order = Some(order);
+        }
+        Ok(())
+    }
+    /// Records every pass into `encoder`, writing the surface through `surface_view`
+    ///
+    /// Intermediate textures are (re)allocated to match `size`.
+    pub fn execute(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        surface_view: &wgpu::TextureView,
+        size: (u32, u32),
+    ) -> anyhow::Result<()> {
+        self.compile()?;
+        if size != self.size {
+            self.size = size;
+            for desc in &mut self.textures {
+                desc.texture = None;
+            }
+        }
+        let mut allocated = false;
+        for desc in &mut self.textures {
+            desc.texture.get_or_insert_with(|| {
+                allocated = true;
+                Texture2D::new_attachment(
+                    device,
+                    desc.format,
+                    size,
+                    wgpu::TextureUsages::TEXTURE_BINDING,
+                    Some(&desc.label),
+                )
+            });
+        }
+        if allocated {
+            self.generation += 1;
+        }
+        let textures = &self.textures;
+        let texture = |handle: GraphTexture| textures[handle.0].texture.as_ref().unwrap();
+        for &index in self.order.as_ref().unwrap() {
+            let pass = &mut self.passes[index];
+            let inputs: Vec<_> = pass.inputs.iter().map(|&input| texture(input)).collect();
+            let output = if pass.output == GraphTexture::SURFACE {
+                surface_view
+            } else {
+                texture(pass.output).view()
+            };
+            (pass.record)(&mut GraphPassContext {
+                device,
+                encoder,
+                inputs: &inputs,
+                output,
+                generation: self.generation,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Sorts passes given as `(inputs, output)` so that writers precede readers
+///
+/// Independent passes keep the order they were added in.
+fn pass_order(passes: &[(&[GraphTexture], GraphTexture)]) -> anyhow::Result<Vec<usize>> {
+    let mut writers = std::collections::HashMap::new();
+    for (index, (_, output)) in passes.iter().enumerate() {
+        if let Some(other) = writers.insert(*output, index) {
+            bail!("Passes {other} and {index} write the same texture");
+        }
+    }
+    if !writers.contains_key(&GraphTexture::SURFACE) {
+        bail!("No pass writes the surface");
+    }
+    let mut dependencies = Vec::with_capacity(passes.len());
+    for (index, (inputs, _)) in passes.iter().enumerate() {
+        let mut deps = Vec::with_capacity(inputs.len());
+        for input in inputs.iter() {
+            if *input == GraphTexture::SURFACE {
+                bail!("Pass {index} reads the surface");
+            }
+            match writers.get(input) {
+                Some(&writer) => deps.push(writer),
+                None => bail!(
+                    "Pass {index} reads texture {} which is never written",
+                    input.0
+                ),
+            }
+        }
+        dependencies.push(deps);
+    }
+    let mut done = vec![false; passes.len()];
+    let mut order = Vec::with_capacity(passes.len());
+    while order.len() < passes.len() {
+        let next = (0..passes.len())
+            .find(|&index| !done[index] && dependencies[index].iter().all(|&dep| done[dep]));
+        let Some(next) = next else {
+            bail!("Passes depend on each other in a cycle");
+        };
+        done[next] = true;
+        order.push(next);
+    }
+    Ok(order)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn writers_run_before_readers() {
+        let (a, b) = (GraphTexture(0), GraphTexture(1));
+        let surface = GraphTexture::SURFACE;
+        let passes: [(&[GraphTexture], _); 3] = [(&[a, b], surface), (&[a], b), (&[], a)];
+        assert_eq!(pass_order(&passes).unwrap(), [2, 1, 0]);
+
+        let cycle: [(&[GraphTexture], _); 3] = [(&[b], a), (&[a], b), (&[a], surface)];
+        assert!(pass_order(&cycle).is_err());
+        assert!(pass_order(&[(&[a], surface)]).is_err());
+        assert!(pass_order(&[(&[], a)]).is_err());
+    }
+}
@@ -0,0 +1,144 @@
+//! Texture samplers
+
+/// A `wgpu::Sampler` that remembers which binding type it needs
+///
+/// ```ignore
+/// let sampler = gfx::Sampler::linear_repeat(&state.device);
+/// let (layout, group) = gfx::BindGroupBuilder::new()
+///     .textured(texture.view(), &sampler, wgpu::ShaderStages::FRAGMENT)
+///     .build(&state.device);
+/// ```
+#[derive(educe::Educe)]
+#[educe(Deref)]
+pub struct Sampler {
+    #[educe(Deref)]
+    sampler: wgpu::Sampler,
+    binding_type: wgpu::SamplerBindingType,
+}
+impl Sampler {
+    /// Returns a builder starting from nearest filtering and clamped addressing
+    #[inline]
+    pub fn builder<'a>() -> SamplerBuilder<'a> {
+        SamplerBuilder::default()
+    }
+    /// Bilinear filtering, wrapping around at the edges
+    pub fn linear_repeat(device: &wgpu::Device) -> Self {
+        Self::builder()
+            .label("Linear Repeat Sampler")
+            .filter(wgpu::FilterMode::Linear)
+            .address_mode(wgpu::AddressMode::Repeat)
+            .build(device)
+    }
+    /// Bilinear filtering, clamped to the edges
+    pub fn linear_clamp(device: &wgpu::Device) -> Self {
+        Self::builder()
+            .label("Linear Clamp Sampler")
+            .filter(wgpu::FilterMode::Linear)
+            .build(device)
+    }
+    /// Nearest texel, clamped to the edges, e.g. for pixel art
+    pub fn nearest_clamp(device: &wgpu::Device) -> Self {
+        Self::builder().label("Nearest Clamp Sampler").build(device)
+    }
+    /// Returns the binding type layouts must use for this sampler
+    #[inline]
+    pub fn binding_type(&self) -> wgpu::SamplerBindingType {
+        self.binding_type
+    }
+}
+
+/// Builds a [`Sampler`] with custom address and filter modes
+#[derive(Default)]
+pub struct SamplerBuilder<'a> {
+    desc: wgpu::SamplerDescriptor<'a>,
+}
+impl<'a> SamplerBuilder<'a> {
+    pub fn label(mut self, label: &'a str) -> Self {
+        self.desc.label = Some(label);
+        self
+    }
+    /// Sets the address mode of all three axes
+    pub fn address_mode(self, mode: wgpu::AddressMode) -> Self {
+        self.address_modes(mode, mode, mode)
+    }
+    pub fn address_modes(
+        mut self,
+        u: wgpu::AddressMode,
+        v: wgpu::AddressMode,
+        w: wgpu::AddressMode,
+    ) -> Self {
+        self.desc.address_mode_u = u;
+        self.desc.address_mode_v = v;
+        self.desc.address_mode_w = w;
+        self
+    }
+    /// Sets the magnification, minification and mipmap filters
+    pub fn filter(mut self, mode: wgpu::FilterMode) -> Self {
+        self.desc.mag_filter = mode;
+        self.desc.min_filter = mode;
+        self.desc.mipmap_filter = mode;
+        self
+    }
+    pub fn mag_filter(mut self, mode: wgpu::FilterMode) -> Self {
+        self.desc.mag_filter = mode;
+        self
+    }
+    pub fn min_filter(mut self, mode: wgpu::FilterMode) -> Self {
+        self.desc.min_filter = mode;
+        self
+    }
+    pub fn mipmap_filter(mut self, mode: wgpu::FilterMode) -> Self {
+        self.desc.mipmap_filter = mode;
+        self
+    }
+    /// Limits sampling to the mip levels `min..=max`
+    pub fn lod_clamp(mut self, min: f32, max: f32) -> Self {
+        self.desc.lod_min_clamp = min;
+        self.desc.lod_max_clamp = max;
+        self
+    }
+    /// Makes this a comparison sampler, e.g. for shadow maps
+    pub fn compare(mut self, compare: wgpu::CompareFunction) -> Self {
+        self.desc.compare = Some(compare);
+        self
+    }
+    /// Sets the maximum anisotropy, which requires all filters to be `Linear`
+    pub fn anisotropy(mut self, clamp: u16) -> Self {
+        self.desc.anisotropy_clamp = clamp;
+        self
+    }
+    pub fn build(self, device: &wgpu::Device) -> Sampler {
+        Sampler {
+            sampler: device.create_sampler(&self.desc),
+            binding_type: binding_type(&self.desc),
+        }
+    }
+}
+
+fn binding_type(desc: &wgpu::SamplerDescriptor) -> wgpu::SamplerBindingType {
+    let filters = [desc.mag_filter, desc.min_filter, desc.mipmap_filter];
+    if desc.compare.is_some() {
+        wgpu::SamplerBindingType::Comparison
+    } else if filters.contains(&wgpu::FilterMode::Linear) {
+        wgpu::SamplerBindingType::Filtering
+    } else {
+        wgpu::SamplerBindingType::NonFiltering
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn binding_type_follows_filters() {
+        use wgpu::SamplerBindingType as T;
+
+        let desc = |builder: SamplerBuilder<'static>| binding_type(&builder.desc);
+        assert_eq!(desc(Sampler::builder()), T::NonFiltering);
+        let linear = Sampler::builder().mipmap_filter(wgpu::FilterMode::Linear);
+        assert_eq!(desc(linear), T::Filtering);
+        let shadow = Sampler::builder().compare(wgpu::CompareFunction::Less);
+        assert_eq!(desc(shadow), T::Comparison);
+    }
+}
@@ -0,0 +1,164 @@
+//! A lightweight container of transformed, renderable entities
+//!
+//! `Scene` is not an ECS: it stores a flat list of entities, each with a
+//! `Transform`, an optional parent for hierarchies and an optional
+//! renderable, typically a `Mesh`.
+
+use std::ops::Range;
+
+use crate::gfx::{IndexType, Mesh, Transform, Vertex};
+
+/// Handle of an entity in a `Scene`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Entity(usize);
+
+struct Node<T> {
+    transform: Transform,
+    parent: Option<Entity>,
+    renderable: Option<T>,
+}
+
+/// Entities with transforms, a parent hierarchy and renderables of type `T`
+pub struct Scene<T> {
+    nodes: Vec<Node<T>>,
+}
+impl<T> Scene<T> {
+    /// Creates an empty scene
+    pub fn new() -> Self {
+        Self { nodes: Vec::new() }
+    }
+    /// Adds an entity without a parent
+    pub fn add(&mut self, transform: Transform, renderable: Option<T>) -> Entity {
+        self.nodes.push(Node {
+            transform,
+            parent: None,
+            renderable,
+        });
+        Entity(self.nodes.len() - 1)
+    }
+    /// Returns the number of entities
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+    /// Returns `true` if the scene has no entities
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+    /// Makes `entity` a child of `parent`, or a root entity if `None`
+    ///
+    /// The entity's transform becomes relative to its parent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this would make an entity its own ancestor.
+    pub fn set_parent(&mut self, entity: Entity, parent: Option<Entity>) {
+        let mut ancestor = parent;
+        while let Some(a) = ancestor {
+            assert!(a != entity, "Entity cannot be its own ancestor");
+            ancestor = self.nodes[a.0].parent;
+        }
+        self.nodes[entity.0].parent = parent;
+    }
+    /// Returns the parent of `entity`
+    pub fn parent(&self, entity: Entity) -> Option<Entity> {
+        self.nodes[entity.0].parent
+    }
+    /// Returns the transform of `entity` relative to its parent
+    pub fn transform(&self, entity: Entity) -> &Transform {
+        &self.nodes[entity.0].transform
+    }
+    /// Returns the transform of `entity` relative to its parent for changing it
+    pub fn transform_mut(&mut self, entity: Entity) -> &mut Transform {
+        &mut self.nodes[entity.0].transform
+    }
+    /// Returns the renderable of `entity`
+    pub fn renderable(&self, entity: Entity) -> Option<&T> {
+        self.nodes[entity.0].renderable.as_ref()
+    }
+    /// Sets the renderable of `entity`
+    pub fn set_renderable(&mut self, entity: Entity, renderable: Option<T>) {
+        self.nodes[entity.0].renderable = renderable;
+    }
+    /// Returns the model matrix of `entity` in world space
+    ///
+    /// This combines the transforms of all ancestors.
+    pub fn world_matrix(&self, entity: Entity) -> glam::Mat4 {
+        let node = &self.nodes[entity.0];
+        let local = node.transform.matrix();
+        match node.parent {
+            Some(parent) => self.world_matrix(parent) * local,
+            None => local,
+        }
+    }
+    /// Iterates over all entities with a renderable and their world matrices
+    pub fn renderables(&self) -> impl Iterator<Item = (Entity, glam::Mat4, &T)> {
+        self.nodes.iter().enumerate().filter_map(|(i, node)| {
+            let entity = Entity(i);
+            let renderable = node.renderable.as_ref()?;
+            Some((entity, self.world_matrix(entity), renderable))
+        })
+    }
+}
+impl<T> Default for Scene<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<V: Vertex, I: IndexType> Scene<Mesh<V, I>> {
+    /// Draws every mesh of the scene into `rpass`
+    ///
+    /// `prepare` is called before each draw with the mesh's world matrix,
+    /// e.g. to set push constants or a dynamic uniform offset. The pipeline
+    /// and the camera bindings must already be set on `rpass`.
+    pub fn render(
+        &self,
+        rpass: &mut wgpu::RenderPass<'_>,
+        instances: Range<u32>,
+        mut prepare: impl FnMut(&mut wgpu::RenderPass<'_>, Entity, glam::Mat4),
+    ) {
+        for (entity, model, mesh) in self.renderables() {
+            prepare(rpass, entity, model);
+            mesh.bind(rpass);
+            mesh.draw(instances.clone(), rpass);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn world_matrix_walks_parents() {
+        let mut scene = Scene::<()>::new();
+        let root = scene.add(
+            Transform {
+                translation: glam::vec3(1.0, 0.0, 0.0),
+                ..Default::default()
+            },
+            None,
+        );
+        let child = scene.add(
+            Transform {
+                translation: glam::vec3(0.0, 2.0, 0.0),
+                scale: glam::Vec3::splat(2.0),
+                ..Default::default()
+            },
+            Some(()),
+        );
+        scene.set_parent(child, Some(root));
+        let point = scene.world_matrix(child).transform_point3(glam::Vec3::X);
+        assert_eq!(point, glam::vec3(3.0, 2.0, 0.0));
+        assert_eq!(scene.renderables().count(), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn parent_cycle() {
+        let mut scene = Scene::<()>::new();
+        let a = scene.add(Transform::default(), None);
+        let b = scene.add(Transform::default(), None);
+        scene.set_parent(b, Some(a));
+        scene.set_parent(a, Some(b));
+    }
+}
@@ -11,25 +11,76 @@ pub struct ShaderPath(pub String);
 #[derive(Debug, Clone)]
 pub struct ShaderLib {
     sources: HashMap<ShaderPath, ShaderCode>,
+    /// Symbols for `@define` substitution and `@ifdef` blocks
+    defines: HashMap<String, String>,
 }
 impl ShaderLib {
     #[inline]
     pub fn new() -> Self {
         Self {
             sources: HashMap::new(),
+            defines: HashMap::new(),
         }
     }
+    /// Defines `name` as if the shader started with `/// @define name value`
+    ///
+    /// An empty `value` only makes `name` visible to `@ifdef`.
+    #[inline]
+    pub fn define(&mut self, name: &str, value: &str) {
+        self.defines.insert(name.into(), value.into());
+    }
+    /// Returns the symbols defined with [`ShaderLib::define`]
+    #[inline]
+    pub fn defines(&self) -> &HashMap<String, String> {
+        &self.defines
+    }
     #[inline]
     pub fn insert(&mut self, path: &str, code: &str) {
         self.sources
             .insert(ShaderPath(path.into()), ShaderCode(code.into()));
     }
+    /// Loads every `.wgsl` file below `dir`, including subdirectories
+    ///
+    /// Each file is keyed by its path relative to `dir`, with `/` as the
+    /// separator and without the extension, so `dir/lib/pbr.wgsl` becomes
+    /// `lib/pbr` and is included with `/// @include "lib/pbr"`. See
+    /// [`shader_lib!`](crate::shader_lib) for embedding shaders at compile time.
+    ///
+    /// # Returns
+    ///
+    /// Returns the library, or an error if a directory or file cannot be read.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_dir(dir: impl AsRef<std::path::Path>) -> anyhow::Result<Self> {
+        fn visit(lib: &mut ShaderLib, dir: &std::path::Path, prefix: &str) -> anyhow::Result<()> {
+            for entry in std::fs::read_dir(dir)? {
+                let path = entry?.path();
+                let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                    continue;
+                };
+                if path.is_dir() {
+                    visit(lib, &path, &format!("{prefix}{name}/"))?;
+                } else if let Some(stem) = name.strip_suffix(".wgsl") {
+                    lib.insert(&format!("{prefix}{stem}"), &std::fs::read_to_string(&path)?);
+                }
+            }
+            Ok(())
+        }
+        let mut lib = Self::new();
+        visit(&mut lib, dir.as_ref(), "")?;
+        Ok(lib)
+    }
+    /// Returns the code registered for `path`
+    #[inline]
+    pub fn get(&self, path: &str) -> Option<&ShaderCode> {
+        self.sources.get(&ShaderPath(path.into()))
+    }
 }
 impl FromIterator<(ShaderPath, ShaderCode)> for ShaderLib {
     #[inline]
     fn from_iter<T: IntoIterator<Item = (ShaderPath, ShaderCode)>>(iter: T) -> Self {
         Self {
             sources: HashMap::from_iter(iter),
+            defines: HashMap::new(),
         }
     }
 }
@@ -56,6 +107,13 @@ impl Default for ShaderLib {
     }
 }
 
+/// Entry point name used for the vertex stage unless another is given
+pub const DEFAULT_VERTEX_ENTRY: &str = "vs_main";
+/// Entry point name used for the fragment stage unless another is given
+pub const DEFAULT_FRAGMENT_ENTRY: &str = "fs_main";
+/// Entry point name used for compute shaders unless another is given
+pub const DEFAULT_COMPUTE_ENTRY: &str = "cs_main";
+
 fn default_shaderlib(lib: Option<&ShaderLib>, vertex_info: &dyn VertexInfo) -> ShaderLib {
     let mut lib = lib.cloned().unwrap_or(ShaderLib::new());
     lib.extend([
@@ -66,43 +124,219 @@ fn default_shaderlib(lib: Option<&ShaderLib>, vertex_info: &dyn VertexInfo) -> S
     ]);
     lib
 }
+/// Preprocesses `code` with `proc_shader_code` like `make_shader_module`
+///
+/// The library is extended with `struct/VertexBuf` generated from
+/// `vertex_info`. The result is the WGSL source of the module
+/// `make_shader_module` creates from the same arguments, as expected by
+/// `RenderPipelineBuilder::build_checked`.
+///
+/// # Returns
+///
+/// Returns an error if a preprocessor directive is malformed.
+pub fn make_shader_code(
+    code: &str,
+    vertex_info: &dyn VertexInfo,
+    lib: Option<&ShaderLib>,
+) -> Result<String, ShaderProcessError> {
+    let lib = default_shaderlib(lib, vertex_info);
+    proc_shader_code(code, Some(&lib))
+}
+/// Preprocesses `code` with `proc_shader_code` and creates a shader module
+///
+/// The library is extended with `struct/VertexBuf` generated from
+/// `vertex_info`, see [`make_shader_code`].
+///
+/// # Returns
+///
+/// Returns an error if a preprocessor directive is malformed. WGSL errors are
+/// reported by wgpu as usual.
 pub fn make_shader_module(
     device: &wgpu::Device,
     code: &str,
     vertex_info: &dyn VertexInfo,
     lib: Option<&ShaderLib>,
     label: Option<&str>,
-) -> wgpu::ShaderModule {
-    let lib = default_shaderlib(lib, vertex_info);
-    let code = proc_shader_code(code, Some(&lib));
-    device.create_shader_module(wgpu::ShaderModuleDescriptor {
+) -> Result<wgpu::ShaderModule, ShaderProcessError> {
+    let code = make_shader_code(code, vertex_info, lib)?;
+    Ok(device.create_shader_module(wgpu::ShaderModuleDescriptor {
         label,
         source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(&code)),
-    })
+    }))
+}
+/// Converts pipeline-overridable constants for `PipelineCompilationOptions`
+///
+/// Keys are the names of WGSL `override` declarations, or their numeric
+/// `@id`s. The pairs are sorted by key so pipelines are created
+/// deterministically.
+///
+/// ```ignore
+/// let constants = HashMap::from([("ENABLE_SHADOWS".to_owned(), 1.0)]);
+/// let pairs = gfx::constant_pairs(&constants);
+/// let options = wgpu::PipelineCompilationOptions {
+///     constants: &pairs,
+///     ..Default::default()
+/// };
+/// ```
+pub fn constant_pairs(constants: &HashMap<String, f64>) -> Vec<(&str, f64)> {
+    let mut pairs: Vec<_> = constants.iter().map(|(k, v)| (k.as_str(), *v)).collect();
+    pairs.sort_by(|a, b| a.0.cmp(b.0));
+    pairs
+}
+/// Error returned by `proc_shader_code` for a malformed directive
+///
+/// Line numbers start at 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShaderProcessError {
+    /// The argument of a directive is not enclosed in `"`
+    MissingQuote { line: usize },
+    /// A directive is not followed by a space
+    MissingSpace { line: usize },
+    /// An `@include` refers to a path missing from the `ShaderLib`
+    UnknownInclude { path: String, line: usize },
+    /// A `@define` or `@ifdef` directive has no symbol name
+    MissingName { line: usize },
+    /// An `@endif` without a matching `@ifdef`
+    UnexpectedEndif { line: usize },
+    /// An `@ifdef` block that is never closed with `@endif`
+    UnterminatedIfdef { line: usize },
+}
+impl std::fmt::Display for ShaderProcessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingQuote { line } => {
+                write!(f, "Missing '\"' in @include directive at line {line}")
+            }
+            Self::MissingSpace { line } => {
+                write!(f, "Missing space after directive at line {line}")
+            }
+            Self::UnknownInclude { path, line } => {
+                write!(f, "Unknown include \"{path}\" at line {line}")
+            }
+            Self::MissingName { line } => write!(f, "Missing symbol name at line {line}"),
+            Self::UnexpectedEndif { line } => write!(f, "@endif without @ifdef at line {line}"),
+            Self::UnterminatedIfdef { line } => {
+                write!(f, "@ifdef at line {line} is missing its @endif")
+            }
+        }
+    }
+}
+impl std::error::Error for ShaderProcessError {}
+
+/// State of `proc_shader_code` while walking the lines
+struct ProcState<'a> {
+    out: String,
+    lib: Option<&'a ShaderLib>,
+    defines: HashMap<String, String>,
+    /// Line of each open `@ifdef` and whether its symbol is defined
+    conditions: Vec<(usize, bool)>,
 }
-fn handle_include(out: &mut String, directive: &str, lib: Option<&ShaderLib>) {
-    let directive = directive
+impl ProcState<'_> {
+    /// Returns `false` inside an `@ifdef` block whose symbol isn't defined
+    fn is_active(&self) -> bool {
+        self.conditions.iter().all(|(_, defined)| *defined)
+    }
+    /// Appends `code` with all defined symbols replaced by their values
+    fn push_substituted(&mut self, code: &str) {
+        if self.defines.is_empty() {
+            self.out += code;
+            return;
+        }
+        let is_ident = |c: char| c.is_ascii_alphanumeric() || c == '_';
+        let mut rest = code;
+        while let Some(start) = rest.find(is_ident) {
+            self.out += &rest[..start];
+            rest = &rest[start..];
+            let end = rest.find(|c| !is_ident(c)).unwrap_or(rest.len());
+            let token = &rest[..end];
+            self.out += self.defines.get(token).map_or(token, String::as_str);
+            rest = &rest[end..];
+        }
+        self.out += rest;
+    }
+}
+
+fn handle_include(
+    state: &mut ProcState<'_>,
+    directive: &str,
+    line: usize,
+) -> Result<(), ShaderProcessError> {
+    let path = directive
         .strip_prefix('"')
-        .expect("Missing '\"' in @include directive");
-    let directive = directive
-        .strip_suffix('"')
-        .expect("Missing '\"' in @include directive");
-    let Some(lib) = lib else {
-        return;
-    };
-    if let Some(source) = lib.sources.get(&ShaderPath(directive.into())) {
-        *out += &source.0;
+        .and_then(|d| d.strip_suffix('"'))
+        .ok_or(ShaderProcessError::MissingQuote { line })?;
+    let source = state
+        .lib
+        .and_then(|lib| lib.sources.get(&ShaderPath(path.into())))
+        .ok_or_else(|| ShaderProcessError::UnknownInclude {
+            path: path.into(),
+            line,
+        })?;
+    state.push_substituted(&source.0);
+    Ok(())
+}
+fn handle_define(
+    state: &mut ProcState<'_>,
+    directive: &str,
+    line: usize,
+) -> Result<(), ShaderProcessError> {
+    let (name, value) = directive.split_once(' ').unwrap_or((directive, ""));
+    if name.is_empty() {
+        return Err(ShaderProcessError::MissingName { line });
+    }
+    state.defines.insert(name.into(), value.trim().into());
+    Ok(())
+}
+fn handle_ifdef(
+    state: &mut ProcState<'_>,
+    directive: &str,
+    line: usize,
+) -> Result<(), ShaderProcessError> {
+    if directive.is_empty() {
+        return Err(ShaderProcessError::MissingName { line });
     }
+    let defined = state.defines.contains_key(directive);
+    state.conditions.push((line, defined));
+    Ok(())
+}
+fn handle_endif(
+    state: &mut ProcState<'_>,
+    _directive: &str,
+    line: usize,
+) -> Result<(), ShaderProcessError> {
+    state
+        .conditions
+        .pop()
+        .map(|_| ())
+        .ok_or(ShaderProcessError::UnexpectedEndif { line })
 }
-pub fn proc_shader_code(code: &str, lib: Option<&ShaderLib>) -> String {
+/// Expands the `///`-prefixed preprocessor directives in `code`
+///
+/// * `/// @include "path"` is replaced with the code registered for `path`
+///   in `lib`.
+/// * `/// @define NAME value` replaces every following `NAME` token with
+///   `value`. Symbols can also be defined with [`ShaderLib::define`].
+/// * `/// @ifdef NAME` ... `/// @endif` keeps the enclosed lines only if
+///   `NAME` is defined. Blocks can be nested.
+pub fn proc_shader_code(code: &str, lib: Option<&ShaderLib>) -> Result<String, ShaderProcessError> {
     const SPECIAL_COMMENT: &str = "///";
+    /// Directives that are evaluated inside inactive `@ifdef` blocks
+    const CONDITIONALS: [&str; 2] = ["@ifdef", "@endif"];
 
-    type DirectiveHandler = fn(&mut String, &str, Option<&ShaderLib>);
+    type DirectiveHandler = fn(&mut ProcState<'_>, &str, usize) -> Result<(), ShaderProcessError>;
     let handlers: HashMap<&str, DirectiveHandler> = HashMap::from_iter([
         ("@include", handle_include as DirectiveHandler), //
+        ("@define", handle_define as DirectiveHandler),   //
+        ("@ifdef", handle_ifdef as DirectiveHandler),     //
+        ("@endif", handle_endif as DirectiveHandler),     //
     ]);
 
-    let mut out = String::with_capacity(code.len());
+    let mut state = ProcState {
+        out: String::with_capacity(code.len()),
+        lib,
+        defines: lib.map(|lib| lib.defines.clone()).unwrap_or_default(),
+        conditions: Vec::new(),
+    };
     let n_lines = code.lines().count();
     for (i, l) in code.lines().enumerate() {
         if l.trim().starts_with(SPECIAL_COMMENT) {
@@ -110,26 +344,29 @@ pub fn proc_shader_code(code: &str, lib: Option<&ShaderLib>) -> String {
 
             // Apply each handler
             for (token, handler) in handlers.iter() {
-                if l.starts_with(token) {
+                if l.starts_with(token) && (state.is_active() || CONDITIONALS.contains(token)) {
                     let directive = l.strip_prefix(token).unwrap();
                     // Make sure directive is preceded by a space
-                    if !directive.starts_with(" ") {
-                        panic!("Missing space after directive!");
+                    if !directive.is_empty() && !directive.starts_with(" ") {
+                        return Err(ShaderProcessError::MissingSpace { line: i + 1 });
                     }
                     let directive = directive.trim();
 
                     // Execute handler
-                    handler(&mut out, directive, lib);
+                    handler(&mut state, directive, i + 1)?;
                 }
             }
-        } else {
-            out += l;
+        } else if state.is_active() {
+            state.push_substituted(l);
         }
         if i < n_lines - 1 {
-            out += "\n";
+            state.out += "\n";
         }
     }
-    out
+    if let Some((line, _)) = state.conditions.first() {
+        return Err(ShaderProcessError::UnterminatedIfdef { line: *line });
+    }
+    Ok(state.out)
 }
 
 #[cfg(test)]
@@ -137,8 +374,7 @@ mod test {
     use super::*;
 
     #[test]
-    #[should_panic]
-    fn proc_shader_code_include_panic_1() {
+    fn proc_shader_code_include_error_1() {
         let lines = [
             "432 Hello",                      //
             "World 123",                      //
@@ -150,11 +386,13 @@ mod test {
         lib.insert("greeting", "HELLO\nEARTH");
 
         let input = lines.join("\n");
-        let _ = proc_shader_code(&input, Some(&lib));
+        assert_eq!(
+            proc_shader_code(&input, Some(&lib)),
+            Err(ShaderProcessError::MissingQuote { line: 3 })
+        );
     }
     #[test]
-    #[should_panic]
-    fn proc_shader_code_include_panic_2() {
+    fn proc_shader_code_include_error_2() {
         let lines = [
             "432 Hello",                      //
             "World 123",                      //
@@ -166,11 +404,13 @@ mod test {
         lib.insert("greeting", "HELLO\nEARTH");
 
         let input = lines.join("\n");
-        let _ = proc_shader_code(&input, Some(&lib));
+        assert_eq!(
+            proc_shader_code(&input, Some(&lib)),
+            Err(ShaderProcessError::MissingQuote { line: 3 })
+        );
     }
     #[test]
-    #[should_panic]
-    fn proc_shader_code_include_panic_3() {
+    fn proc_shader_code_include_error_3() {
         let lines = [
             "432 Hello",                     //
             "World 123",                     //
@@ -182,12 +422,14 @@ mod test {
         lib.insert("greeting", "HELLO\nEARTH");
 
         let input = lines.join("\n");
-        let _ = proc_shader_code(&input, Some(&lib));
+        assert_eq!(
+            proc_shader_code(&input, Some(&lib)),
+            Err(ShaderProcessError::MissingQuote { line: 3 })
+        );
     }
 
     #[test]
-    #[should_panic]
-    fn proc_shader_code_include_panic_4() {
+    fn proc_shader_code_include_error_4() {
         let lines = [
             "432 Hello",                      //
             "World 123",                      //
@@ -199,7 +441,10 @@ mod test {
         lib.insert("greeting", "HELLO\nEARTH");
 
         let input = lines.join("\n");
-        let _ = proc_shader_code(&input, Some(&lib));
+        assert_eq!(
+            proc_shader_code(&input, Some(&lib)),
+            Err(ShaderProcessError::MissingSpace { line: 3 })
+        );
     }
 
     #[test]
@@ -215,7 +460,7 @@ mod test {
         lib.insert("greeting", "HELLO\nEARTH");
 
         let input = lines.join("\n");
-        let output = proc_shader_code(&input, Some(&lib));
+        let output = proc_shader_code(&input, Some(&lib)).unwrap();
 
         let mut expected = Vec::from(lines);
         expected.remove(2);
@@ -226,6 +471,81 @@ mod test {
         assert_eq!(expected, output);
     }
 
+    #[test]
+    fn proc_shader_code_unknown_include() {
+        let input = "a\n/// @include \"missing\"";
+        assert_eq!(
+            proc_shader_code(input, Some(&ShaderLib::new())),
+            Err(ShaderProcessError::UnknownInclude {
+                path: "missing".into(),
+                line: 2
+            })
+        );
+    }
+
+    #[test]
+    fn proc_shader_code_defines() {
+        let lines = [
+            "/// @define SCALE 2.0",
+            "let a = SCALE * X_SCALE;",
+            "/// @ifdef LIGHTING",
+            "let lit = true;",
+            "/// @ifdef SHADOWS",
+            "let shadowed = true;",
+            "/// @endif",
+            "/// @endif",
+            "/// @ifdef FOG",
+            "let fog = true;",
+            "/// @endif",
+        ];
+        let mut lib = ShaderLib::new();
+        lib.define("LIGHTING", "");
+        let output = proc_shader_code(&lines.join("\n"), Some(&lib)).unwrap();
+        let expected = ["", "let a = 2.0 * X_SCALE;", "", "let lit = true;"];
+        assert_eq!(output, expected.join("\n") + &"\n".repeat(7));
+
+        assert_eq!(
+            proc_shader_code("/// @endif", None),
+            Err(ShaderProcessError::UnexpectedEndif { line: 1 })
+        );
+        assert_eq!(
+            proc_shader_code("a\n/// @ifdef FOG\nb", None),
+            Err(ShaderProcessError::UnterminatedIfdef { line: 2 })
+        );
+    }
+
+    #[test]
+    fn constant_pairs_sorted() {
+        let constants = HashMap::from([("b".to_owned(), 2.0), ("a".to_owned(), 1.0)]);
+        assert_eq!(constant_pairs(&constants), vec![("a", 1.0), ("b", 2.0)]);
+    }
+
+    #[test]
+    fn shader_lib_from_dir() {
+        let dir = std::env::temp_dir().join(format!("shaderlib_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("lib")).unwrap();
+        std::fs::write(dir.join("common.wgsl"), "COMMON").unwrap();
+        std::fs::write(dir.join("lib/pbr.wgsl"), "PBR").unwrap();
+        std::fs::write(dir.join("lib/notes.txt"), "ignored").unwrap();
+        let lib = ShaderLib::from_dir(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(lib.get("common"), Some(&ShaderCode("COMMON".into())));
+        assert_eq!(lib.get("lib/pbr"), Some(&ShaderCode("PBR".into())));
+        assert_eq!(lib.into_iter().count(), 2);
+    }
+
+    #[test]
+    fn shader_lib_macro() {
+        let lib = crate::shader_lib!("" => ["blit", "shadow"]);
+        assert!(lib.get("blit").unwrap().0.contains("fn vs_fullscreen"));
+        assert!(lib.get("shadow").is_some());
+
+        let dir_lib = crate::shader_lib!("");
+        assert_eq!(dir_lib.get("blit"), lib.get("blit"));
+        assert!(dir_lib.get("sprite").is_some());
+    }
+
     #[test]
     fn proc_shader_code_noop() {
         let lines = [
@@ -235,7 +555,7 @@ mod test {
             "world",     //
         ];
         let input = lines.join("\n");
-        let output = proc_shader_code(&input, None);
+        let output = proc_shader_code(&input, None).unwrap();
         assert_eq!(input, output);
     }
 }
@@ -0,0 +1,145 @@
+//! Hot reloading of shader files during development
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow::{Context, bail};
+use parking_lot::Mutex;
+
+use crate::EVENTS;
+use crate::gfx::{GfxState, ShaderLib, VertexInfoObj, make_shader_module};
+use crate::observer::{FnSubscriber, Subscription};
+
+/// Reloads a WGSL file when it changes on disk
+///
+/// The file is processed with `proc_shader_code` against the `ShaderLib`,
+/// like with `make_shader_module`. If the changed file fails to compile, the
+/// error is logged and the previous module is kept.
+///
+/// ```ignore
+/// let watcher = gfx::ShaderWatcher::new(&state.device, "shaders/mesh.wgsl", Vertex3D::info(), None)?;
+/// watcher.watch(move |state, module| client.rebuild_pipeline(state, module));
+/// ```
+pub struct ShaderWatcher {
+    path: PathBuf,
+    vertex_info: VertexInfoObj,
+    lib: Option<ShaderLib>,
+    modified: Option<SystemTime>,
+    last_check: Instant,
+    module: wgpu::ShaderModule,
+}
+impl ShaderWatcher {
+    /// Minimum time between two checks of the file's modification time
+    pub const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+    /// Loads and compiles the shader at `path`
+    ///
+    /// # Returns
+    ///
+    /// Returns an error if the file cannot be read or does not compile.
+    pub fn new(
+        device: &wgpu::Device,
+        path: impl AsRef<Path>,
+        vertex_info: VertexInfoObj,
+        lib: Option<ShaderLib>,
+    ) -> anyhow::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let modified = modified_time(&path);
+        let module = compile(device, &path, &vertex_info, lib.as_ref())?;
+        Ok(Self {
+            path,
+            vertex_info,
+            lib,
+            modified,
+            last_check: Instant::now(),
+            module,
+        })
+    }
+    /// Returns the path of the watched file
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+    /// Returns the most recent module that compiled successfully
+    #[inline]
+    pub fn module(&self) -> &wgpu::ShaderModule {
+        &self.module
+    }
+    /// Recompiles the shader if the file changed since the last check
+    ///
+    /// Checks at most once per [`ShaderWatcher::POLL_INTERVAL`].
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if a new module was created.
+    pub fn poll(&mut self, device: &wgpu::Device) -> bool {
+        if self.last_check.elapsed() < Self::POLL_INTERVAL {
+            return false;
+        }
+        self.last_check = Instant::now();
+        let modified = modified_time(&self.path);
+        if modified == self.modified {
+            return false;
+        }
+        // Remember the change even on failure so a broken file isn't recompiled every poll
+        self.modified = modified;
+        match compile(device, &self.path, &self.vertex_info, self.lib.as_ref()) {
+            Ok(module) => {
+                log::info!("Reloaded shader {}", self.path.display());
+                self.module = module;
+                true
+            }
+            Err(e) => {
+                log::error!("Keeping previous shader, reload failed: {e:#}");
+                false
+            }
+        }
+    }
+    /// Polls the watcher at the start of every frame
+    ///
+    /// `on_reload` runs after a new module was created, with the state lock
+    /// held, so it must use the given `GfxState` rather than `app().state()`.
+    /// This is where pipelines using the shader should be rebuilt.
+    pub fn watch(self, on_reload: impl Fn(&GfxState, &wgpu::ShaderModule) + Send + 'static) {
+        let watcher = Arc::new(Mutex::new(self));
+        EVENTS.start_of_frame().subscribe(
+            FnSubscriber::new(move |_: &()| {
+                let app = crate::app();
+                let state = app.state();
+                if let Some(state) = state.as_ref() {
+                    let mut watcher = watcher.lock();
+                    if watcher.poll(&state.device) {
+                        on_reload(state, watcher.module());
+                    }
+                }
+                Subscription::Keep
+            })
+            .boxed(),
+        );
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn compile(
+    device: &wgpu::Device,
+    path: &Path,
+    vertex_info: &VertexInfoObj,
+    lib: Option<&ShaderLib>,
+) -> anyhow::Result<wgpu::ShaderModule> {
+    let code = std::fs::read_to_string(path)
+        .with_context(|| format!("Unable to read {}", path.display()))?;
+    let label = path.to_string_lossy();
+    // Capture validation errors instead of letting the uncaptured error handler panic
+    device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let module = make_shader_module(device, &code, vertex_info.as_ref(), lib, Some(&label));
+    let error = pollster::block_on(device.pop_error_scope());
+    let module = module?;
+    if let Some(e) = error {
+        bail!("{}: {e}", path.display());
+    }
+    Ok(module)
+}
@@ -0,0 +1,182 @@
+//! Shadow mapping
+//!
+//! A `ShadowMap` renders scene depth from a light's point of view into a
+//! sampleable depth texture. Clients bind [`ShadowMap::view`] together with
+//! [`ShadowMap::sampler`] in their main pass and compare fragment depths in
+//! light space against it.
+
+use std::marker::PhantomData;
+
+use crate::gfx::{IndexType, Mesh, Texture2D, UniformBuffer, Vertex, make_shader_module};
+
+/// A depth texture together with the depth-only pipeline that fills it
+///
+/// The pipeline reads the vertex position from `@location(0)`, which must be
+/// a `vec3f` field named `position` (as in `Vertex3D`).
+///
+/// # Type Parameters
+/// * `V` - The vertex type of the meshes casting shadows
+pub struct ShadowMap<V: Vertex> {
+    texture: Texture2D,
+    sampler: wgpu::Sampler,
+    pipeline: wgpu::RenderPipeline,
+    light: UniformBuffer<glam::Mat4>,
+    bind_group: wgpu::BindGroup,
+    _vertex: PhantomData<V>,
+}
+impl<V: Vertex> ShadowMap<V> {
+    /// Creates a square shadow map with sides of `size` texels
+    pub fn new(device: &wgpu::Device, size: u32) -> Self {
+        let texture = Texture2D::new_attachment(
+            device,
+            Texture2D::DEPTH_FORMAT,
+            (size, size),
+            wgpu::TextureUsages::TEXTURE_BINDING,
+            Some("Shadow Map"),
+        );
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Shadow Map Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            compare: Some(wgpu::CompareFunction::LessEqual),
+            ..Default::default()
+        });
+        let light = UniformBuffer::new(
+            device,
+            &glam::Mat4::IDENTITY,
+            wgpu::BufferUsages::COPY_DST,
+            Some("Shadow Light Uniform"),
+        );
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Shadow Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Shadow Group"),
+            layout: &layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: light.as_entire_binding(),
+            }],
+        });
+
+        let vertex_info = V::info();
+        let module = make_shader_module(
+            device,
+            include_str!("shadow.wgsl"),
+            vertex_info.as_ref(),
+            None,
+            Some("shadow.wgsl"),
+        )
+        .expect("Invalid directive in shadow.wgsl");
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("shadow.wgsl Layout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("shadow.wgsl Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: Some("vs_shadow"),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                buffers: &[vertex_info.describe()],
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: Texture2D::DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                // Bias depths slightly to avoid shadow acne
+                bias: wgpu::DepthBiasState {
+                    constant: 2,
+                    slope_scale: 2.0,
+                    clamp: 0.0,
+                },
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            fragment: None,
+            multiview: None,
+            cache: None,
+        });
+        Self {
+            texture,
+            sampler,
+            pipeline,
+            light,
+            bind_group,
+            _vertex: Default::default(),
+        }
+    }
+
+    /// Returns the depth texture
+    #[inline]
+    pub fn texture(&self) -> &Texture2D {
+        &self.texture
+    }
+
+    /// Returns a view of the depth texture for sampling in the main pass
+    #[inline]
+    pub fn view(&self) -> &wgpu::TextureView {
+        self.texture.view()
+    }
+
+    /// Returns a comparison sampler (`LessEqual`) for sampling the shadow map
+    #[inline]
+    pub fn sampler(&self) -> &wgpu::Sampler {
+        &self.sampler
+    }
+
+    /// Renders the depth of `meshes` as seen through `light_view_proj`
+    ///
+    /// The shadow map is cleared first, so all shadow casters must be passed
+    /// in a single call.
+    ///
+    /// # Arguments
+    /// * `queue` - The queue used to upload the light matrix
+    /// * `encoder` - The command encoder to record the depth pass into
+    /// * `meshes` - The meshes casting shadows
+    /// * `light_view_proj` - The light's combined view and projection matrix
+    pub fn render<I: IndexType>(
+        &self,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        meshes: &[&Mesh<V, I>],
+        light_view_proj: glam::Mat4,
+    ) {
+        self.light.write_if_changed(queue, 0, &light_view_proj);
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Shadow Pass"),
+            color_attachments: &[],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: self.texture.view(),
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        rpass.set_pipeline(&self.pipeline);
+        rpass.set_bind_group(0, &self.bind_group, &[]);
+        for mesh in meshes {
+            mesh.bind(&mut rpass);
+            mesh.draw(0..1, &mut rpass);
+        }
+    }
+}
@@ -0,0 +1,13 @@
+/// @include "struct/VertexBuf"
+
+struct Light {
+    view_proj: mat4x4f,
+};
+
+@group(0) @binding(0)
+var<uniform> light: Light;
+
+@vertex
+fn vs_shadow(in: VertexBuf) -> @builtin(position) vec4f {
+    return light.view_proj * vec4f(in.position, 1.0);
+}
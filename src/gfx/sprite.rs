@@ -0,0 +1,379 @@
+//! Batched drawing of textured 2D quads
+//!
+//! A `SpriteBatch` collects sprites into one vertex buffer and draws all
+//! consecutive sprites sharing a texture with a single draw call, e.g. for
+//! UI and HUD elements on top of the scene.
+
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::gfx::{
+    Color, DynamicBuffer, RenderConfig, RenderPipeline, RenderPipelineBuilder, Sampler, Texture2D,
+    UniformBuffer, Vertex, Vertex2D, make_shader_module, quad_indices_u32,
+};
+
+/// An axis aligned rectangle
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    /// Top-left corner
+    pub min: glam::Vec2,
+    /// Bottom-right corner
+    pub max: glam::Vec2,
+}
+impl Rect {
+    /// The unit square, i.e. the texture coordinates of a whole texture
+    pub const UNIT: Self = Self::new(glam::Vec2::ZERO, glam::Vec2::ONE);
+
+    #[inline]
+    pub const fn new(min: glam::Vec2, max: glam::Vec2) -> Self {
+        Self { min, max }
+    }
+    /// Creates a rectangle from its top-left corner and size
+    #[inline]
+    pub fn from_position_size(position: glam::Vec2, size: glam::Vec2) -> Self {
+        Self::new(position, position + size)
+    }
+    #[inline]
+    pub fn size(&self) -> glam::Vec2 {
+        self.max - self.min
+    }
+}
+
+/// A texture and sampler bound for drawing with a [`SpriteBatch`]
+///
+/// Cheap to clone. Create it once with [`SpriteBatch::texture`] rather than
+/// every frame.
+#[derive(Clone)]
+pub struct SpriteTexture {
+    bind_group: Arc<wgpu::BindGroup>,
+    size: glam::UVec2,
+}
+impl SpriteTexture {
+    /// Returns the size of the texture in texels
+    #[inline]
+    pub fn size(&self) -> glam::UVec2 {
+        self.size
+    }
+    /// Returns the texture coordinates of a region given in texels, e.g. a
+    /// frame of a sprite sheet
+    pub fn uv_rect(&self, texels: Rect) -> Rect {
+        let size = self.size.as_vec2();
+        Rect::new(texels.min / size, texels.max / size)
+    }
+}
+
+/// Consecutive sprites sharing a texture
+struct SpriteDraw {
+    texture: Arc<wgpu::BindGroup>,
+    indices: Range<u32>,
+}
+
+/// Draws textured, tinted quads in pixel coordinates
+///
+/// Sprites are collected between [`SpriteBatch::begin`] and
+/// [`SpriteBatch::end`], which uploads them, and drawn by
+/// [`SpriteBatch::draw`] in the order they were added. Uploading needs the
+/// device and queue, which are not available while the state is locked in
+/// `AppClient::render`, so call `end` from `update` and only `draw` from
+/// `render`. Each batch holds one set of sprites at a time; use several
+/// batches to draw in more than one place per frame.
+///
+/// Sprites are alpha blended and neither test nor write depth. The
+/// projection maps `(0, 0)` to the top-left corner of the viewport and
+/// `(width, height)` to the bottom-right one.
+///
+/// ```ignore
+/// // Once:
+/// let mut batch = gfx::SpriteBatch::new(&state.device, format, &config, size);
+/// let icon = batch.texture(&state.device, &texture, &gfx::Sampler::linear_clamp(&state.device));
+/// // In update:
+/// batch.begin();
+/// batch.draw_sprite(gfx::Rect::from_position_size(pos, size), &icon, gfx::Color::WHITE);
+/// batch.end(&state.device, &state.queue);
+/// // In render:
+/// batch.draw(rpass);
+/// ```
+pub struct SpriteBatch {
+    pipeline: RenderPipeline,
+    texture_layout: wgpu::BindGroupLayout,
+    projection: UniformBuffer<glam::Mat4>,
+    projection_group: wgpu::BindGroup,
+    vertices: DynamicBuffer<Vertex2D>,
+    indices: DynamicBuffer<u32>,
+    pending_vertices: Vec<Vertex2D>,
+    pending: Vec<SpriteDraw>,
+    uploaded: Vec<SpriteDraw>,
+}
+impl SpriteBatch {
+    /// Creates a batch drawing into targets of `format`
+    ///
+    /// # Arguments
+    /// * `device` - The device to create the pipeline and buffers with
+    /// * `format` - The format of the color target, usually the surface format
+    /// * `config` - The attachments of the render pass the batch draws in
+    /// * `viewport` - The size of the viewport in pixels, see [`SpriteBatch::set_viewport`]
+    pub fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        config: &RenderConfig,
+        viewport: (u32, u32),
+    ) -> Self {
+        let projection = UniformBuffer::new(
+            device,
+            &pixel_projection(viewport.0, viewport.1),
+            wgpu::BufferUsages::COPY_DST,
+            Some("Sprite Projection Uniform"),
+        );
+        let projection_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Sprite Projection Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let projection_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Sprite Projection Group"),
+            layout: &projection_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: projection.as_entire_binding(),
+            }],
+        });
+        let texture_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Sprite Texture Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let vertex_info = Vertex2D::info();
+        let module = make_shader_module(
+            device,
+            include_str!("sprite.wgsl"),
+            vertex_info.as_ref(),
+            None,
+            Some("sprite.wgsl"),
+        )
+        .expect("Invalid directive in sprite.wgsl");
+        let layouts = [&projection_layout, &texture_layout];
+        let mut builder =
+            RenderPipelineBuilder::new(device, &module, vertex_info.as_ref(), format, &layouts)
+                .label("sprite.wgsl Pipeline")
+                .render_config(config)
+                .cull_mode(None)
+                .blend(Some(wgpu::BlendState::ALPHA_BLENDING));
+        if let Some(depth_format) = config.depth_format {
+            builder = builder.depth(depth_format, false, wgpu::CompareFunction::Always);
+        }
+        let pipeline = builder.build();
+
+        let usage = wgpu::BufferUsages::VERTEX;
+        let vertices = DynamicBuffer::new(device, 256, usage, Some("Sprite Vertices"));
+        let usage = wgpu::BufferUsages::INDEX;
+        let indices = DynamicBuffer::new(device, 384, usage, Some("Sprite Indices"));
+        Self {
+            pipeline,
+            texture_layout,
+            projection,
+            projection_group,
+            vertices,
+            indices,
+            pending_vertices: Vec::new(),
+            pending: Vec::new(),
+            uploaded: Vec::new(),
+        }
+    }
+
+    /// Binds `texture` for drawing sprites with it
+    ///
+    /// The texture needs `TEXTURE_BINDING` usage and a filterable float
+    /// format; `sampler` must not be a comparison sampler.
+    pub fn texture(
+        &self,
+        device: &wgpu::Device,
+        texture: &Texture2D,
+        sampler: &Sampler,
+    ) -> SpriteTexture {
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Sprite Texture Group"),
+            layout: &self.texture_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(texture.view()),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+        });
+        SpriteTexture {
+            bind_group: Arc::new(bind_group),
+            size: glam::uvec2(texture.width(), texture.height()),
+        }
+    }
+
+    /// Maps pixel coordinates to a viewport of `width` by `height` pixels
+    ///
+    /// Call this when the surface is resized. Pass the logical size instead
+    /// to position sprites in logical pixels.
+    pub fn set_viewport(&self, queue: &wgpu::Queue, width: u32, height: u32) {
+        self.set_projection(queue, pixel_projection(width, height));
+    }
+    /// Replaces the projection, e.g. with a 2D camera's view projection
+    pub fn set_projection(&self, queue: &wgpu::Queue, projection: glam::Mat4) {
+        self.projection.write_if_changed(queue, 0, &projection);
+    }
+
+    /// Starts collecting a new set of sprites
+    pub fn begin(&mut self) {
+        self.pending_vertices.clear();
+        self.pending.clear();
+    }
+    /// Adds a sprite showing the whole texture
+    ///
+    /// # Arguments
+    /// * `rect` - Where to draw the sprite, in pixels
+    /// * `texture` - The texture to draw
+    /// * `tint` - Linear color the texture is multiplied with, `Color::WHITE` for none
+    pub fn draw_sprite(&mut self, rect: Rect, texture: &SpriteTexture, tint: Color) {
+        self.draw_sprite_uv(rect, Rect::UNIT, texture, tint);
+    }
+    /// Adds a sprite showing the region `uv` of the texture
+    ///
+    /// See [`SpriteTexture::uv_rect`] to get the region from texels.
+    pub fn draw_sprite_uv(&mut self, rect: Rect, uv: Rect, texture: &SpriteTexture, tint: Color) {
+        let quad = (self.pending_vertices.len() / 4) as u32;
+        self.pending_vertices
+            .extend(quad_vertices(rect, uv, tint.to_array()));
+        let indices = quad * 6..quad * 6 + 6;
+        match self.pending.last_mut() {
+            Some(last) if Arc::ptr_eq(&last.texture, &texture.bind_group) => {
+                last.indices.end = indices.end;
+            }
+            _ => self.pending.push(SpriteDraw {
+                texture: texture.bind_group.clone(),
+                indices,
+            }),
+        }
+    }
+    /// Returns the number of sprites added since [`SpriteBatch::begin`]
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.pending_vertices.len() / 4
+    }
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.pending_vertices.is_empty()
+    }
+    /// Uploads the collected sprites for the next [`SpriteBatch::draw`]
+    pub fn end(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let quads = self.len() as u64;
+        if self.indices.len() < quads * 6 {
+            // Indices only depend on the quad count, so they are kept
+            self.indices.clear();
+            self.indices.extend(
+                device,
+                queue,
+                &quad_indices_u32(quads.next_power_of_two() as u32),
+            );
+        }
+        self.vertices.clear();
+        self.vertices.extend(device, queue, &self.pending_vertices);
+        self.uploaded = std::mem::take(&mut self.pending);
+    }
+    /// Draws the sprites uploaded by the last [`SpriteBatch::end`]
+    ///
+    /// The render pass must use the format and attachments the batch was
+    /// created with.
+    pub fn draw(&self, rpass: &mut wgpu::RenderPass<'_>) {
+        if self.uploaded.is_empty() {
+            return;
+        }
+        rpass.set_pipeline(&self.pipeline);
+        rpass.set_bind_group(0, &self.projection_group, &[]);
+        rpass.set_vertex_buffer(0, self.vertices.used_slice());
+        rpass.set_index_buffer(self.indices.used_slice(), wgpu::IndexFormat::Uint32);
+        for draw in &self.uploaded {
+            rpass.set_bind_group(1, &*draw.texture, &[]);
+            rpass.draw_indexed(draw.indices.clone(), 0, 0..1);
+        }
+    }
+}
+
+/// Returns the orthographic projection of a `width` by `height` pixel viewport
+///
+/// `(0, 0)` is the top-left corner and Y points down.
+fn pixel_projection(width: u32, height: u32) -> glam::Mat4 {
+    glam::Mat4::orthographic_rh(
+        0.0,
+        width.max(1) as f32,
+        height.max(1) as f32,
+        0.0,
+        -1.0,
+        1.0,
+    )
+}
+
+/// Returns the corners of a sprite in the order `quad_indices` expects
+fn quad_vertices(rect: Rect, uv: Rect, color: [f32; 4]) -> [Vertex2D; 4] {
+    let corner = |x: f32, y: f32, u: f32, v: f32| Vertex2D {
+        position: [x, y],
+        tex_coords: [u, v],
+        color,
+    };
+    [
+        corner(rect.max.x, rect.min.y, uv.max.x, uv.min.y),
+        corner(rect.min.x, rect.min.y, uv.min.x, uv.min.y),
+        corner(rect.min.x, rect.max.y, uv.min.x, uv.max.y),
+        corner(rect.max.x, rect.max.y, uv.max.x, uv.max.y),
+    ]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pixel_projection_corners() {
+        let projection = pixel_projection(200, 100);
+        let clip = |x, y| projection.project_point3(glam::vec3(x, y, 0.0)).truncate();
+        assert!(clip(0.0, 0.0).abs_diff_eq(glam::vec2(-1.0, 1.0), 1e-6));
+        assert!(clip(200.0, 100.0).abs_diff_eq(glam::vec2(1.0, -1.0), 1e-6));
+        assert!(clip(100.0, 50.0).abs_diff_eq(glam::Vec2::ZERO, 1e-6));
+    }
+
+    #[test]
+    fn quad_corners_match_uvs() {
+        let rect = Rect::from_position_size(glam::vec2(10.0, 20.0), glam::vec2(30.0, 40.0));
+        let uv = Rect::new(glam::vec2(0.5, 0.0), glam::vec2(1.0, 0.5));
+        let quad = quad_vertices(rect, uv, [1.0; 4]);
+        // Top-right first, then counter-clockwise on screen
+        assert_eq!(quad[0].position, [40.0, 20.0]);
+        assert_eq!(quad[0].tex_coords, [1.0, 0.0]);
+        assert_eq!(quad[2].position, [10.0, 60.0]);
+        assert_eq!(quad[2].tex_coords, [0.5, 0.5]);
+    }
+}
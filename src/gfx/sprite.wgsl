@@ -0,0 +1,34 @@
+// Sprite batch shader
+//
+// Transforms pixel coordinates with the batch projection and multiplies
+// the sampled texture by the vertex tint.
+
+/// @include "struct/VertexBuf"
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) tex_coords: vec2f,
+    @location(1) color: vec4f,
+};
+
+@group(0) @binding(0)
+var<uniform> projection: mat4x4f;
+
+@group(1) @binding(0)
+var t_sprite: texture_2d<f32>;
+@group(1) @binding(1)
+var s_sprite: sampler;
+
+@vertex
+fn vs_main(in: VertexBuf) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = projection * vec4f(in.position, 0.0, 1.0);
+    out.tex_coords = in.tex_coords;
+    out.color = in.color;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    return textureSample(t_sprite, s_sprite, in.tex_coords) * in.color;
+}
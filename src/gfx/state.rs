@@ -5,16 +5,26 @@
 
 use std::sync::Arc;
 
+use parking_lot::Mutex;
 use winit::window::Window;
 
+use crate::DeviceLostData;
 use crate::app;
-use crate::gfx::Texture2D;
+use crate::app::{AppClient, ControlFlow};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::gfx::read_texture_rgba8;
+use crate::gfx::{
+    Blitter, Color, GfxStateOptions, PassTargets, RenderConfig, Texture2D, begin_pass,
+    read_texture_rgba8_async,
+};
 
 /// Central rendering state that manages all WGPU resources
 ///
 /// The GfxState struct encapsulates the WGPU adapter, device, queue, and surface.
 /// It handles initialization, resizing, and the main render loop.
 pub struct GfxState {
+    /// WGPU instance the adapter and surfaces were created from
+    instance: wgpu::Instance,
     /// WGPU adapter representing a physical graphics device
     pub adapter: wgpu::Adapter,
     /// WGPU logical device for creating resources
@@ -23,6 +33,10 @@ pub struct GfxState {
     pub queue: wgpu::Queue,
     /// Background clear color for rendering
     pub clear_color: glam::Vec4,
+    /// Keep the previous color contents instead of clearing to `clear_color`
+    pub preserve_color: bool,
+    /// Keep the previous depth contents instead of clearing to 1.0
+    pub preserve_depth: bool,
 
     /// Surface for presenting rendered frames (None for headless)
     pub surface: Option<wgpu::Surface<'static>>,
@@ -31,11 +45,41 @@ pub struct GfxState {
     /// Surface configuration for presentation
     pub config: Option<wgpu::SurfaceConfiguration>,
 
-    /// Depth buffer texture
-    pub depth_buffer: Texture2D,
+    /// Depth buffer texture, present if `RenderConfig::depth_format` is set
+    ///
+    /// Recreated whenever the surface is resized, and cleared to 1.0 at the
+    /// start of every frame unless `preserve_depth` is set.
+    pub depth_buffer: Option<Texture2D>,
 
     /// Internal flag tracking if surface has been configured
     pub(crate) is_surface_configured: bool,
+
+    /// Attachment requirements declared by the client
+    render_config: RenderConfig,
+    /// Multisampled color target resolved into the frame when `sample_count > 1`
+    msaa_target: Option<Texture2D>,
+    /// Internal resolution scale relative to the surface size
+    render_scale: f32,
+    /// Offscreen targets used when rendering below the surface resolution
+    scaled_target: Option<ScaledTarget>,
+    /// Blitter upscaling the offscreen target to the surface
+    blitter: Option<Blitter>,
+    /// Path the next rendered frame is saved to
+    #[cfg(not(target_arch = "wasm32"))]
+    screenshot: Option<std::path::PathBuf>,
+    /// Set by the device lost callback
+    device_lost: Arc<Mutex<Option<DeviceLostData>>>,
+    /// Options the adapter and device were selected with
+    options: GfxStateOptions,
+}
+
+/// Offscreen color and depth targets for rendering at a reduced resolution
+struct ScaledTarget {
+    size: (u32, u32),
+    color: Texture2D,
+    depth: Option<Texture2D>,
+    /// Bind group sampling `color` for the upscaling blit
+    bind_group: wgpu::BindGroup,
 }
 
 impl GfxState {
@@ -51,14 +95,38 @@ impl GfxState {
     /// # Returns
     ///
     /// Returns a configured GfxState instance or an error if initialization fails.
+    #[inline]
     pub async fn new(window: Option<Arc<Window>>) -> anyhow::Result<Self> {
+        Self::with_options(window, GfxStateOptions::default()).await
+    }
+
+    /// Creates a new GfxState with a specific adapter and device configuration
+    ///
+    /// See [`GfxState::new`]. The main window's state is created with
+    /// `AppClientInfo::gfx_options`, completed with the client's main pass
+    /// settings. The surface settings of `options` only apply with a window.
+    ///
+    /// # Returns
+    ///
+    /// Returns an error if no adapter matches, or if the adapter lacks a
+    /// required feature or limit.
+    pub async fn with_options(
+        window: Option<Arc<Window>>,
+        options: GfxStateOptions,
+    ) -> anyhow::Result<Self> {
         let mut size = (0u32, 0u32);
-        // Create WGPU instance with platform-appropriate backends
+        // Vulkan/Metal/DX12 on native. WebGL on web; headless state uses
+        // WebGPU as WebGL lacks compute
+        let default_backends = if cfg!(not(target_arch = "wasm32")) {
+            wgpu::Backends::PRIMARY
+        } else if window.is_some() {
+            wgpu::Backends::GL
+        } else {
+            wgpu::Backends::BROWSER_WEBGPU
+        };
+        let backends = options.backends.unwrap_or(default_backends);
         let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
-            #[cfg(not(target_arch = "wasm32"))]
-            backends: wgpu::Backends::PRIMARY, // Vulkan/Metal/DX12 on native
-            #[cfg(target_arch = "wasm32")]
-            backends: wgpu::Backends::GL, // WebGL on web
+            backends,
             ..Default::default()
         });
         // Create surface from window if provided
@@ -68,38 +136,126 @@ impl GfxState {
             size.1 = s.height;
             instance.create_surface(w.clone()).unwrap()
         });
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::default(),
-                force_fallback_adapter: false,
-                compatible_surface: surface.as_ref(),
-            })
-            .await?;
+        let adapter = match &options.adapter {
+            #[cfg(not(target_arch = "wasm32"))]
+            Some(selector) => {
+                Self::select_adapter(&instance, backends, selector, surface.as_ref())?
+            }
+            selector => {
+                if selector.is_some() {
+                    log::warn!("Adapter selection is not supported on the web");
+                }
+                instance
+                    .request_adapter(&wgpu::RequestAdapterOptions {
+                        power_preference: options.power_preference,
+                        force_fallback_adapter: false,
+                        compatible_surface: surface.as_ref(),
+                    })
+                    .await?
+            }
+        };
+        let info = adapter.get_info();
+        log::info!(
+            "Using adapter `{}` ({:?}, {:?}), driver: {} {}",
+            info.name,
+            info.backend,
+            info.device_type,
+            info.driver,
+            info.driver_info
+        );
+        let missing = options.required_features - adapter.features();
+        if !missing.is_empty() {
+            anyhow::bail!(
+                "Adapter `{}` does not support the required features {missing:?}",
+                info.name
+            );
+        }
+        let mut required_features = options.required_features;
+        let mut required_limits = match options.required_limits.clone() {
+            Some(limits) => {
+                let supported = adapter.limits();
+                let mut exceeded = Vec::new();
+                limits.check_limits_with_fail_fn(&supported, false, |name, requested, allowed| {
+                    exceeded.push(format!("{name} (requested {requested}, allowed {allowed})"));
+                });
+                if !exceeded.is_empty() {
+                    anyhow::bail!(
+                        "Adapter `{}` does not support the required limits: {}",
+                        info.name,
+                        exceeded.join(", ")
+                    );
+                }
+                limits
+            }
+            None if cfg!(target_arch = "wasm32") && window.is_some() => {
+                wgpu::Limits::downlevel_webgl2_defaults()
+            }
+            None => wgpu::Limits::default(),
+        };
+        let push_constant_size = options.push_constant_size;
+        if push_constant_size > 0 {
+            if adapter.features().contains(wgpu::Features::PUSH_CONSTANTS)
+                && push_constant_size <= adapter.limits().max_push_constant_size
+            {
+                required_features |= wgpu::Features::PUSH_CONSTANTS;
+                required_limits.max_push_constant_size = push_constant_size;
+            } else {
+                log::warn!("Push constants of {push_constant_size} bytes are not supported");
+            }
+        }
         let (device, queue) = adapter
             .request_device(&wgpu::DeviceDescriptor {
                 label: None,
-                required_features: wgpu::Features::empty(),
-                required_limits: if cfg!(target_arch = "wasm32") {
-                    wgpu::Limits::downlevel_webgl2_defaults()
-                } else {
-                    wgpu::Limits::default()
-                },
+                required_features,
+                required_limits,
                 memory_hints: Default::default(),
                 trace: wgpu::Trace::Off,
             })
             .await?;
-        let config = surface
-            .as_ref()
-            .map(|surface| Self::initial_surface_config(surface, &adapter, size.0, size.1));
+        let device_lost = Arc::new(Mutex::new(None));
+        {
+            let device_lost = device_lost.clone();
+            device.set_device_lost_callback(move |reason, message| {
+                // Dropping the device reports it as destroyed, which is expected
+                if reason == wgpu::DeviceLostReason::Destroyed {
+                    return;
+                }
+                log::error!("Device lost ({reason:?}): {message}");
+                *device_lost.lock() = Some(DeviceLostData {
+                    reason: format!("{reason:?}"),
+                    message,
+                });
+            });
+        }
+        log::info!("Enabled device features: {:?}", device.features());
+        log::debug!("Device limits: {:?}", device.limits());
 
-        let depth_buffer = Texture2D::new_attachment(
-            &device,
-            Texture2D::DEPTH_FORMAT,
-            size,
-            wgpu::TextureUsages::empty(),
-            Some("Depth Buffer"),
-        );
+        let mut render_config = options.render_config;
+        let clear_color = options.clear_color;
+        let config = surface.as_ref().map(|surface| {
+            Self::initial_surface_config(
+                surface,
+                &adapter,
+                size.0,
+                size.1,
+                render_config.color_format_override,
+                &options.surface_view_formats,
+                options.present_mode,
+            )
+        });
+
+        if let Some(config) = config.as_ref() {
+            let formats = [Some(config.format), render_config.depth_format];
+            render_config.sample_count = Self::supported_sample_count(
+                &adapter,
+                formats.iter().flatten().copied(),
+                render_config.sample_count,
+            );
+        }
+
+        let depth_buffer = Self::create_depth_buffer(&device, &render_config, size);
         Ok(Self {
+            instance,
             adapter,
             device,
             queue,
@@ -107,10 +263,240 @@ impl GfxState {
             window,
             is_surface_configured: false,
             config,
-            clear_color: glam::vec4(0.0, 0.0, 0.0, 1.0),
+            clear_color,
+            preserve_color: false,
+            preserve_depth: false,
             depth_buffer,
+            render_config,
+            msaa_target: None,
+            render_scale: 1.0,
+            scaled_target: None,
+            blitter: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            screenshot: None,
+            device_lost,
+            options,
         })
     }
+    /// Returns the adapter on `backends` matching `selector`
+    #[cfg(not(target_arch = "wasm32"))]
+    fn select_adapter(
+        instance: &wgpu::Instance,
+        backends: wgpu::Backends,
+        selector: &crate::gfx::AdapterSelector,
+        surface: Option<&wgpu::Surface<'_>>,
+    ) -> anyhow::Result<wgpu::Adapter> {
+        let adapters = instance.enumerate_adapters(backends);
+        let names: Vec<_> = adapters.iter().map(|a| a.get_info().name).collect();
+        let Some(adapter) = adapters
+            .into_iter()
+            .enumerate()
+            .find_map(|(i, a)| selector.matches(i, &a.get_info()).then_some(a))
+        else {
+            anyhow::bail!(
+                "No adapter matches {selector:?}, available: [{}]",
+                names.join(", ")
+            );
+        };
+        if let Some(surface) = surface
+            && !adapter.is_surface_supported(surface)
+        {
+            anyhow::bail!(
+                "Adapter `{}` cannot present to the window",
+                adapter.get_info().name
+            );
+        }
+        Ok(adapter)
+    }
+
+    /// Returns `true` if the device has been lost and must be recreated
+    pub fn is_device_lost(&self) -> bool {
+        self.device_lost.lock().is_some()
+    }
+
+    /// Takes the details of a device loss, if one happened
+    pub(crate) fn take_device_lost(&mut self) -> Option<DeviceLostData> {
+        self.device_lost.lock().take()
+    }
+
+    /// Rebuilds the adapter, device, queue and surface
+    ///
+    /// The app calls this automatically when the device is lost and then
+    /// publishes `Events::device_lost`. Every GPU resource created from the
+    /// old device, such as buffers, textures, bind groups and pipelines,
+    /// becomes invalid and must be rebuilt by the client. Settings like the
+    /// clear color and render scale are kept.
+    ///
+    /// If recreating fails the state is left without a surface and renders
+    /// nothing.
+    pub async fn recreate_device(&mut self) -> anyhow::Result<()> {
+        // Release the old surface first, some platforms allow only one per window
+        self.surface = None;
+        self.is_surface_configured = false;
+        let mut state = Self::with_options(self.window.clone(), self.options.clone()).await?;
+        state.clear_color = self.clear_color;
+        state.preserve_color = self.preserve_color;
+        state.preserve_depth = self.preserve_depth;
+        state.render_scale = self.render_scale;
+        if let Some(window) = state.window.as_ref() {
+            let size = window.inner_size();
+            state.resize(size.width, size.height);
+        }
+        *self = state;
+        Ok(())
+    }
+
+    /// Creates a surface for another window, compatible with this state's adapter
+    pub(crate) fn create_surface(
+        &self,
+        window: Arc<Window>,
+    ) -> anyhow::Result<wgpu::Surface<'static>> {
+        let surface = self.instance.create_surface(window)?;
+        if !self.adapter.is_surface_supported(&surface) {
+            anyhow::bail!("The adapter cannot present to this window");
+        }
+        Ok(surface)
+    }
+
+    /// Stops presenting until [`GfxState::resume_surface`] is called
+    pub(crate) fn suspend_surface(&mut self) {
+        self.is_surface_configured = false;
+    }
+    /// Recreates and configures the surface of the window after a suspend
+    ///
+    /// Some platforms, e.g. Android, destroy the native window surface while
+    /// the app is suspended.
+    pub(crate) fn resume_surface(&mut self) -> anyhow::Result<()> {
+        let Some(window) = self.window.clone() else {
+            return Ok(());
+        };
+        self.surface = None;
+        self.surface = Some(self.create_surface(window.clone())?);
+        let size = window.inner_size();
+        self.resize(size.width, size.height);
+        window.request_redraw();
+        Ok(())
+    }
+
+    /// Returns the name, backend, device type and driver of the adapter in use
+    ///
+    /// Worth including in bug reports. See `gfx::enumerate_adapters` for all
+    /// adapters of the system.
+    pub fn adapter_info(&self) -> wgpu::AdapterInfo {
+        self.adapter.get_info()
+    }
+
+    /// Returns the features the device was actually created with
+    ///
+    /// Clients can use this to skip work depending on optional features,
+    /// e.g. timestamp queries, when they were not granted.
+    pub fn features(&self) -> wgpu::Features {
+        self.device.features()
+    }
+
+    /// Returns the limits the device was actually created with
+    pub fn limits(&self) -> wgpu::Limits {
+        self.device.limits()
+    }
+
+    /// Creates a command encoder on the engine's device
+    pub fn create_encoder(&self, label: Option<&str>) -> wgpu::CommandEncoder {
+        self.device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label })
+    }
+
+    /// Finishes `encoder` and submits it to the engine's queue
+    ///
+    /// This can be used outside of the render loop, e.g. for initial uploads
+    /// or mipmap generation. Returns the submission index, which can be
+    /// waited on with `Device::poll`.
+    pub fn submit(&self, encoder: wgpu::CommandEncoder) -> wgpu::SubmissionIndex {
+        self.queue.submit(Some(encoder.finish()))
+    }
+
+    /// Records commands with `f` into a new encoder and submits them immediately
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// state.immediate_submit(Some("Upload"), |encoder| {
+    ///     encoder.copy_buffer_to_buffer(&staging, 0, &target, 0, size);
+    /// });
+    /// ```
+    pub fn immediate_submit(
+        &self,
+        label: Option<&str>,
+        f: impl FnOnce(&mut wgpu::CommandEncoder),
+    ) -> wgpu::SubmissionIndex {
+        let mut encoder = self.create_encoder(label);
+        f(&mut encoder);
+        self.submit(encoder)
+    }
+
+    /// Returns `true` if pipelines may use push constants
+    ///
+    /// This requires a non-zero `AppClientInfo::push_constant_size` and
+    /// adapter support, which WebGL and some native backends lack. Clients
+    /// should fall back to a uniform buffer otherwise.
+    #[inline]
+    pub fn supports_push_constants(&self) -> bool {
+        self.device
+            .features()
+            .contains(wgpu::Features::PUSH_CONSTANTS)
+    }
+
+    /// Returns the render configuration the main pass attachments follow
+    ///
+    /// Pipelines drawing in `AppClient::render` should take their depth and
+    /// multisample state from this, see [`RenderConfig::depth_stencil`] and
+    /// [`RenderConfig::multisample`].
+    pub fn render_config(&self) -> RenderConfig {
+        self.render_config
+    }
+
+    /// Returns the number of samples per pixel of the main pass attachments
+    ///
+    /// This is `RenderConfig::sample_count`, lowered if the adapter does not
+    /// support it for the surface or depth format.
+    #[inline]
+    pub fn sample_count(&self) -> u32 {
+        self.render_config.sample_count
+    }
+
+    /// Returns the highest sample count up to `requested` that every format supports
+    fn supported_sample_count(
+        adapter: &wgpu::Adapter,
+        formats: impl Iterator<Item = wgpu::TextureFormat> + Clone,
+        requested: u32,
+    ) -> u32 {
+        let supported = |count: u32| {
+            formats.clone().all(|format| {
+                adapter
+                    .get_texture_format_features(format)
+                    .flags
+                    .sample_count_supported(count)
+            })
+        };
+        if requested <= 1 || supported(requested) {
+            return requested.max(1);
+        }
+        let fallback = [16, 8, 4, 2]
+            .into_iter()
+            .filter(|&count| count < requested)
+            .find(|&count| supported(count))
+            .unwrap_or(1);
+        log::warn!("Sample count {requested} is not supported, using {fallback}");
+        fallback
+    }
+
+    /// Returns `true` if this state has no window or surface to present to
+    ///
+    /// Headless state is created with `GfxState::new(None)`, e.g. for compute
+    /// jobs. Surface-dependent work such as `render` must be skipped then.
+    #[inline]
+    pub fn is_headless(&self) -> bool {
+        self.surface.is_none()
+    }
 
     /// Creates the initial surface configuration with appropriate format and settings
     ///
@@ -120,32 +506,158 @@ impl GfxState {
     /// * `adapter` - The adapter to query capabilities from
     /// * `width` - Initial width in pixels
     /// * `height` - Initial height in pixels
-    fn initial_surface_config(
+    /// * `format_override` - Format to use instead of the automatic choice
+    /// * `view_formats` - Additional formats surface texture views may use
+    /// * `present_mode` - Preferred presentation mode
+    pub(crate) fn initial_surface_config(
         surface: &wgpu::Surface,
         adapter: &wgpu::Adapter,
         width: u32,
         height: u32,
+        format_override: Option<wgpu::TextureFormat>,
+        view_formats: &[wgpu::TextureFormat],
+        present_mode: wgpu::PresentMode,
     ) -> wgpu::SurfaceConfiguration {
         let surface_caps = surface.get_capabilities(adapter);
+        let format_override = format_override.filter(|f| {
+            let supported = surface_caps.formats.contains(f);
+            if !supported {
+                log::warn!("Surface format {f:?} is not supported, choosing automatically");
+            }
+            supported
+        });
         // Prefer sRGB format for better color accuracy
-        let surface_format = surface_caps
-            .formats
+        let surface_format = format_override.unwrap_or_else(|| {
+            surface_caps
+                .formats
+                .iter()
+                .find(|f| f.is_srgb())
+                .copied()
+                .unwrap_or(surface_caps.formats[0])
+        });
+
+        // Views may only reinterpret the sRGB-ness of the surface format
+        let view_formats = view_formats
             .iter()
-            .find(|f| f.is_srgb())
             .copied()
-            .unwrap_or(surface_caps.formats[0]);
+            .filter(|f| {
+                let compatible = f.remove_srgb_suffix() == surface_format.remove_srgb_suffix();
+                if !compatible {
+                    log::warn!(
+                        "Ignoring surface view format {f:?}, incompatible with {surface_format:?}"
+                    );
+                }
+                compatible && *f != surface_format
+            })
+            .collect();
+
+        // Allow copying frames out of the surface for screenshots where supported
+        let usage = wgpu::TextureUsages::RENDER_ATTACHMENT
+            | (surface_caps.usages & wgpu::TextureUsages::COPY_SRC);
 
         wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            usage,
             format: surface_format,
             width,
             height,
-            present_mode: surface_caps.present_modes[0],
+            present_mode: Self::supported_present_mode(&surface_caps, present_mode),
             alpha_mode: surface_caps.alpha_modes[0],
-            view_formats: vec![],
+            view_formats,
             desired_maximum_frame_latency: 2,
         }
     }
+
+    /// Returns `mode` if the surface supports it, or its first supported mode
+    ///
+    /// The `Auto*` modes are always supported, wgpu resolves them when
+    /// configuring the surface.
+    fn supported_present_mode(
+        caps: &wgpu::SurfaceCapabilities,
+        mode: wgpu::PresentMode,
+    ) -> wgpu::PresentMode {
+        use wgpu::PresentMode::{AutoNoVsync, AutoVsync};
+        if matches!(mode, AutoVsync | AutoNoVsync) || caps.present_modes.contains(&mode) {
+            return mode;
+        }
+        let fallback = caps.present_modes[0];
+        log::warn!("Present mode {mode:?} is not supported, using {fallback:?}");
+        fallback
+    }
+
+    /// Returns the presentation mode of the surface, `None` for headless state
+    pub fn present_mode(&self) -> Option<wgpu::PresentMode> {
+        self.config.as_ref().map(|config| config.present_mode)
+    }
+
+    /// Changes the presentation mode and reconfigures the surface
+    ///
+    /// Unsupported modes fall back to the first supported mode with a
+    /// warning, see `AppClientInfo::present_mode`. Does nothing for headless
+    /// state.
+    pub fn set_present_mode(&mut self, mode: wgpu::PresentMode) {
+        let (Some(surface), Some(config)) = (self.surface.as_ref(), self.config.as_mut()) else {
+            return;
+        };
+        // Keep the requested mode for `recreate_device`
+        self.options.present_mode = mode;
+        let caps = surface.get_capabilities(&self.adapter);
+        config.present_mode = Self::supported_present_mode(&caps, mode);
+        if self.is_surface_configured {
+            surface.configure(&self.device, config);
+        }
+    }
+
+    /// Creates a view of a surface texture, optionally reinterpreted as `format`
+    ///
+    /// # Arguments
+    ///
+    /// * `texture` - The surface texture, e.g. `SurfaceTexture::texture`
+    /// * `format` - The view format, or `None` to use the surface format
+    ///
+    /// # Returns
+    ///
+    /// Returns an error if `format` is neither the surface format nor one of
+    /// the configured `AppClientInfo::surface_view_formats`.
+    pub fn surface_view(
+        &self,
+        texture: &wgpu::Texture,
+        format: Option<wgpu::TextureFormat>,
+    ) -> anyhow::Result<wgpu::TextureView> {
+        let view_formats = self.config.as_ref().map_or(&[][..], |c| &c.view_formats);
+        Self::create_surface_view(texture, view_formats, format)
+    }
+    /// Creates a view of a surface configured with `view_formats`, see [`GfxState::surface_view`]
+    pub(crate) fn create_surface_view(
+        texture: &wgpu::Texture,
+        view_formats: &[wgpu::TextureFormat],
+        format: Option<wgpu::TextureFormat>,
+    ) -> anyhow::Result<wgpu::TextureView> {
+        if let Some(format) = format
+            && format != texture.format()
+        {
+            anyhow::ensure!(
+                view_formats.contains(&format),
+                "Surface view format {format:?} is not in `surface_view_formats`"
+            );
+        }
+        Ok(texture.create_view(&wgpu::TextureViewDescriptor {
+            format,
+            ..Default::default()
+        }))
+    }
+
+    /// Creates a linear (non-sRGB) view of a surface texture
+    ///
+    /// Unless the surface format is linear already, its non-sRGB variant must
+    /// be listed in `AppClientInfo::surface_view_formats`, see
+    /// [`GfxState::surface_view`]. Clients receive such a view every frame in
+    /// `AppClient::encode_surface`.
+    pub fn linear_surface_view(
+        &self,
+        texture: &wgpu::Texture,
+    ) -> anyhow::Result<wgpu::TextureView> {
+        self.surface_view(texture, Some(texture.format().remove_srgb_suffix()))
+    }
     /// Resizes the surface to the new dimensions
     ///
     /// This function updates the surface configuration and reconfigures the surface
@@ -166,7 +678,243 @@ impl GfxState {
             config.height = height;
             surface.configure(&self.device, self.config.as_ref().unwrap());
             self.is_surface_configured = true;
+            // The depth buffer must match the size of the color attachment
+            self.depth_buffer =
+                Self::create_depth_buffer(&self.device, &self.render_config, (width, height));
+        }
+    }
+
+    /// Creates the full resolution depth buffer, if `config` asks for one
+    pub(crate) fn create_depth_buffer(
+        device: &wgpu::Device,
+        config: &RenderConfig,
+        size: (u32, u32),
+    ) -> Option<Texture2D> {
+        config.depth_format.map(|format| {
+            Texture2D::new_multisampled_attachment(
+                device,
+                format,
+                size,
+                config.sample_count,
+                wgpu::TextureUsages::empty(),
+                Some("Depth Buffer"),
+            )
+        })
+    }
+
+    /// Requests that the next rendered frame is saved as an image to `path`
+    ///
+    /// The image format is derived from the file extension. Saving blocks
+    /// until the GPU has finished the frame. Errors are logged, including
+    /// when the surface doesn't support `COPY_SRC`; `render_to_image` works
+    /// regardless.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn request_screenshot(&mut self, path: impl Into<std::path::PathBuf>) {
+        self.screenshot = Some(path.into());
+    }
+
+    /// Saves a presented surface texture to the requested screenshot path
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_screenshot(&mut self, texture: &wgpu::Texture) {
+        let Some(path) = self.screenshot.take() else {
+            return;
+        };
+        // Surfaces are only copyable where their capabilities allow it
+        if !texture.usage().contains(wgpu::TextureUsages::COPY_SRC) {
+            log::error!(
+                "Unable to save screenshot to {}: the surface does not support copies",
+                path.display()
+            );
+            return;
+        }
+        let result = read_texture_rgba8(&self.device, &self.queue, texture).and_then(|image| {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            Ok(image.save(&path)?)
+        });
+        if let Err(e) = result {
+            log::error!("Unable to save screenshot to {}: {e}", path.display());
+        }
+    }
+
+    /// Renders one frame of `client` into an offscreen image
+    ///
+    /// Runs `AppClient::encode` and `AppClient::render` like `render` does,
+    /// but into a color texture of the given size and format instead of the
+    /// surface. The client is passed in rather than taken from `app()`, so
+    /// this also works for headless state without an app, e.g. for
+    /// golden-image tests. Multisampling and depth follow the client's
+    /// `AppClient::render_config`, with the sample count lowered to one
+    /// `format` supports; its color format override is ignored in favor of
+    /// `format`. The format must be an 8-bit RGBA or BGRA format, see
+    /// [`read_texture_rgba8`].
+    ///
+    /// If the state belongs to a running app, the client must not lock the
+    /// state while rendering, as with `render`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn render_to_image(
+        &self,
+        client: &dyn AppClient,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+    ) -> anyhow::Result<image::RgbaImage> {
+        pollster::block_on(self.render_to_image_async(client, width, height, format))
+    }
+
+    /// Async variant of [`GfxState::render_to_image`], available on all targets
+    pub async fn render_to_image_async(
+        &self,
+        client: &dyn AppClient,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+    ) -> anyhow::Result<image::RgbaImage> {
+        let size = (width, height);
+        let mut render_config = client.render_config();
+        let formats = [Some(format), render_config.depth_format];
+        render_config.sample_count = Self::supported_sample_count(
+            &self.adapter,
+            formats.iter().flatten().copied(),
+            render_config.sample_count,
+        );
+        let sample_count = render_config.sample_count;
+        let color = Texture2D::new_attachment(
+            &self.device,
+            format,
+            size,
+            wgpu::TextureUsages::COPY_SRC,
+            Some("Offscreen Color Target"),
+        );
+        let msaa = (sample_count > 1).then(|| {
+            Texture2D::new_multisampled_attachment(
+                &self.device,
+                format,
+                size,
+                sample_count,
+                wgpu::TextureUsages::empty(),
+                Some("Offscreen Multisampled Target"),
+            )
+        });
+        let depth = Self::create_depth_buffer(&self.device, &render_config, size);
+        let (color_view, resolve_target) = match msaa.as_ref() {
+            Some(msaa) => (msaa.view(), Some(color.view())),
+            None => (color.view(), None),
+        };
+
+        let mut encoder = self.create_encoder(Some("Offscreen Render Encoder"));
+        client.encode(&mut encoder);
+        {
+            let targets = PassTargets {
+                color: color_view,
+                color_load: wgpu::LoadOp::Clear(Color(self.clear_color).into()),
+                resolve_target,
+                depth: depth.as_ref().map(Texture2D::view),
+                depth_load: wgpu::LoadOp::Clear(1.0),
+            };
+            let mut render_pass = begin_pass(&mut encoder, &targets, Some("Offscreen Render Pass"));
+            client.render(&mut render_pass);
+        }
+        self.submit(encoder);
+        read_texture_rgba8_async(&self.device, &self.queue, &color).await
+    }
+
+    /// Minimum supported render scale
+    pub const MIN_RENDER_SCALE: f32 = 0.25;
+
+    /// Returns the internal resolution scale relative to the surface size
+    pub fn render_scale(&self) -> f32 {
+        self.render_scale
+    }
+
+    /// Sets the internal resolution scale relative to the surface size
+    ///
+    /// With a scale below 1.0 the frame is rendered into offscreen color and
+    /// depth textures of `surface_size * scale` and then upscaled onto the
+    /// surface. The value is clamped to `MIN_RENDER_SCALE..=1.0` and can be
+    /// changed every frame, e.g. based on the measured frame time.
+    pub fn set_render_scale(&mut self, scale: f32) {
+        self.render_scale = scale.clamp(Self::MIN_RENDER_SCALE, 1.0);
+    }
+
+    /// Returns the size the frame is actually rendered at
+    ///
+    /// This is the surface size multiplied by the render scale, or (0, 0)
+    /// for headless state.
+    pub fn render_size(&self) -> (u32, u32) {
+        let Some(config) = self.config.as_ref() else {
+            return (0, 0);
+        };
+        let scale = |v: u32| ((v as f32 * self.render_scale).round() as u32).max(1);
+        (scale(config.width), scale(config.height))
+    }
+
+    /// Makes sure the offscreen targets match the current render size
+    ///
+    /// Returns `false` if rendering happens directly at surface resolution.
+    fn prepare_scaled_target(&mut self) -> bool {
+        if self.render_scale >= 1.0 {
+            self.scaled_target = None;
+            return false;
+        }
+        let size = self.render_size();
+        if self.scaled_target.as_ref().is_some_and(|t| t.size == size) {
+            return true;
+        }
+        let format = self.config.as_ref().unwrap().format;
+        let blitter = self
+            .blitter
+            .get_or_insert_with(|| Blitter::new(&self.device, format));
+        let color = Texture2D::new_attachment(
+            &self.device,
+            format,
+            size,
+            wgpu::TextureUsages::TEXTURE_BINDING,
+            Some("Scaled Color Target"),
+        );
+        let depth = self.render_config.depth_format.map(|format| {
+            Texture2D::new_multisampled_attachment(
+                &self.device,
+                format,
+                size,
+                self.render_config.sample_count,
+                wgpu::TextureUsages::empty(),
+                Some("Scaled Depth Buffer"),
+            )
+        });
+        let bind_group = blitter.bind_group(&self.device, color.view());
+        self.scaled_target = Some(ScaledTarget {
+            size,
+            color,
+            depth,
+            bind_group,
+        });
+        true
+    }
+
+    /// Makes sure the multisampled color target matches `size`
+    ///
+    /// Does nothing when multisampling is disabled.
+    fn prepare_msaa_target(&mut self, size: (u32, u32)) {
+        if self.render_config.sample_count <= 1 {
+            return;
         }
+        if self
+            .msaa_target
+            .as_ref()
+            .is_some_and(|t| (t.width(), t.height()) == size)
+        {
+            return;
+        }
+        self.msaa_target = Some(Texture2D::new_multisampled_attachment(
+            &self.device,
+            self.config.as_ref().unwrap().format,
+            size,
+            self.render_config.sample_count,
+            wgpu::TextureUsages::empty(),
+            Some("Multisampled Color Target"),
+        ));
     }
 
     /// Executes the main render loop
@@ -178,6 +926,7 @@ impl GfxState {
     ///
     /// Returns Ok(()) on success, or a SurfaceError if rendering fails.
     pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        phase_span!("render");
         // Early return if no window or surface (headless mode)
         let Some(window) = self.window.as_ref() else {
             return Ok(());
@@ -186,8 +935,10 @@ impl GfxState {
             return Ok(());
         };
 
-        // Request the next frame
-        window.request_redraw();
+        // Request the next frame when rendering continuously
+        if app().control_flow() == ControlFlow::Poll {
+            window.request_redraw();
+        }
 
         // Skip rendering if surface isn't configured yet
         if !self.is_surface_configured {
@@ -195,52 +946,85 @@ impl GfxState {
         }
         // Get the next frame to render to
         let output = surface.get_current_texture()?;
-        let view = output
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
+        let view = output.texture.create_view(&Default::default());
+        let linear_view = self.linear_surface_view(&output.texture).ok();
+
+        // Let a client provided graph draw the whole frame
+        let client = app().client();
+        if let Some(graph) = client.render_graph() {
+            let config = self.config.as_ref().unwrap();
+            let size = (config.width, config.height);
+            let mut encoder = self.create_encoder(Some("Render Graph Encoder"));
+            client.encode(&mut encoder);
+            if let Err(e) = graph
+                .lock()
+                .execute(&self.device, &mut encoder, &view, size)
+            {
+                log::error!("Unable to execute render graph: {e:#}");
+            }
+            client.encode_surface(&mut encoder, &output.texture, linear_view.as_ref());
+            self.submit(encoder);
+            #[cfg(not(target_arch = "wasm32"))]
+            self.save_screenshot(&output.texture);
+            output.present();
+            return Ok(());
+        }
+
+        // Render into the offscreen targets when using a reduced resolution
+        let scaled = self.prepare_scaled_target();
+        self.prepare_msaa_target(self.render_size());
+        let (frame_view, depth_view) = if scaled {
+            let target = self.scaled_target.as_ref().unwrap();
+            (target.color.view(), target.depth.as_ref())
+        } else {
+            (&view, self.depth_buffer.as_ref())
+        };
+        // Multisampled rendering resolves into the frame
+        let (color_view, resolve_target) = match self.msaa_target.as_ref() {
+            Some(msaa) => (msaa.view(), Some(frame_view)),
+            None => (frame_view, None),
+        };
 
         // Create command encoder for recording GPU commands
-        let mut encoder = self
-            .device
-            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                label: Some("Render Encoder"),
-            });
-        // Create and execute render pass
-        {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    depth_slice: None,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        // Clear with the configured background color
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: self.clear_color.x as f64,
-                            g: self.clear_color.y as f64,
-                            b: self.clear_color.z as f64,
-                            a: self.clear_color.w as f64,
-                        }),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                    view: self.depth_buffer.view(),
-                    depth_ops: Some(wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(1.0),
-                        store: wgpu::StoreOp::Store,
-                    }),
-                    stencil_ops: None,
-                }),
-                timestamp_writes: None,
-                occlusion_query_set: None,
-            });
+        let mut encoder = self.create_encoder(Some("Render Encoder"));
+        // Let the application client record work preceding the main pass
+        client.encode(&mut encoder);
+        // Create and execute render pass, unless the client recorded the frame itself
+        if !client.encode_frame(&mut encoder, frame_view) {
+            let targets = PassTargets {
+                color: color_view,
+                color_load: if self.preserve_color {
+                    wgpu::LoadOp::Load
+                } else {
+                    // Clear with the configured background color
+                    wgpu::LoadOp::Clear(Color(self.clear_color).into())
+                },
+                resolve_target,
+                depth: depth_view.map(Texture2D::view),
+                depth_load: if self.preserve_depth {
+                    wgpu::LoadOp::Load
+                } else {
+                    wgpu::LoadOp::Clear(1.0)
+                },
+            };
+            let mut render_pass = begin_pass(&mut encoder, &targets, Some("Render Pass"));
             // Let the application client render its content
-            app().client().render(&mut render_pass);
+            client.render(&mut render_pass);
+        }
+        // Upscale the offscreen target onto the surface
+        if let Some(target) = self.scaled_target.as_ref() {
+            let targets = PassTargets::new(&view);
+            let mut blit_pass = begin_pass(&mut encoder, &targets, Some("Upscale Pass"));
+            let blitter = self.blitter.as_ref().unwrap();
+            blitter.blit(&mut blit_pass, &target.bind_group);
         }
+        // Let the application client post-process the finished frame
+        client.encode_surface(&mut encoder, &output.texture, linear_view.as_ref());
 
         // Submit commands to GPU and present the frame
-        self.queue.submit(Some(encoder.finish()));
+        self.submit(encoder);
+        #[cfg(not(target_arch = "wasm32"))]
+        self.save_screenshot(&output.texture);
         output.present();
         Ok(())
     }
@@ -0,0 +1,217 @@
+//! Adapter and device selection
+//!
+//! `GfxStateOptions` is passed to `GfxState::with_options`, or set as
+//! `AppClientInfo::gfx_options` for the state of the main window, and
+//! controls which adapter is picked, what the device is created with and
+//! how the main pass is set up. [`enumerate_adapters`] lists the candidates.
+
+use crate::gfx::RenderConfig;
+
+/// Requirements for the adapter and device of a `GfxState`
+///
+/// The defaults pick the platform's default adapter with no extra features
+/// and the default limits, exactly like `GfxState::new`. The app fills the
+/// main pass settings of the main window's options in from its client.
+#[derive(Debug, Clone)]
+pub struct GfxStateOptions {
+    /// Whether to prefer an integrated or a discrete GPU
+    pub power_preference: wgpu::PowerPreference,
+    /// Features the device must be created with
+    ///
+    /// Creating the state fails if the adapter lacks any of them.
+    pub required_features: wgpu::Features,
+    /// Limits the device must be created with, `None` for the defaults
+    ///
+    /// The defaults are `wgpu::Limits::default()`, or the WebGL2 downlevel
+    /// limits for windowed state on the web. Creating the state fails if
+    /// the adapter does not reach them.
+    pub required_limits: Option<wgpu::Limits>,
+    /// Backends to choose the adapter from, `None` for the platform default
+    ///
+    /// The default is Vulkan, Metal and DX12 on native platforms, WebGL for
+    /// windowed state on the web and WebGPU for headless state there.
+    pub backends: Option<wgpu::Backends>,
+    /// Adapter to use instead of the one wgpu picks, `None` by default
+    ///
+    /// Native only; the web ignores it with a warning. Creating the state
+    /// fails if no adapter on `backends` matches, or if the matching one
+    /// cannot present to the window.
+    pub adapter: Option<AdapterSelector>,
+    /// Bytes of push constants pipelines may use, 0 (disabled) by default
+    ///
+    /// See `AppClientInfo::push_constant_size`.
+    pub push_constant_size: u32,
+    /// Attachment requirements of the main pass, see `AppClient::render_config`
+    pub render_config: RenderConfig,
+    /// Initial background color of the main pass, opaque black by default
+    pub clear_color: glam::Vec4,
+    /// Additional formats views of the surface texture may use (windowed only)
+    ///
+    /// See `AppClientInfo::surface_view_formats`.
+    pub surface_view_formats: Vec<wgpu::TextureFormat>,
+    /// Preferred presentation mode, `AutoVsync` by default (windowed only)
+    pub present_mode: wgpu::PresentMode,
+}
+impl GfxStateOptions {
+    /// Creates the default options
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            power_preference: wgpu::PowerPreference::None,
+            required_features: wgpu::Features::empty(),
+            required_limits: None,
+            backends: None,
+            adapter: None,
+            push_constant_size: 0,
+            render_config: RenderConfig::new(),
+            clear_color: glam::vec4(0.0, 0.0, 0.0, 1.0),
+            surface_view_formats: Vec::new(),
+            present_mode: wgpu::PresentMode::AutoVsync,
+        }
+    }
+    /// Prefers the given kind of GPU, e.g. `HighPerformance` for compute jobs
+    #[inline]
+    pub fn with_power_preference(self, power_preference: wgpu::PowerPreference) -> Self {
+        Self {
+            power_preference,
+            ..self
+        }
+    }
+    /// Requires `features` in addition to those already required
+    #[inline]
+    pub fn with_features(self, features: wgpu::Features) -> Self {
+        Self {
+            required_features: self.required_features | features,
+            ..self
+        }
+    }
+    /// Requires the device to be created with `limits`
+    #[inline]
+    pub fn with_limits(self, limits: wgpu::Limits) -> Self {
+        Self {
+            required_limits: Some(limits),
+            ..self
+        }
+    }
+    /// Restricts the adapter to `backends`
+    #[inline]
+    pub fn with_backends(self, backends: wgpu::Backends) -> Self {
+        Self {
+            backends: Some(backends),
+            ..self
+        }
+    }
+    /// Uses the adapter matching `selector`, see [`enumerate_adapters`]
+    #[inline]
+    pub fn with_adapter(self, selector: AdapterSelector) -> Self {
+        Self {
+            adapter: Some(selector),
+            ..self
+        }
+    }
+    /// Allows pipelines to use `size` bytes of push constants where supported
+    #[inline]
+    pub fn with_push_constant_size(self, size: u32) -> Self {
+        Self {
+            push_constant_size: size,
+            ..self
+        }
+    }
+    /// Sets the attachment requirements of the main pass
+    #[inline]
+    pub fn with_render_config(self, render_config: RenderConfig) -> Self {
+        Self {
+            render_config,
+            ..self
+        }
+    }
+    /// Sets the initial background color of the main pass
+    #[inline]
+    pub fn with_clear_color(self, clear_color: glam::Vec4) -> Self {
+        Self {
+            clear_color,
+            ..self
+        }
+    }
+    /// Allows views of the surface texture with the given formats
+    #[inline]
+    pub fn with_surface_view_formats(self, formats: Vec<wgpu::TextureFormat>) -> Self {
+        Self {
+            surface_view_formats: formats,
+            ..self
+        }
+    }
+    /// Sets the preferred presentation mode
+    #[inline]
+    pub fn with_present_mode(self, present_mode: wgpu::PresentMode) -> Self {
+        Self {
+            present_mode,
+            ..self
+        }
+    }
+}
+impl Default for GfxStateOptions {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Picks an adapter by name or position, see `GfxStateOptions::adapter`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AdapterSelector {
+    /// The first adapter whose name contains this, ignoring case
+    Name(String),
+    /// The adapter at this index of [`enumerate_adapters`]
+    Index(usize),
+}
+impl AdapterSelector {
+    /// Returns `true` if the adapter at `index` with `info` is selected
+    pub fn matches(&self, index: usize, info: &wgpu::AdapterInfo) -> bool {
+        match self {
+            Self::Name(name) => info.name.to_lowercase().contains(&name.to_lowercase()),
+            Self::Index(i) => *i == index,
+        }
+    }
+}
+
+/// Returns the info of every adapter on the given backends (native only)
+///
+/// `None` uses the native default backends, see
+/// `GfxStateOptions::backends`. Browsers only hand out a single adapter on
+/// request, so this is not available on the web.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn enumerate_adapters(backends: Option<wgpu::Backends>) -> Vec<wgpu::AdapterInfo> {
+    let backends = backends.unwrap_or(wgpu::Backends::PRIMARY);
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+        backends,
+        ..Default::default()
+    });
+    instance
+        .enumerate_adapters(backends)
+        .iter()
+        .map(wgpu::Adapter::get_info)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn adapter_selector_matches() {
+        let info = wgpu::AdapterInfo {
+            name: String::from("NVIDIA GeForce RTX 4070"),
+            vendor: 0,
+            device: 0,
+            device_type: wgpu::DeviceType::DiscreteGpu,
+            driver: String::new(),
+            driver_info: String::new(),
+            backend: wgpu::Backend::Vulkan,
+        };
+        assert!(AdapterSelector::Name("geforce".into()).matches(0, &info));
+        assert!(!AdapterSelector::Name("Radeon".into()).matches(0, &info));
+        assert!(AdapterSelector::Index(1).matches(1, &info));
+        assert!(!AdapterSelector::Index(1).matches(0, &info));
+    }
+}
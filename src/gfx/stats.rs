@@ -0,0 +1,70 @@
+//! Per-frame draw statistics
+//!
+//! `Mesh::draw` counts every draw call it issues. The counters are plain
+//! atomics, cheap enough to be always on, and are rotated at the start of
+//! each frame so [`render_stats`] reports the last complete frame.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Draw calls issued in the current frame
+static DRAW_CALLS: AtomicU64 = AtomicU64::new(0);
+/// Triangles submitted in the current frame
+static TRIANGLES: AtomicU64 = AtomicU64::new(0);
+/// Draw calls issued in the last complete frame
+static LAST_DRAW_CALLS: AtomicU64 = AtomicU64::new(0);
+/// Triangles submitted in the last complete frame
+static LAST_TRIANGLES: AtomicU64 = AtomicU64::new(0);
+
+/// Draw statistics of one frame
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    /// Number of draw calls
+    pub draw_calls: u64,
+    /// Number of triangles, assuming triangle lists
+    pub triangles: u64,
+}
+
+/// Returns the draw statistics of the last complete frame
+pub fn render_stats() -> RenderStats {
+    RenderStats {
+        draw_calls: LAST_DRAW_CALLS.load(Ordering::Relaxed),
+        triangles: LAST_TRIANGLES.load(Ordering::Relaxed),
+    }
+}
+
+/// Counts a draw call of `count` vertices or indices and `instances` instances
+///
+/// `Mesh::draw` calls this automatically. Custom draw code can call it to
+/// be included in the statistics.
+pub fn record_draw(count: u32, instances: u32) {
+    DRAW_CALLS.fetch_add(1, Ordering::Relaxed);
+    TRIANGLES.fetch_add((count / 3) as u64 * instances as u64, Ordering::Relaxed);
+}
+
+/// Publishes the current counters as the last frame's and resets them
+pub(crate) fn rotate_render_stats() {
+    LAST_DRAW_CALLS.store(DRAW_CALLS.swap(0, Ordering::Relaxed), Ordering::Relaxed);
+    LAST_TRIANGLES.store(TRIANGLES.swap(0, Ordering::Relaxed), Ordering::Relaxed);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn counts_and_rotates() {
+        rotate_render_stats();
+        record_draw(6, 1);
+        record_draw(3, 10);
+        rotate_render_stats();
+        assert_eq!(
+            render_stats(),
+            RenderStats {
+                draw_calls: 2,
+                triangles: 12
+            }
+        );
+        rotate_render_stats();
+        assert_eq!(render_stats(), RenderStats::default());
+    }
+}
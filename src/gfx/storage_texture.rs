@@ -0,0 +1,87 @@
+//! Textures written by compute shaders and sampled while rendering
+//!
+//! A `StorageTexture` is created with both `STORAGE_BINDING` and
+//! `TEXTURE_BINDING` usage, so a compute pass can write it and a later render
+//! pass can sample it. wgpu tracks the usage transition between passes
+//! recorded on the same encoder, so no manual barrier is required; the
+//! compute pass just has to be recorded first, e.g. in `AppClient::encode`.
+
+use crate::gfx::Texture2D;
+
+/// A 2D texture written in compute passes and sampled in render passes
+#[derive(educe::Educe)]
+#[educe(Deref)]
+pub struct StorageTexture {
+    #[educe(Deref)]
+    tex: Texture2D,
+    format: wgpu::TextureFormat,
+    size: (u32, u32),
+}
+impl StorageTexture {
+    /// Creates a storage texture
+    ///
+    /// `format` must support storage binding, e.g. `Rgba8Unorm` or `Rgba16Float`.
+    pub fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        size: (u32, u32),
+        label: Option<&str>,
+    ) -> Self {
+        let tex = Texture2D::new(
+            device,
+            format,
+            size,
+            wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING,
+            label,
+        );
+        Self { tex, format, size }
+    }
+    /// Returns the size in texels
+    #[inline]
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+    /// Returns a layout entry binding the texture for writing in a compute shader
+    ///
+    /// Matches `var tex: texture_storage_2d<format, write>` in WGSL.
+    pub fn storage_layout_entry(&self, binding: u32) -> wgpu::BindGroupLayoutEntry {
+        wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::StorageTexture {
+                access: wgpu::StorageTextureAccess::WriteOnly,
+                format: self.format,
+                view_dimension: wgpu::TextureViewDimension::D2,
+            },
+            count: None,
+        }
+    }
+    /// Returns a layout entry binding the texture for sampling in a fragment shader
+    pub fn sampled_layout_entry(&self, binding: u32) -> wgpu::BindGroupLayoutEntry {
+        let sample_type = self
+            .format
+            .sample_type(None, None)
+            .expect("Storage texture format cannot be sampled");
+        wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        }
+    }
+    /// Returns the number of workgroups needed to cover the texture
+    ///
+    /// # Arguments
+    /// * `workgroup_size` - The `@workgroup_size(x, y)` of the compute shader
+    #[inline]
+    pub fn workgroups(&self, workgroup_size: (u32, u32)) -> (u32, u32) {
+        (
+            self.size.0.div_ceil(workgroup_size.0),
+            self.size.1.div_ceil(workgroup_size.1),
+        )
+    }
+}
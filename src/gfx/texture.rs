@@ -13,17 +13,144 @@ impl Texture2D {
         size: (u32, u32),
         extra_usage: wgpu::TextureUsages,
         label: Option<&str>,
+    ) -> Self {
+        Self::new_multisampled_attachment(device, format, size, 1, extra_usage, label)
+    }
+    /// Creates a render attachment with `sample_count` samples per pixel
+    pub fn new_multisampled_attachment(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        size: (u32, u32),
+        sample_count: u32,
+        extra_usage: wgpu::TextureUsages,
+        label: Option<&str>,
     ) -> Self {
         let usage = wgpu::TextureUsages::RENDER_ATTACHMENT | extra_usage;
+        Self::create(device, format, size, sample_count, 1, 1, usage, label)
+    }
+    /// Creates a single-sampled texture with exactly the given usage
+    pub fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        size: (u32, u32),
+        usage: wgpu::TextureUsages,
+        label: Option<&str>,
+    ) -> Self {
+        Self::create(device, format, size, 1, 1, 1, usage, label)
+    }
+    /// Creates a single-sampled texture with a mip chain and array layers
+    ///
+    /// Use [`Texture2D::view_of`] to render into a single mip level or layer.
+    /// The default view covers all of them.
+    pub fn new_with_mips(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        size: (u32, u32),
+        mip_levels: u32,
+        layers: u32,
+        usage: wgpu::TextureUsages,
+        label: Option<&str>,
+    ) -> Self {
+        Self::create(device, format, size, 1, mip_levels, layers, usage, label)
+    }
+    /// Decodes an image file and uploads it as a sampled texture
+    ///
+    /// See [`Texture2D::from_image`]. The `image` crate is built with PNG
+    /// support only.
+    ///
+    /// # Returns
+    ///
+    /// Returns an error if the bytes cannot be decoded.
+    pub fn from_bytes(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        bytes: &[u8],
+        srgb: bool,
+        label: Option<&str>,
+    ) -> anyhow::Result<Self> {
+        let image = image::load_from_memory(bytes)?;
+        Ok(Self::from_image(device, queue, &image, srgb, label))
+    }
+    /// Reads an image file from disk and uploads it, see [`Texture2D::from_bytes`]
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_file(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        path: impl AsRef<std::path::Path>,
+        srgb: bool,
+    ) -> anyhow::Result<Self> {
+        use anyhow::Context;
+
+        let path = path.as_ref();
+        let bytes =
+            std::fs::read(path).with_context(|| format!("Unable to read {}", path.display()))?;
+        let label = path.to_string_lossy();
+        Self::from_bytes(device, queue, &bytes, srgb, Some(&label))
+            .with_context(|| format!("Unable to decode {}", path.display()))
+    }
+    /// Uploads an image as a sampled texture
+    ///
+    /// The image is converted to RGBA8. With `srgb` set the texture uses
+    /// `Rgba8UnormSrgb`, so sampling returns linear values, which is what
+    /// color textures want; data such as normal maps should pass `false`.
+    /// The texture has `TEXTURE_BINDING` and `COPY_DST` usage.
+    pub fn from_image(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        image: &image::DynamicImage,
+        srgb: bool,
+        label: Option<&str>,
+    ) -> Self {
+        let rgba = image.to_rgba8();
+        let size = rgba.dimensions();
+        let format = if srgb {
+            wgpu::TextureFormat::Rgba8UnormSrgb
+        } else {
+            wgpu::TextureFormat::Rgba8Unorm
+        };
+        let usage = wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST;
+        let texture = Self::new(device, format, size, usage, label);
+        queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &texture.tex,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &rgba,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * size.0),
+                rows_per_image: Some(size.1),
+            },
+            wgpu::Extent3d {
+                width: size.0,
+                height: size.1,
+                depth_or_array_layers: 1,
+            },
+        );
+        texture
+    }
+    #[allow(clippy::too_many_arguments)]
+    fn create(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        size: (u32, u32),
+        sample_count: u32,
+        mip_levels: u32,
+        layers: u32,
+        usage: wgpu::TextureUsages,
+        label: Option<&str>,
+    ) -> Self {
         let tex = device.create_texture(&wgpu::TextureDescriptor {
             label,
             size: wgpu::Extent3d {
                 width: size.0,
                 height: size.1,
-                depth_or_array_layers: 1,
+                depth_or_array_layers: layers,
             },
-            mip_level_count: 1,
-            sample_count: 1,
+            mip_level_count: mip_levels,
+            sample_count,
             dimension: wgpu::TextureDimension::D2,
             format,
             usage,
@@ -35,4 +162,69 @@ impl Texture2D {
     pub fn view(&self) -> &wgpu::TextureView {
         &self.view
     }
+    /// Creates a view restricted to a single mip level and array layer
+    ///
+    /// The returned view can be used as a render attachment to render into a
+    /// specific subresource, e.g. one level of a mip chain.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mip_level` or `base_layer` is out of range for this texture.
+    pub fn view_of(&self, mip_level: u32, base_layer: u32) -> wgpu::TextureView {
+        assert!(
+            mip_level < self.tex.mip_level_count(),
+            "Mip level {mip_level} out of range"
+        );
+        assert!(
+            base_layer < self.tex.depth_or_array_layers(),
+            "Array layer {base_layer} out of range"
+        );
+        self.tex.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(wgpu::TextureViewDimension::D2),
+            base_mip_level: mip_level,
+            mip_level_count: Some(1),
+            base_array_layer: base_layer,
+            array_layer_count: Some(1),
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gfx::noop_device;
+
+    #[test]
+    fn views_single_subresource() {
+        let (device, _queue) = noop_device();
+        let texture = Texture2D::new_with_mips(
+            &device,
+            wgpu::TextureFormat::Rgba8Unorm,
+            (16, 16),
+            3,
+            2,
+            wgpu::TextureUsages::RENDER_ATTACHMENT,
+            None,
+        );
+        assert_eq!(texture.mip_level_count(), 3);
+        assert_eq!(texture.depth_or_array_layers(), 2);
+        texture.view_of(2, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Mip level 3 out of range")]
+    fn rejects_missing_mip_level() {
+        let (device, _queue) = noop_device();
+        let texture = Texture2D::new_with_mips(
+            &device,
+            wgpu::TextureFormat::Rgba8Unorm,
+            (16, 16),
+            3,
+            1,
+            wgpu::TextureUsages::RENDER_ATTACHMENT,
+            None,
+        );
+        texture.view_of(3, 0);
+    }
 }
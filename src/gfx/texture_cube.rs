@@ -0,0 +1,143 @@
+//! Cube map textures, e.g. for skyboxes
+
+use anyhow::bail;
+
+/// A cube map with six square faces and a `Cube` view
+///
+/// Faces are stored as array layers in the order wgpu expects:
+/// `+X`, `-X`, `+Y`, `-Y`, `+Z`, `-Z`.
+#[derive(educe::Educe)]
+#[educe(Deref)]
+pub struct TextureCube {
+    #[educe(Deref)]
+    tex: wgpu::Texture,
+    view: wgpu::TextureView,
+}
+impl TextureCube {
+    /// Uploads six face images in `+X`, `-X`, `+Y`, `-Y`, `+Z`, `-Z` order
+    ///
+    /// The images are converted to RGBA8, with `srgb` choosing between
+    /// `Rgba8UnormSrgb` and `Rgba8Unorm` like `Texture2D::from_image`.
+    ///
+    /// # Returns
+    ///
+    /// Returns an error if the faces are not square or differ in size.
+    pub fn from_images(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        faces: [&image::DynamicImage; 6],
+        srgb: bool,
+        label: Option<&str>,
+    ) -> anyhow::Result<Self> {
+        let size = faces[0].width();
+        if let Some(face) = faces
+            .iter()
+            .find(|face| (face.width(), face.height()) != (size, size))
+        {
+            bail!(
+                "Cube faces must be square and of equal size, expected {size}x{size}, got {}x{}",
+                face.width(),
+                face.height()
+            );
+        }
+        let format = if srgb {
+            wgpu::TextureFormat::Rgba8UnormSrgb
+        } else {
+            wgpu::TextureFormat::Rgba8Unorm
+        };
+        let tex = device.create_texture(&wgpu::TextureDescriptor {
+            label,
+            size: wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: 6,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        for (layer, face) in faces.iter().enumerate() {
+            queue.write_texture(
+                wgpu::TexelCopyTextureInfo {
+                    texture: &tex,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x: 0,
+                        y: 0,
+                        z: layer as u32,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                &face.to_rgba8(),
+                wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(4 * size),
+                    rows_per_image: Some(size),
+                },
+                wgpu::Extent3d {
+                    width: size,
+                    height: size,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+        let view = tex.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(wgpu::TextureViewDimension::Cube),
+            ..Default::default()
+        });
+        Ok(Self { tex, view })
+    }
+    /// Splits a horizontal cross image into faces and uploads them
+    ///
+    /// The image is 4 faces wide and 3 faces high:
+    ///
+    /// ```text
+    ///     +Y
+    /// -X  +Z  +X  -Z
+    ///     -Y
+    /// ```
+    pub fn from_cross(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        image: &image::DynamicImage,
+        srgb: bool,
+        label: Option<&str>,
+    ) -> anyhow::Result<Self> {
+        let (size, origins) = cross_face_origins(image.width(), image.height())?;
+        let faces = origins.map(|(x, y)| image.crop_imm(x, y, size, size));
+        Self::from_images(device, queue, faces.each_ref(), srgb, label)
+    }
+    /// Returns the `Cube` view for sampling
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+}
+
+/// Returns the face size and the top-left corner of each face in a horizontal cross
+fn cross_face_origins(width: u32, height: u32) -> anyhow::Result<(u32, [(u32, u32); 6])> {
+    let size = width / 4;
+    if size == 0 || width != size * 4 || height != size * 3 {
+        bail!("A cube map cross must be 4 faces wide and 3 high, got {width}x{height}");
+    }
+    let tiles = [(2, 1), (0, 1), (1, 0), (1, 2), (1, 1), (3, 1)];
+    Ok((size, tiles.map(|(x, y)| (x * size, y * size))))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cross_layout_faces() {
+        let (size, origins) = cross_face_origins(400, 300).unwrap();
+        assert_eq!(size, 100);
+        assert_eq!(origins[0], (200, 100));
+        assert_eq!(origins[2], (100, 0));
+        assert_eq!(origins[5], (300, 100));
+        assert!(cross_face_origins(400, 400).is_err());
+        assert!(cross_face_origins(3, 0).is_err());
+    }
+}
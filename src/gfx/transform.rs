@@ -0,0 +1,131 @@
+//! Translation, rotation and scale of objects
+
+/// Position, orientation and size of an object in 3D space
+///
+/// The transform applies scale first, then rotation, then translation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    /// Position of the object
+    pub translation: glam::Vec3,
+    /// Orientation of the object
+    pub rotation: glam::Quat,
+    /// Size of the object along its local axes
+    pub scale: glam::Vec3,
+}
+impl Transform {
+    /// The identity transform
+    pub const IDENTITY: Self = Self {
+        translation: glam::Vec3::ZERO,
+        rotation: glam::Quat::IDENTITY,
+        scale: glam::Vec3::ONE,
+    };
+
+    /// Creates a transform that only moves an object
+    #[inline]
+    pub const fn from_translation(translation: glam::Vec3) -> Self {
+        Self {
+            translation,
+            ..Self::IDENTITY
+        }
+    }
+    /// Creates a transform that only rotates an object
+    #[inline]
+    pub const fn from_rotation(rotation: glam::Quat) -> Self {
+        Self {
+            rotation,
+            ..Self::IDENTITY
+        }
+    }
+    /// Creates a transform that only scales an object
+    #[inline]
+    pub const fn from_scale(scale: glam::Vec3) -> Self {
+        Self {
+            scale,
+            ..Self::IDENTITY
+        }
+    }
+    /// Returns this transform rotated so its local `-Z` axis points at `target`
+    ///
+    /// The local `+Y` axis leans towards `up`, matching the camera convention
+    /// of `glam::Mat4::look_at_rh`. The rotation is kept if `target` is at
+    /// the translation or straight along `up`.
+    pub fn looking_at(self, target: glam::Vec3, up: glam::Vec3) -> Self {
+        let Some(forward) = (target - self.translation).try_normalize() else {
+            return self;
+        };
+        let Some(right) = forward.cross(up).try_normalize() else {
+            return self;
+        };
+        let up = right.cross(forward);
+        let rotation = glam::Quat::from_mat3(&glam::Mat3::from_cols(right, up, -forward));
+        Self { rotation, ..self }
+    }
+
+    /// Returns the model matrix of this transform
+    #[inline]
+    pub fn matrix(&self) -> glam::Mat4 {
+        glam::Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.translation)
+    }
+    /// Transforms a point from local to parent space
+    #[inline]
+    pub fn transform_point(&self, point: glam::Vec3) -> glam::Vec3 {
+        self.translation + self.rotation * (self.scale * point)
+    }
+}
+/// Composes two transforms, `parent * child` applying `child` first
+///
+/// Like nested model matrices, except that a non-uniform parent scale is
+/// applied along the child's axes instead of shearing it.
+impl std::ops::Mul for Transform {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, child: Self) -> Self {
+        Self {
+            translation: self.transform_point(child.translation),
+            rotation: self.rotation * child.rotation,
+            scale: self.scale * child.scale,
+        }
+    }
+}
+impl std::ops::MulAssign for Transform {
+    #[inline]
+    fn mul_assign(&mut self, child: Self) {
+        *self = *self * child;
+    }
+}
+impl Default for Transform {
+    #[inline]
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn composition_matches_matrices() {
+        let parent = Transform {
+            translation: glam::vec3(1.0, 2.0, 3.0),
+            rotation: glam::Quat::from_rotation_y(0.5),
+            scale: glam::Vec3::splat(2.0),
+        };
+        let child = Transform::from_translation(glam::Vec3::X)
+            * Transform::from_rotation(glam::Quat::from_rotation_x(1.0));
+        let combined = (parent * child).matrix();
+        assert!(combined.abs_diff_eq(parent.matrix() * child.matrix(), 1e-5));
+    }
+
+    #[test]
+    fn looking_at_faces_target() {
+        let transform = Transform::from_translation(glam::vec3(0.0, 0.0, 5.0))
+            .looking_at(glam::Vec3::ZERO, glam::Vec3::Y);
+        let forward = transform.rotation * glam::Vec3::NEG_Z;
+        assert!(forward.abs_diff_eq(glam::Vec3::NEG_Z, 1e-6));
+        let sideways = transform.looking_at(glam::vec3(5.0, 0.0, 5.0), glam::Vec3::Y);
+        assert!((sideways.rotation * glam::Vec3::NEG_Z).abs_diff_eq(glam::Vec3::X, 1e-6));
+        assert!((sideways.rotation * glam::Vec3::Y).abs_diff_eq(glam::Vec3::Y, 1e-6));
+    }
+}
@@ -1,6 +1,6 @@
 use std::mem::size_of;
 
-use crate::gfx::ShaderCode;
+use crate::gfx::{Color, ShaderCode};
 
 /// Trait for vertex types that can be used with the graphics pipeline.
 ///
@@ -12,7 +12,7 @@ pub trait Vertex: bytemuck::Pod + bytemuck::Zeroable {
 }
 
 /// Trait for providing vertex buffer layout descriptions to wgpu.
-pub trait VertexInfo {
+pub trait VertexInfo: Send + Sync {
     /// Returns the vertex buffer layout describing attribute locations and formats.
     fn describe(&self) -> wgpu::VertexBufferLayout<'_>;
     fn shader_code(&self) -> ShaderCode;
@@ -32,9 +32,25 @@ pub struct Vertex2D {
     pub position: [f32; 2],
     /// Texture coordinates (u, v) for sampling textures
     pub tex_coords: [f32; 2],
-    /// RGBA color values, each component in range [0.0, 1.0]
+    /// RGBA color values in linear space, each component in range [0.0, 1.0]
+    ///
+    /// Colors are interpolated and written to the (usually sRGB) surface as
+    /// linear values. Colors picked in an image editor or from hex codes are
+    /// sRGB encoded and should be set with [`Vertex2D::with_srgb_color`].
     pub color: [f32; 4],
 }
+impl Vertex2D {
+    /// Returns this vertex with an sRGB encoded color converted to linear space
+    ///
+    /// The alpha component is used as is.
+    #[inline]
+    pub fn with_srgb_color(self, srgb: [f32; 4]) -> Self {
+        Self {
+            color: Color::srgb_to_linear(srgb.into()).to_array(),
+            ..self
+        }
+    }
+}
 impl Default for Vertex2D {
     #[inline]
     fn default() -> Self {
@@ -92,9 +108,25 @@ pub struct Vertex3D {
     pub normal: [f32; 3],
     /// Texture coordinates (u, v) for sampling textures
     pub tex_coords: [f32; 2],
-    /// RGBA color values, each component in range [0.0, 1.0]
+    /// RGBA color values in linear space, each component in range [0.0, 1.0]
+    ///
+    /// Colors are interpolated and written to the (usually sRGB) surface as
+    /// linear values. Colors picked in an image editor or from hex codes are
+    /// sRGB encoded and should be set with [`Vertex3D::with_srgb_color`].
     pub color: [f32; 4],
 }
+impl Vertex3D {
+    /// Returns this vertex with an sRGB encoded color converted to linear space
+    ///
+    /// The alpha component is used as is.
+    #[inline]
+    pub fn with_srgb_color(self, srgb: [f32; 4]) -> Self {
+        Self {
+            color: Color::srgb_to_linear(srgb.into()).to_array(),
+            ..self
+        }
+    }
+}
 
 impl Vertex for Vertex3D {
     fn info() -> VertexInfoObj {
@@ -141,3 +173,134 @@ impl Default for Vertex3D {
         }
     }
 }
+
+/// Per-instance model matrix and color, e.g. for drawing many copies of a mesh
+///
+/// Uses `wgpu::VertexStepMode::Instance` and shader locations 8 to 12, which
+/// leaves 0 to 7 to the vertex buffer. Its shader code declares a struct
+/// named `InstanceBuf`; register it in the shader library to include it:
+///
+/// ```ignore
+/// let mut lib = gfx::ShaderLib::new();
+/// lib.insert("struct/InstanceBuf", &gfx::ModelInstance::info().shader_code().0);
+/// // In WGSL:
+/// // /// @include "struct/InstanceBuf"
+/// // @vertex fn vs_main(in: VertexBuf, instance: InstanceBuf) -> VertexOutput {
+/// //     let model = mat4x4f(instance.model_0, instance.model_1, instance.model_2, instance.model_3);
+/// // }
+/// ```
+#[repr(C)]
+#[derive(Clone, Copy)]
+#[derive(bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ModelInstance {
+    /// Columns of the model matrix
+    pub model: [[f32; 4]; 4],
+    /// RGBA color in linear space, e.g. to tint the instance
+    pub color: [f32; 4],
+}
+impl ModelInstance {
+    #[inline]
+    pub fn new(model: glam::Mat4, color: Color) -> Self {
+        Self {
+            model: model.to_cols_array_2d(),
+            color: color.to_array(),
+        }
+    }
+}
+impl Default for ModelInstance {
+    #[inline]
+    fn default() -> Self {
+        Self::new(glam::Mat4::IDENTITY, Color::WHITE)
+    }
+}
+impl Vertex for ModelInstance {
+    fn info() -> VertexInfoObj {
+        struct Info;
+        impl VertexInfo for Info {
+            fn describe(&self) -> wgpu::VertexBufferLayout<'_> {
+                const ATTRS: &[wgpu::VertexAttribute] = &wgpu::vertex_attr_array![
+                    8 => Float32x4,  // model column 0
+                    9 => Float32x4,  // model column 1
+                    10 => Float32x4, // model column 2
+                    11 => Float32x4, // model column 3
+                    12 => Float32x4, // color
+                ];
+                wgpu::VertexBufferLayout {
+                    array_stride: size_of::<ModelInstance>() as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Instance,
+                    attributes: ATTRS,
+                }
+            }
+            fn shader_code(&self) -> ShaderCode {
+                const CODE: &str = r#"
+                struct InstanceBuf {
+                    @location(8) model_0: vec4f,
+                    @location(9) model_1: vec4f,
+                    @location(10) model_2: vec4f,
+                    @location(11) model_3: vec4f,
+                    @location(12) color: vec4f,
+                };
+                "#;
+                ShaderCode(CODE.into())
+            }
+        }
+        Box::new(Info)
+    }
+}
+
+/// Untyped vertex data, one 32-bit word per element
+///
+/// The default layout is a single `f32` at location 0. Together with
+/// `Mesh::with_info` this stores interleaved vertices of any layout built by
+/// `VertexLayoutBuilder`.
+impl Vertex for f32 {
+    fn info() -> VertexInfoObj {
+        struct Info;
+        impl VertexInfo for Info {
+            fn describe(&self) -> wgpu::VertexBufferLayout<'_> {
+                const ATTRS: &[wgpu::VertexAttribute] = &wgpu::vertex_attr_array![
+                    0 => Float32,  // value
+                ];
+                wgpu::VertexBufferLayout {
+                    array_stride: size_of::<f32>() as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: ATTRS,
+                }
+            }
+            fn shader_code(&self) -> ShaderCode {
+                const CODE: &str = r#"
+                struct VertexBuf {
+                    @location(0) value: f32,
+                };
+                "#;
+                ShaderCode(CODE.into())
+            }
+        }
+        Box::new(Info)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn srgb_vertex_color() {
+        let vertex = Vertex3D::default().with_srgb_color([0.5, 0.0, 1.0, 0.5]);
+        assert!((vertex.color[0] - 0.21404).abs() < 1e-4);
+        assert_eq!(vertex.color[1], 0.0);
+        assert!((vertex.color[2] - 1.0).abs() < 1e-6);
+        assert_eq!(vertex.color[3], 0.5);
+    }
+
+    #[test]
+    fn model_instance_layout() {
+        let info = ModelInstance::info();
+        let layout = info.describe();
+        assert_eq!(layout.array_stride, 80);
+        assert_eq!(layout.step_mode, wgpu::VertexStepMode::Instance);
+        assert_eq!(layout.attributes[4].offset, 64);
+        let instance = ModelInstance::new(glam::Mat4::from_translation(glam::Vec3::X), Color::RED);
+        assert_eq!(instance.model[3], [1.0, 0.0, 0.0, 1.0]);
+    }
+}
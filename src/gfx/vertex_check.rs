@@ -0,0 +1,281 @@
+//! Validation of shader code against its use in pipelines
+//!
+//! The WGSL source is parsed with `naga`. Entry point names and override
+//! constants are checked with [`validate_entry_points`] and
+//! [`validate_overrides`], which the `build_checked` methods of the pipeline
+//! builders run before creating a pipeline. The `@location` inputs of each
+//! vertex entry point can be compared to the attributes of the vertex
+//! buffers with [`vertex_input_mismatches`]; with the `vertex-validation`
+//! feature, `RenderPipelineBuilder::build_checked` runs this check in debug
+//! builds and logs mismatches.
+
+use crate::gfx::VertexInfo;
+
+/// Compares the vertex inputs of a WGSL shader to the layouts of its vertex buffers
+///
+/// Reports shader inputs without a matching attribute in any buffer and
+/// inputs whose scalar kind (float, signed or unsigned integer) differs from
+/// the attribute format. Attributes the shader does not read are fine.
+///
+/// # Arguments
+/// * `code` - Preprocessed WGSL source
+/// * `entry_point` - Vertex entry point to check, or `None` for all of them
+/// * `buffers` - The layouts of all vertex buffers used with the shader,
+///   e.g. the vertex and the instance layout
+///
+/// # Returns
+///
+/// Returns a description of every mismatch found, or an error if the
+/// source cannot be parsed.
+pub fn vertex_input_mismatches(
+    code: &str,
+    entry_point: Option<&str>,
+    buffers: &[&dyn VertexInfo],
+) -> anyhow::Result<Vec<String>> {
+    let module = naga::front::wgsl::parse_str(code)?;
+    Ok(check_vertex_inputs(&module, entry_point, buffers))
+}
+
+/// Compares the vertex inputs of a parsed module, see [`vertex_input_mismatches`]
+pub(crate) fn check_vertex_inputs(
+    module: &naga::Module,
+    entry_point: Option<&str>,
+    buffers: &[&dyn VertexInfo],
+) -> Vec<String> {
+    let layouts: Vec<_> = buffers.iter().map(|info| info.describe()).collect();
+    let mut mismatches = Vec::new();
+
+    let entry_points = module.entry_points.iter().filter(|ep| {
+        ep.stage == naga::ShaderStage::Vertex && entry_point.is_none_or(|name| ep.name == name)
+    });
+    for ep in entry_points {
+        let mut check = |location: u32, ty: naga::Handle<naga::Type>, name: Option<&str>| {
+            let name = name.unwrap_or("<unnamed>");
+            let Some(attribute) = layouts
+                .iter()
+                .flat_map(|layout| layout.attributes)
+                .find(|a| a.shader_location == location)
+            else {
+                mismatches.push(format!(
+                    "`{}` reads `{name}` from @location({location}), which the vertex layout does not provide",
+                    ep.name
+                ));
+                return;
+            };
+            let shader_kind = match module.types[ty].inner {
+                naga::TypeInner::Scalar(scalar) | naga::TypeInner::Vector { scalar, .. } => {
+                    scalar.kind
+                }
+                _ => return,
+            };
+            let format_kind = format_kind(attribute.format);
+            if shader_kind != format_kind {
+                mismatches.push(format!(
+                    "`{}` reads `{name}` at @location({location}) as {shader_kind:?}, but the attribute format {:?} is {format_kind:?}",
+                    ep.name, attribute.format
+                ));
+            }
+        };
+        for arg in ep.function.arguments.iter() {
+            match (&arg.binding, &module.types[arg.ty].inner) {
+                (Some(naga::Binding::Location { location, .. }), _) => {
+                    check(*location, arg.ty, arg.name.as_deref());
+                }
+                (None, naga::TypeInner::Struct { members, .. }) => {
+                    for member in members {
+                        if let Some(naga::Binding::Location { location, .. }) = member.binding {
+                            check(location, member.ty, member.name.as_deref());
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    mismatches
+}
+
+/// Checks that a WGSL shader has the given vertex and fragment entry points
+///
+/// # Returns
+///
+/// Returns an error naming the missing entry point and the available ones
+/// of that stage, or if the source cannot be parsed.
+pub fn validate_entry_points(
+    code: &str,
+    vertex_entry: &str,
+    fragment_entry: Option<&str>,
+) -> anyhow::Result<()> {
+    let module = naga::front::wgsl::parse_str(code)?;
+    check_entry_point(&module, naga::ShaderStage::Vertex, vertex_entry)?;
+    if let Some(fragment_entry) = fragment_entry {
+        check_entry_point(&module, naga::ShaderStage::Fragment, fragment_entry)?;
+    }
+    Ok(())
+}
+
+/// Checks that a parsed module has an entry point `name` of `stage`
+pub(crate) fn check_entry_point(
+    module: &naga::Module,
+    stage: naga::ShaderStage,
+    name: &str,
+) -> anyhow::Result<()> {
+    let available = module.entry_points.iter().filter(|ep| ep.stage == stage);
+    if available.clone().any(|ep| ep.name == name) {
+        return Ok(());
+    }
+    let available = available.map(|ep| ep.name.as_str()).collect::<Vec<_>>();
+    anyhow::bail!(
+        "No {stage:?} entry point named `{name}`, available: [{}]",
+        available.join(", ")
+    )
+}
+
+/// Checks that every key of `constants` names an `override` in the shader
+///
+/// Keys may be override names or their numeric `@id`s, as accepted by
+/// `PipelineCompilationOptions::constants`.
+///
+/// # Returns
+///
+/// Returns an error listing the unknown keys and the declared overrides, or
+/// if the source cannot be parsed.
+pub fn validate_overrides(
+    code: &str,
+    constants: &std::collections::HashMap<String, f64>,
+) -> anyhow::Result<()> {
+    let module = naga::front::wgsl::parse_str(code)?;
+    check_overrides(&module, constants)
+}
+
+/// Checks that every key of `constants` names an `override` of a parsed module
+pub(crate) fn check_overrides(
+    module: &naga::Module,
+    constants: &std::collections::HashMap<String, f64>,
+) -> anyhow::Result<()> {
+    let is_declared = |key: &str| {
+        module.overrides.iter().any(|(_, o)| {
+            o.name.as_deref() == Some(key) || o.id.is_some_and(|id| id.to_string() == key)
+        })
+    };
+    let mut unknown: Vec<_> = constants
+        .keys()
+        .map(String::as_str)
+        .filter(|key| !is_declared(key))
+        .collect();
+    if unknown.is_empty() {
+        return Ok(());
+    }
+    unknown.sort_unstable();
+    let declared: Vec<_> = module
+        .overrides
+        .iter()
+        .filter_map(|(_, o)| o.name.as_deref())
+        .collect();
+    anyhow::bail!(
+        "Unknown override constants [{}], declared: [{}]",
+        unknown.join(", "),
+        declared.join(", ")
+    )
+}
+
+/// Returns the scalar kind a vertex format is read as in shaders
+fn format_kind(format: wgpu::VertexFormat) -> naga::ScalarKind {
+    use wgpu::VertexFormat::*;
+    match format {
+        Uint8 | Uint8x2 | Uint8x4 | Uint16 | Uint16x2 | Uint16x4 | Uint32 | Uint32x2 | Uint32x3
+        | Uint32x4 => naga::ScalarKind::Uint,
+        Sint8 | Sint8x2 | Sint8x4 | Sint16 | Sint16x2 | Sint16x4 | Sint32 | Sint32x2 | Sint32x3
+        | Sint32x4 => naga::ScalarKind::Sint,
+        _ => naga::ScalarKind::Float,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gfx::{ModelInstance, Vertex, Vertex3D, VertexLayoutBuilder};
+
+    #[test]
+    fn reports_missing_and_mistyped_locations() {
+        const CODE: &str = r#"
+        struct VertexBuf {
+            @location(0) position: vec3f,
+            @location(1) id: u32,
+            @location(3) color: vec4f,
+        };
+        @vertex
+        fn vs_main(in: VertexBuf) -> @builtin(position) vec4f {
+            return vec4f(in.position, 1.0);
+        }
+        "#;
+        let info = VertexLayoutBuilder::new()
+            .attribute("position", wgpu::VertexFormat::Float32x3)
+            .attribute("id", wgpu::VertexFormat::Float32)
+            .build();
+        let mismatches = vertex_input_mismatches(CODE, None, &[info.as_ref()]).unwrap();
+        assert_eq!(mismatches.len(), 2);
+        assert!(mismatches[0].contains("@location(1)"));
+        assert!(mismatches[1].contains("@location(3)"));
+    }
+
+    #[test]
+    fn checks_instance_locations() {
+        let vertex_info = Vertex3D::info();
+        let instance_info = ModelInstance::info();
+        let code = format!(
+            "{}{}{}",
+            vertex_info.shader_code().0,
+            instance_info.shader_code().0,
+            r#"
+            @vertex
+            fn vs_main(in: VertexBuf, instance: InstanceBuf) -> @builtin(position) vec4f {
+                return instance.model_0 * in.position.x;
+            }
+            "#
+        );
+        let buffers = [vertex_info.as_ref(), instance_info.as_ref()];
+        assert!(
+            vertex_input_mismatches(&code, None, &buffers)
+                .unwrap()
+                .is_empty()
+        );
+        let mismatches = vertex_input_mismatches(&code, None, &buffers[..1]).unwrap();
+        assert_eq!(mismatches.len(), 5);
+    }
+
+    #[test]
+    fn entry_points() {
+        const CODE: &str = r#"
+        @vertex
+        fn vs_sky(@builtin(vertex_index) i: u32) -> @builtin(position) vec4f {
+            return vec4f(f32(i));
+        }
+        @fragment
+        fn fs_main() -> @location(0) vec4f {
+            return vec4f(1.0);
+        }
+        "#;
+        assert!(validate_entry_points(CODE, "vs_sky", Some("fs_main")).is_ok());
+        assert!(validate_entry_points(CODE, "vs_sky", None).is_ok());
+        let err = validate_entry_points(CODE, "vs_main", Some("fs_main")).unwrap_err();
+        assert!(err.to_string().contains("vs_sky"));
+        assert!(validate_entry_points(CODE, "vs_sky", Some("vs_sky")).is_err());
+    }
+
+    #[test]
+    fn overrides() {
+        const CODE: &str = r#"
+        override ENABLE_SHADOWS: f32 = 0.0;
+        @id(7) override STEPS: u32 = 4u;
+        @compute @workgroup_size(1)
+        fn cs_main() {
+            _ = ENABLE_SHADOWS * f32(STEPS);
+        }
+        "#;
+        let constants = |keys: &[&str]| keys.iter().map(|k| (k.to_string(), 1.0)).collect();
+        assert!(validate_overrides(CODE, &constants(&["ENABLE_SHADOWS", "7"])).is_ok());
+        let err = validate_overrides(CODE, &constants(&["ENABLE_SHADOW"])).unwrap_err();
+        assert!(err.to_string().contains("ENABLE_SHADOW]"));
+    }
+}
@@ -0,0 +1,191 @@
+//! Vertex layouts assembled at runtime
+//!
+//! `VertexLayoutBuilder` describes a vertex format attribute by attribute and
+//! produces a `VertexInfoObj`, the same type the `Vertex` trait provides for
+//! static vertex types. This allows choosing the layout at runtime, e.g. to
+//! support meshes with and without normals using one code path.
+
+use crate::gfx::{ShaderCode, VertexInfo, VertexInfoObj};
+
+/// Builds a vertex layout from a list of named attributes
+///
+/// Attributes are packed in the order they are added, each at the next offset
+/// aligned to `min(4, format.size())` as WebGPU requires, and get
+/// consecutive shader locations starting at 0. The stride is rounded up to
+/// `wgpu::VERTEX_STRIDE_ALIGNMENT`. The generated `VertexBuf`
+/// shader struct uses the attribute names as field names.
+///
+/// # Example
+///
+/// ```ignore
+/// let mut builder = gfx::VertexLayoutBuilder::new().attribute("position", wgpu::VertexFormat::Float32x3);
+/// if has_normals {
+///     builder = builder.attribute("normal", wgpu::VertexFormat::Float32x3);
+/// }
+/// let info = builder.build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct VertexLayoutBuilder {
+    attributes: Vec<(String, wgpu::VertexAttribute)>,
+    stride: wgpu::BufferAddress,
+    step_mode: wgpu::VertexStepMode,
+}
+impl VertexLayoutBuilder {
+    /// Creates an empty per-vertex layout
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            attributes: Vec::new(),
+            stride: 0,
+            step_mode: wgpu::VertexStepMode::Vertex,
+        }
+    }
+    /// Appends an attribute at the next shader location
+    ///
+    /// # Arguments
+    /// * `name` - The field name in the generated `VertexBuf` struct
+    /// * `format` - The attribute format
+    ///
+    /// # Panics
+    ///
+    /// Panics if `format` has no corresponding WGSL type (64-bit formats).
+    pub fn attribute(mut self, name: &str, format: wgpu::VertexFormat) -> Self {
+        assert!(
+            wgsl_type(format).is_some(),
+            "Vertex format {format:?} is not supported in WGSL"
+        );
+        let offset = wgpu::util::align_to(self.stride, format.size().min(4));
+        let attribute = wgpu::VertexAttribute {
+            format,
+            offset,
+            shader_location: self.attributes.len() as u32,
+        };
+        self.stride = offset + format.size();
+        self.attributes.push((name.into(), attribute));
+        self
+    }
+    /// Sets the step mode, e.g. `Instance` for per-instance data
+    #[inline]
+    pub fn step_mode(self, step_mode: wgpu::VertexStepMode) -> Self {
+        Self { step_mode, ..self }
+    }
+    /// Returns the size of one vertex in bytes, including trailing padding
+    #[inline]
+    pub fn stride(&self) -> wgpu::BufferAddress {
+        wgpu::util::align_to(self.stride, wgpu::VERTEX_STRIDE_ALIGNMENT)
+    }
+    /// Creates the vertex info describing this layout
+    pub fn build(self) -> VertexInfoObj {
+        let mut code = String::from("struct VertexBuf {\n");
+        for (name, attribute) in self.attributes.iter() {
+            let ty = wgsl_type(attribute.format).unwrap();
+            code += &format!(
+                "    @location({}) {name}: {ty},\n",
+                attribute.shader_location
+            );
+        }
+        code += "};\n";
+        Box::new(DynamicVertexInfo {
+            stride: self.stride(),
+            attributes: self.attributes.into_iter().map(|(_, a)| a).collect(),
+            step_mode: self.step_mode,
+            code,
+        })
+    }
+}
+
+impl Default for VertexLayoutBuilder {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Vertex info produced by `VertexLayoutBuilder`
+struct DynamicVertexInfo {
+    attributes: Vec<wgpu::VertexAttribute>,
+    stride: wgpu::BufferAddress,
+    step_mode: wgpu::VertexStepMode,
+    code: String,
+}
+impl VertexInfo for DynamicVertexInfo {
+    fn describe(&self) -> wgpu::VertexBufferLayout<'_> {
+        wgpu::VertexBufferLayout {
+            array_stride: self.stride,
+            step_mode: self.step_mode,
+            attributes: &self.attributes,
+        }
+    }
+    fn shader_code(&self) -> ShaderCode {
+        ShaderCode(self.code.clone())
+    }
+}
+
+/// Returns the WGSL type a vertex attribute of `format` is read as
+fn wgsl_type(format: wgpu::VertexFormat) -> Option<&'static str> {
+    use wgpu::VertexFormat::*;
+    Some(match format {
+        Uint8 | Uint16 | Uint32 => "u32",
+        Sint8 | Sint16 | Sint32 => "i32",
+        Unorm8 | Snorm8 | Unorm16 | Snorm16 | Float16 | Float32 => "f32",
+        Uint8x2 | Uint16x2 | Uint32x2 => "vec2u",
+        Sint8x2 | Sint16x2 | Sint32x2 => "vec2i",
+        Unorm8x2 | Snorm8x2 | Unorm16x2 | Snorm16x2 | Float16x2 | Float32x2 => "vec2f",
+        Uint32x3 => "vec3u",
+        Sint32x3 => "vec3i",
+        Float32x3 => "vec3f",
+        Uint8x4 | Uint16x4 | Uint32x4 => "vec4u",
+        Sint8x4 | Sint16x4 | Sint32x4 => "vec4i",
+        Unorm8x4 | Snorm8x4 | Unorm16x4 | Snorm16x4 | Float16x4 | Float32x4 | Unorm10_10_10_2
+        | Unorm8x4Bgra => "vec4f",
+        Float64 | Float64x2 | Float64x3 | Float64x4 => return None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn builder_packs_attributes() {
+        let info = VertexLayoutBuilder::new()
+            .attribute("position", wgpu::VertexFormat::Float32x3)
+            .attribute("color", wgpu::VertexFormat::Unorm8x4)
+            .build();
+        let layout = info.describe();
+        assert_eq!(layout.array_stride, 16);
+        assert_eq!(layout.attributes[1].offset, 12);
+        assert_eq!(layout.attributes[1].shader_location, 1);
+
+        let code = info.shader_code().0;
+        assert!(code.contains("@location(0) position: vec3f,"));
+        assert!(code.contains("@location(1) color: vec4f,"));
+
+        // Offsets and the stride are aligned
+        let builder = VertexLayoutBuilder::new()
+            .attribute("position", wgpu::VertexFormat::Float32x3)
+            .attribute("uv", wgpu::VertexFormat::Unorm8x2)
+            .attribute("weight", wgpu::VertexFormat::Float32);
+        assert_eq!(builder.stride(), 20);
+        let info = builder.build();
+        let layout = info.describe();
+        assert_eq!(layout.attributes[1].offset, 12);
+        assert_eq!(layout.attributes[2].offset, 16);
+        assert_eq!(layout.array_stride, 20);
+
+        let info = VertexLayoutBuilder::new()
+            .attribute("position", wgpu::VertexFormat::Float32x3)
+            .attribute("uv", wgpu::VertexFormat::Unorm8x2)
+            .build();
+        assert_eq!(info.describe().array_stride, 16);
+        let info = VertexLayoutBuilder::new()
+            .attribute("a", wgpu::VertexFormat::Uint8x2)
+            .attribute("b", wgpu::VertexFormat::Float16)
+            .attribute("c", wgpu::VertexFormat::Uint8)
+            .build();
+        let layout = info.describe();
+        assert_eq!(layout.attributes[1].offset, 2);
+        assert_eq!(layout.attributes[2].offset, 4);
+        assert_eq!(layout.array_stride, 8);
+    }
+}
@@ -0,0 +1,312 @@
+//! Polled keyboard, mouse and touch state.
+//!
+//! `EVENTS` reports input as it happens. For game logic it is often simpler
+//! to ask for the current state instead, which the global [`INPUT`] tracks
+//! by subscribing to the keyboard and mouse button events.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, LazyLock};
+
+use parking_lot::{MappedMutexGuard, Mutex, MutexGuard};
+
+use crate::events::{
+    EVENTS, KeyCode, KeyboardData, MouseButton, MouseButtonData, TouchData, TouchPhase,
+};
+use crate::observer::{FnSubscriber, Priority, Subscription};
+
+/// Global input state instance.
+///
+/// `run` initializes it before the event loop starts, so no input event is
+/// missed.
+pub static INPUT: LazyLock<Input> = LazyLock::new(|| {
+    let input = Input {
+        state: Arc::new(Mutex::new(InputState::default())),
+    };
+    input.init();
+    input
+});
+
+/// Keys and buttons currently held down or pressed this frame
+#[derive(Default)]
+struct InputState {
+    held_keys: HashSet<KeyCode>,
+    held_buttons: HashSet<MouseButton>,
+    /// Keys pressed since the last end of frame, without repeats
+    pressed_keys: HashSet<KeyCode>,
+    /// Buttons pressed since the last end of frame
+    pressed_buttons: HashSet<MouseButton>,
+    /// Positions of the fingers on the screen, by touch id
+    touches: HashMap<u64, glam::Vec2>,
+    /// Whether the pressed flags last one update tick instead of one frame
+    per_update_tick: bool,
+    /// Keys pressed since the last update tick started
+    pending_keys: HashSet<KeyCode>,
+    /// Buttons pressed since the last update tick started
+    pending_buttons: HashSet<MouseButton>,
+}
+impl InputState {
+    fn press_key(&mut self, key: KeyCode) {
+        if self.per_update_tick {
+            self.pending_keys.insert(key);
+        } else {
+            self.pressed_keys.insert(key);
+        }
+    }
+    fn press_button(&mut self, button: MouseButton) {
+        if self.per_update_tick {
+            self.pending_buttons.insert(button);
+        } else {
+            self.pressed_buttons.insert(button);
+        }
+    }
+    fn end_frame(&mut self) {
+        if !self.per_update_tick {
+            self.pressed_keys.clear();
+            self.pressed_buttons.clear();
+        }
+    }
+}
+
+/// Polled keyboard, mouse and touch state, maintained from `EVENTS`.
+pub struct Input {
+    state: Arc<Mutex<InputState>>,
+}
+
+impl Input {
+    /// Subscribes to the keyboard, mouse button, touch and end of frame events.
+    fn init(&self) {
+        let state = self.state.clone();
+        EVENTS.keyboard().subscribe(
+            FnSubscriber::new(move |data: &KeyboardData| {
+                let mut state = state.lock();
+                if data.is_pressed {
+                    state.held_keys.insert(data.key_code);
+                    if !data.is_repeat {
+                        state.press_key(data.key_code);
+                    }
+                } else {
+                    state.held_keys.remove(&data.key_code);
+                }
+                Subscription::Keep
+            })
+            // Run first so other subscribers see the updated state
+            .with_priority(Priority::early(i32::MIN))
+            .boxed(),
+        );
+        let state = self.state.clone();
+        EVENTS.mouse_button().subscribe(
+            FnSubscriber::new(move |data: &MouseButtonData| {
+                let mut state = state.lock();
+                if data.is_pressed {
+                    state.held_buttons.insert(data.button);
+                    state.press_button(data.button);
+                } else {
+                    state.held_buttons.remove(&data.button);
+                }
+                Subscription::Keep
+            })
+            .with_priority(Priority::early(i32::MIN))
+            .boxed(),
+        );
+        let state = self.state.clone();
+        EVENTS.touch().subscribe(
+            FnSubscriber::new(move |data: &TouchData| {
+                let mut state = state.lock();
+                match data.phase {
+                    TouchPhase::Started | TouchPhase::Moved => {
+                        state.touches.insert(data.id, data.position);
+                    }
+                    TouchPhase::Ended | TouchPhase::Cancelled => {
+                        state.touches.remove(&data.id);
+                    }
+                }
+                Subscription::Keep
+            })
+            .with_priority(Priority::early(i32::MIN))
+            .boxed(),
+        );
+        let state = self.state.clone();
+        EVENTS.end_of_frame().subscribe(
+            FnSubscriber::new(move |_: &()| {
+                state.lock().end_frame();
+                Subscription::Keep
+            })
+            // Run last so other end of frame subscribers still see this frame's presses
+            .with_priority(Priority::late(i32::MAX))
+            .boxed(),
+        );
+    }
+
+    /// Makes the pressed flags last one update tick instead of one frame
+    ///
+    /// Used when `AppClient::update` runs on its own thread, which then
+    /// calls [`Input::begin_update_tick`] before every update.
+    pub(crate) fn set_per_update_tick(&self, enabled: bool) {
+        self.state.lock().per_update_tick = enabled;
+    }
+
+    /// Starts a new update tick, see [`Input::set_per_update_tick`]
+    ///
+    /// Presses since the previous call become the pressed flags, so every
+    /// press is seen by exactly one tick.
+    pub(crate) fn begin_update_tick(&self) {
+        let mut state = self.state.lock();
+        let state = &mut *state;
+        std::mem::swap(&mut state.pressed_keys, &mut state.pending_keys);
+        std::mem::swap(&mut state.pressed_buttons, &mut state.pending_buttons);
+        state.pending_keys.clear();
+        state.pending_buttons.clear();
+    }
+
+    /// Returns `true` if `key` is currently held down.
+    pub fn is_key_down(&self, key: KeyCode) -> bool {
+        self.state.lock().held_keys.contains(&key)
+    }
+
+    /// Returns `true` if `key` was pressed during the current frame.
+    ///
+    /// Key repeats don't count. The flag is cleared at the end of the frame,
+    /// or when the next update tick starts if `update` runs on its own thread.
+    pub fn was_key_pressed(&self, key: KeyCode) -> bool {
+        self.state.lock().pressed_keys.contains(&key)
+    }
+
+    /// Returns `true` if `button` is currently held down.
+    pub fn mouse_button_down(&self, button: MouseButton) -> bool {
+        self.state.lock().held_buttons.contains(&button)
+    }
+
+    /// Returns `true` if `button` was pressed during the current frame.
+    ///
+    /// The flag is cleared like the one of [`Input::was_key_pressed`].
+    pub fn was_mouse_button_pressed(&self, button: MouseButton) -> bool {
+        self.state.lock().pressed_buttons.contains(&button)
+    }
+
+    /// Returns the set of keys currently held down.
+    ///
+    /// The iteration order is unspecified. The returned guard locks the
+    /// input state, so drop it before more input events are published.
+    pub fn held_keys(&self) -> MappedMutexGuard<'_, HashSet<KeyCode>> {
+        MutexGuard::map(self.state.lock(), |state| &mut state.held_keys)
+    }
+
+    /// Returns the set of mouse buttons currently held down.
+    ///
+    /// See [`Input::held_keys`] for the locking caveat.
+    pub fn held_buttons(&self) -> MappedMutexGuard<'_, HashSet<MouseButton>> {
+        MutexGuard::map(self.state.lock(), |state| &mut state.held_buttons)
+    }
+
+    /// Returns the physical positions of all fingers on the screen, by touch id.
+    ///
+    /// See [`Input::held_keys`] for the locking caveat.
+    pub fn touches(&self) -> MappedMutexGuard<'_, HashMap<u64, glam::Vec2>> {
+        MutexGuard::map(self.state.lock(), |state| &mut state.touches)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::events::ModifiersState;
+
+    #[test]
+    fn tracks_held_keys_and_buttons() {
+        LazyLock::force(&INPUT);
+        let key = |is_pressed| KeyboardData {
+            key_code: KeyCode::F13,
+            is_pressed,
+            is_repeat: false,
+            modifiers: ModifiersState::empty(),
+            scancode: None,
+        };
+        EVENTS.keyboard().notify(&key(true));
+        EVENTS.mouse_button().notify(&MouseButtonData {
+            button: MouseButton::Back,
+            is_pressed: true,
+        });
+        assert!(INPUT.held_keys().contains(&KeyCode::F13));
+        assert!(INPUT.held_buttons().contains(&MouseButton::Back));
+        EVENTS.keyboard().notify(&key(false));
+        assert!(!INPUT.held_keys().contains(&KeyCode::F13));
+    }
+
+    #[test]
+    fn tracks_active_touches() {
+        LazyLock::force(&INPUT);
+        let touch = |id, phase, x| TouchData {
+            id,
+            phase,
+            position: glam::vec2(x, 0.0),
+            logical_position: glam::vec2(x, 0.0),
+        };
+        EVENTS.touch().notify(&touch(100, TouchPhase::Started, 1.0));
+        EVENTS.touch().notify(&touch(101, TouchPhase::Started, 2.0));
+        EVENTS.touch().notify(&touch(100, TouchPhase::Moved, 3.0));
+        assert_eq!(INPUT.touches().get(&100), Some(&glam::vec2(3.0, 0.0)));
+        assert!(INPUT.touches().contains_key(&101));
+        EVENTS.touch().notify(&touch(101, TouchPhase::Ended, 2.0));
+        EVENTS
+            .touch()
+            .notify(&touch(100, TouchPhase::Cancelled, 3.0));
+        assert!(!INPUT.touches().contains_key(&100));
+        assert!(!INPUT.touches().contains_key(&101));
+    }
+
+    #[test]
+    fn pressed_flags_last_one_frame() {
+        LazyLock::force(&INPUT);
+        EVENTS.keyboard().notify(&KeyboardData {
+            key_code: KeyCode::F14,
+            is_pressed: true,
+            is_repeat: false,
+            modifiers: ModifiersState::empty(),
+            scancode: None,
+        });
+        assert!(INPUT.is_key_down(KeyCode::F14));
+        assert!(INPUT.was_key_pressed(KeyCode::F14));
+        EVENTS.end_of_frame().notify(&());
+        assert!(INPUT.is_key_down(KeyCode::F14));
+        assert!(!INPUT.was_key_pressed(KeyCode::F14));
+
+        // Repeats keep the key held without a new press
+        EVENTS.keyboard().notify(&KeyboardData {
+            key_code: KeyCode::F14,
+            is_pressed: true,
+            is_repeat: true,
+            modifiers: ModifiersState::empty(),
+            scancode: None,
+        });
+        assert!(!INPUT.was_key_pressed(KeyCode::F14));
+    }
+
+    #[test]
+    fn pressed_flags_last_one_update_tick() {
+        let input = Input {
+            state: Default::default(),
+        };
+        input.init();
+        input.set_per_update_tick(true);
+        let press = || {
+            EVENTS.keyboard().notify(&KeyboardData {
+                key_code: KeyCode::F15,
+                is_pressed: true,
+                is_repeat: false,
+                modifiers: ModifiersState::empty(),
+                scancode: None,
+            })
+        };
+
+        // Presses between ticks are seen by the next tick only
+        press();
+        assert!(!input.was_key_pressed(KeyCode::F15));
+        input.begin_update_tick();
+        assert!(input.was_key_pressed(KeyCode::F15));
+        // and survive the end of frames rendered meanwhile
+        input.state.lock().end_frame();
+        assert!(input.was_key_pressed(KeyCode::F15));
+        input.begin_update_tick();
+        assert!(!input.was_key_pressed(KeyCode::F15));
+    }
+}
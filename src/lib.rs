@@ -1,17 +1,23 @@
 #![doc = include_str!("../README.md")]
 
+#[macro_use]
+mod profiling;
+
 mod app;
 mod events;
+mod input;
 mod run;
 mod time;
 
 pub mod gfx;
 pub mod macros;
 pub mod observer;
+pub mod rng;
 pub mod third_party;
 pub mod window;
 
 pub use app::*;
 pub use events::*;
+pub use input::*;
 pub use run::*;
 pub use time::*;
@@ -1,4 +1,4 @@
-//! Macros for defining application entry points
+//! Macros for defining application entry points and embedding shaders
 
 /// Defines the main entry point for a WGPU engine application
 ///
@@ -55,3 +55,44 @@ macro_rules! define_entry_point {
         }
     };
 }
+
+/// Builds a `ShaderLib` from `.wgsl` files embedded at compile time
+///
+/// Paths are relative to the file the macro is used in. Each file is
+/// registered under its path relative to the directory without the `.wgsl`
+/// extension, matching `ShaderLib::from_dir`, so a library can be loaded
+/// from disk during development and embedded for release builds.
+///
+/// The directory form embeds every `.wgsl` file below the directory. Edits
+/// to embedded files trigger a rebuild, but added or removed files are only
+/// picked up once the calling crate is rebuilt for another reason. Listing
+/// the keys embeds just those files.
+///
+/// # Example
+///
+/// ```ignore
+/// // Embeds every .wgsl file below shaders/, e.g. shaders/lib/pbr.wgsl as "lib/pbr"
+/// let lib = wgpu_engine::shader_lib!("shaders/");
+/// // Embeds shaders/common.wgsl and shaders/lib/pbr.wgsl
+/// let lib = wgpu_engine::shader_lib!("shaders/" => ["common", "lib/pbr"]);
+/// ```
+#[macro_export]
+macro_rules! shader_lib {
+    ($dir:literal) => {{
+        let mut lib = $crate::gfx::ShaderLib::new();
+        for (key, code) in $crate::macros::__shader_files!($dir) {
+            lib.insert(key, code);
+        }
+        lib
+    }};
+    ($dir:literal => [$($key:literal),* $(,)?]) => {{
+        let mut lib = $crate::gfx::ShaderLib::new();
+        $(
+            lib.insert($key, include_str!(concat!($dir, $key, ".wgsl")));
+        )*
+        lib
+    }};
+}
+
+#[doc(hidden)]
+pub use wgpu_engine_macros::shader_files as __shader_files;
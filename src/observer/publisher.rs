@@ -2,6 +2,9 @@
 
 use std::collections::HashSet;
 use std::collections::btree_map::{BTreeMap, Entry as BTreeMapEntry};
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 use crate::observer::Subscription;
 
@@ -20,9 +23,14 @@ use parking_lot::Mutex;
 pub struct Publisher<S: Subscriber> {
     /// Subscribers organized by priority (lower values = higher priority)
     registered: BTreeMap<Priority, Vec<(S, u64)>>,
-    dead_subscribers: Mutex<HashSet<u64>>,
-    /// Counter for generating unique subscriber IDs
-    next_id: u64,
+    /// IDs to remove on the next `maintain`, shared with `SubscriptionHandle`s
+    dead_subscribers: Arc<Mutex<HashSet<u64>>>,
+    /// Subscribers added through a `SubscribeQueue`, registered on the next `maintain`
+    pending: Arc<Mutex<Vec<(S, u64)>>>,
+    /// Counter for generating unique subscriber IDs, shared with `SubscribeQueue`s
+    next_id: Arc<AtomicU64>,
+    /// Whether panicking subscribers are caught instead of unwinding `notify`
+    catch_panics: bool,
 }
 impl<S: Subscriber> Publisher<S> {
     /// Creates a new empty publisher
@@ -30,10 +38,32 @@ impl<S: Subscriber> Publisher<S> {
     pub(crate) fn new() -> Self {
         Self {
             registered: BTreeMap::new(),
-            dead_subscribers: Mutex::new(HashSet::new()),
-            next_id: 1, // Start IDs at 1 (0 could be used as a sentinel value)
+            dead_subscribers: Arc::new(Mutex::new(HashSet::new())),
+            pending: Arc::new(Mutex::new(Vec::new())),
+            // Start IDs at 1 (0 could be used as a sentinel value)
+            next_id: Arc::new(AtomicU64::new(1)),
+            catch_panics: false,
         }
     }
+    /// Enables or disables per-subscriber panic isolation
+    ///
+    /// When enabled, a panic inside a subscriber's `handle_event` is caught
+    /// during `notify`, logged with the subscriber's ID, and the subscriber is
+    /// marked for unsubscribe. The remaining subscribers are still notified.
+    ///
+    /// This is disabled by default, so panics propagate as usual. Handlers are
+    /// treated as unwind safe, so any state a panicking handler was modifying
+    /// may be left inconsistent. Panics can't be caught at all when building
+    /// with `panic = "abort"`.
+    #[inline]
+    pub fn set_catch_panics(&mut self, enabled: bool) {
+        self.catch_panics = enabled;
+    }
+    /// Returns `true` if per-subscriber panic isolation is enabled
+    #[inline]
+    pub fn catch_panics(&self) -> bool {
+        self.catch_panics
+    }
     /// Subscribes a listener to this publisher
     ///
     /// The listener will be added to the appropriate priority group based on
@@ -47,9 +77,11 @@ impl<S: Subscriber> Publisher<S> {
     #[inline]
     pub fn subscribe(&mut self, listener: S) -> u64 {
         // Generate unique ID for this subscriber
-        let id = self.next_id;
-        self.next_id += 1;
-
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.register(listener, id);
+        id
+    }
+    fn register(&mut self, listener: S, id: u64) {
         // Add subscriber to the appropriate priority group
         match self.registered.entry(listener.priority()) {
             BTreeMapEntry::Vacant(vacant_entry) => {
@@ -61,7 +93,35 @@ impl<S: Subscriber> Publisher<S> {
                 occupied_entry.get_mut().push((listener, id));
             }
         }
-        id
+    }
+    /// Returns a handle for subscribing without access to the publisher
+    ///
+    /// Event publishers are locked while they notify, so a handler can't
+    /// subscribe to its own publisher directly. It can subscribe through a
+    /// queue obtained beforehand instead, see [`SubscribeQueue::subscribe`].
+    pub fn subscribe_queue(&self) -> SubscribeQueue<S> {
+        SubscribeQueue {
+            pending: self.pending.clone(),
+            next_id: self.next_id.clone(),
+        }
+    }
+    /// Subscribes a listener for as long as the returned handle is alive
+    ///
+    /// Dropping the handle marks the listener for unsubscribe, and it is
+    /// removed by the next `maintain`. The handle doesn't borrow the
+    /// publisher, so it can be kept anywhere, e.g. in a struct field.
+    ///
+    /// ```ignore
+    /// let _guard = EVENTS.keyboard().subscribe_scoped(subscriber);
+    /// ```
+    ///
+    /// # Arguments
+    /// * `listener` - The subscriber to add
+    pub fn subscribe_scoped(&mut self, listener: S) -> SubscriptionHandle {
+        SubscriptionHandle {
+            id: self.subscribe(listener),
+            dead_subscribers: Some(self.dead_subscribers.clone()),
+        }
     }
     /// Returns the total number of subscribers across all priority levels
     #[inline]
@@ -91,19 +151,66 @@ impl<S: Subscriber> Publisher<S> {
     pub fn unsubscribe(&mut self, listener_id: u64) {
         // Search through all priority groups and remove the subscriber with matching ID
         for (_, listeners) in self.registered.iter_mut() {
-            listeners.retain(|(_, id)| *id != listener_id);
+            listeners.retain(|(listener, id)| {
+                let keep = *id != listener_id;
+                if !keep {
+                    listener.on_unsubscribe();
+                }
+                keep
+            });
         }
     }
 
+    /// Moves a subscriber to another priority level
+    ///
+    /// The subscriber is called after the subscribers already registered at
+    /// `priority`, as if it had just subscribed there. Its own `priority()`
+    /// is not consulted again.
+    ///
+    /// Returns `false` if the ID is not found.
+    ///
+    /// # Arguments
+    /// * `listener_id` - The registration ID returned by `subscribe()`
+    /// * `priority` - The new priority of the subscriber
+    pub fn reprioritize(&mut self, listener_id: u64, priority: Priority) -> bool {
+        let found = self.registered.iter_mut().find_map(|(&old, listeners)| {
+            let index = listeners.iter().position(|(_, id)| *id == listener_id)?;
+            Some((old, listeners.remove(index)))
+        });
+        let Some((old, entry)) = found else {
+            return false;
+        };
+        if self.registered[&old].is_empty() {
+            self.registered.remove(&old);
+        }
+        self.registered.entry(priority).or_default().push(entry);
+        true
+    }
+
     pub fn mark_for_unsubscribe(&self, id: u64) {
         self.dead_subscribers.lock().insert(id);
     }
 
+    /// Registers queued subscribers and removes all subscribers marked for unsubscribe
+    ///
+    /// Each removed subscriber's `on_unsubscribe` hook runs here, in priority
+    /// order, right before it is dropped.
     pub fn maintain(&mut self) {
+        // Register subscribers queued through a `SubscribeQueue`
+        let pending = std::mem::take(&mut *self.pending.lock());
+        for (listener, id) in pending {
+            self.register(listener, id);
+        }
         // Remove dead subscribers
         let mut dead_subscribers = self.dead_subscribers.lock();
         self.registered.values_mut().for_each(|listeners| {
-            listeners.retain(|(_, id)| !dead_subscribers.contains(id));
+            listeners.retain(|(listener, id)| {
+                let keep = !dead_subscribers.contains(id);
+                if !keep {
+                    listener.on_unsubscribe();
+                }
+                keep
+            });
         });
         dead_subscribers.clear();
     }
@@ -123,13 +230,30 @@ impl<S: Subscriber> Publisher<S> {
                 .iter()
                 .filter(|(_, id)| !self.dead_subscribers.lock().contains(id)) // Exclude "dead" listeners
                 .for_each(|(l, id)| {
-                    if l.handle_event(data) == Subscription::Unsubscribe {
+                    if self.handle_event(l, *id, data) == Subscription::Unsubscribe {
                         self.mark_for_unsubscribe(*id);
                     }
                 });
         }
     }
 
+    /// Calls a single subscriber, isolating panics if enabled
+    fn handle_event(&self, listener: &S, id: u64, data: &S::Data) -> Subscription {
+        if !self.catch_panics {
+            return listener.handle_event(data);
+        }
+        let result = std::panic::catch_unwind(AssertUnwindSafe(|| listener.handle_event(data)));
+        result.unwrap_or_else(|payload| {
+            let message = payload
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+                .unwrap_or("<unknown panic>");
+            log::error!("Subscriber {id} panicked and will be unsubscribed: {message}");
+            Subscription::Unsubscribe
+        })
+    }
+
     #[inline]
     pub fn notify_mut(&mut self, data: &S::Data) {
         self.notify(data);
@@ -137,6 +261,78 @@ impl<S: Subscriber> Publisher<S> {
     }
 }
 
+/// Subscribes to a `Publisher` from anywhere, including its own handlers
+///
+/// Returned by `Publisher::subscribe_queue`. Queues are cheap to clone and
+/// don't borrow the publisher.
+///
+/// ```ignore
+/// let queue = EVENTS.keyboard().subscribe_queue();
+/// EVENTS.keyboard().subscribe(FnSubscriber::once(move |_: &KeyboardData| {
+///     queue.subscribe(follow_up());
+/// }).boxed());
+/// ```
+pub struct SubscribeQueue<S: Subscriber> {
+    pending: Arc<Mutex<Vec<(S, u64)>>>,
+    next_id: Arc<AtomicU64>,
+}
+impl<S: Subscriber> SubscribeQueue<S> {
+    /// Queues a listener to be subscribed on the next `Publisher::maintain`
+    ///
+    /// The listener doesn't receive events before that, so one added from a
+    /// handler misses the event being handled and fires from the first
+    /// `notify` after the publisher was maintained. `EVENTS` publishers are
+    /// maintained once per frame.
+    ///
+    /// Returns the registration ID of the listener, which is valid right
+    /// away, e.g. for `Publisher::mark_for_unsubscribe`.
+    pub fn subscribe(&self, listener: S) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.pending.lock().push((listener, id));
+        id
+    }
+}
+impl<S: Subscriber> Clone for SubscribeQueue<S> {
+    fn clone(&self) -> Self {
+        Self {
+            pending: self.pending.clone(),
+            next_id: self.next_id.clone(),
+        }
+    }
+}
+
+/// Keeps a subscription made with `Publisher::subscribe_scoped` alive
+///
+/// The subscriber is marked for unsubscribe when the handle is dropped.
+#[must_use = "the subscription ends when the handle is dropped"]
+pub struct SubscriptionHandle {
+    id: u64,
+    /// Dead set of the publisher, `None` once detached
+    dead_subscribers: Option<Arc<Mutex<HashSet<u64>>>>,
+}
+impl SubscriptionHandle {
+    /// Returns the registration ID of the subscriber
+    #[inline]
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+    /// Keeps the subscription alive without the handle
+    ///
+    /// Returns the registration ID, which can still be used with
+    /// `Publisher::unsubscribe`.
+    pub fn detach(mut self) -> u64 {
+        self.dead_subscribers = None;
+        self.id
+    }
+}
+impl Drop for SubscriptionHandle {
+    fn drop(&mut self) {
+        if let Some(dead_subscribers) = self.dead_subscribers.take() {
+            dead_subscribers.lock().insert(self.id);
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::observer::Subscription;
@@ -249,4 +445,154 @@ mod test {
         assert!(publisher.is_empty());
         assert_eq!(publisher.len(), 0);
     }
+    #[test]
+    fn on_unsubscribe_cleanup() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        use crate::observer::FnSubscriber;
+
+        let cleaned_up = Arc::new(AtomicBool::new(false));
+        let mut publisher: Publisher<Box<dyn Subscriber<Data = i32>>> = Publisher::new();
+        {
+            let cleaned_up = cleaned_up.clone();
+            publisher.subscribe(
+                FnSubscriber::new(|_: &i32| Subscription::Unsubscribe)
+                    .on_unsubscribe(move || cleaned_up.store(true, Ordering::SeqCst))
+                    .boxed(),
+            );
+        }
+
+        // Cleanup only runs once the subscriber is actually removed
+        publisher.notify(&0);
+        assert!(!cleaned_up.load(Ordering::SeqCst));
+        publisher.maintain();
+        assert!(cleaned_up.load(Ordering::SeqCst));
+        assert!(publisher.is_empty());
+    }
+    #[test]
+    fn once_subscriber() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicI32, Ordering};
+
+        use crate::observer::FnSubscriber;
+
+        let sum = Arc::new(AtomicI32::new(0));
+        let mut publisher: Publisher<Box<dyn Subscriber<Data = i32>>> = Publisher::new();
+        {
+            let sum = sum.clone();
+            publisher.subscribe(
+                FnSubscriber::once(move |value: &i32| {
+                    sum.fetch_add(*value, Ordering::SeqCst);
+                })
+                .with_priority(Priority::early(0))
+                .boxed(),
+            );
+        }
+
+        publisher.notify(&1);
+        publisher.notify(&2);
+        assert_eq!(sum.load(Ordering::SeqCst), 1);
+        publisher.maintain();
+        assert!(publisher.is_empty());
+    }
+    #[test]
+    fn scoped_subscription() {
+        let mut publisher: Publisher<TestSubscriber> = Publisher::new();
+        let subscriber = || TestSubscriber {
+            value: 1,
+            priority: Priority::new(0),
+        };
+        let handle = publisher.subscribe_scoped(subscriber());
+        let detached = publisher.subscribe_scoped(subscriber()).detach();
+        publisher.maintain();
+        assert_eq!(publisher.len(), 2);
+
+        drop(handle);
+        publisher.maintain();
+        assert_eq!(publisher.len(), 1);
+        publisher.unsubscribe(detached);
+        assert!(publisher.is_empty());
+    }
+    #[test]
+    fn reprioritize_changes_order() {
+        let test_value: ValueSeq = Rc::new(RefCell::new(Vec::new()));
+        let mut publisher: Publisher<TestSubscriber> = Publisher::new();
+        let ids: Vec<_> = [1, 2, 3]
+            .map(|value| {
+                publisher.subscribe(TestSubscriber {
+                    value,
+                    priority: Priority::new(0),
+                })
+            })
+            .into();
+
+        assert!(publisher.reprioritize(ids[2], Priority::early(0)));
+        assert!(publisher.reprioritize(ids[0], Priority::new(0)));
+        assert!(!publisher.reprioritize(0, Priority::new(0)));
+        publisher.notify(&test_value);
+        assert_eq!(*test_value.borrow(), vec![3, 2, 1]);
+        assert_eq!(publisher.len(), 3);
+    }
+    #[test]
+    fn subscribe_from_handler() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicI32, Ordering};
+
+        use crate::observer::FnSubscriber;
+
+        let calls = Arc::new(AtomicI32::new(0));
+        let mut publisher: Publisher<Box<dyn Subscriber<Data = i32>>> = Publisher::new();
+        let queue = publisher.subscribe_queue();
+        {
+            let calls = calls.clone();
+            publisher.subscribe(
+                FnSubscriber::once(move |_: &i32| {
+                    let calls = calls.clone();
+                    queue.subscribe(
+                        FnSubscriber::new(move |value: &i32| {
+                            calls.fetch_add(*value, Ordering::SeqCst);
+                            Subscription::Keep
+                        })
+                        .boxed(),
+                    );
+                })
+                .boxed(),
+            );
+        }
+
+        // The queued subscriber only fires once the publisher was maintained
+        publisher.notify(&1);
+        publisher.notify(&2);
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+        publisher.maintain();
+        assert_eq!(publisher.len(), 1);
+        publisher.notify(&3);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+    #[test]
+    fn catch_panics() {
+        /// Test subscriber that panics on every event
+        struct PanicSubscriber;
+        impl Subscriber for PanicSubscriber {
+            type Data = ValueSeq;
+            fn handle_event(&self, _data: &ValueSeq) -> Subscription {
+                panic!("Test panic");
+            }
+        }
+
+        let test_value: ValueSeq = Rc::new(RefCell::new(Vec::new()));
+        let mut publisher: Publisher<Box<dyn Subscriber<Data = ValueSeq>>> = Publisher::new();
+        publisher.set_catch_panics(true);
+        publisher.subscribe(Box::new(PanicSubscriber));
+        publisher.subscribe(Box::new(TestSubscriber {
+            value: 42,
+            priority: Priority::late(0),
+        }));
+
+        // The panicking subscriber is removed, the other one still runs
+        publisher.notify_mut(&test_value);
+        assert_eq!(*test_value.borrow(), vec![42]);
+        assert_eq!(publisher.len(), 1);
+    }
 }
@@ -30,6 +30,13 @@ pub trait Subscriber: Send {
     /// # Arguments
     /// * `data` - The event data to handle
     fn handle_event(&self, data: &Self::Data) -> Subscription;
+    /// Called once when the subscriber is removed from its publisher
+    ///
+    /// This runs from `Publisher::maintain` (or `Publisher::unsubscribe`)
+    /// right before the subscriber is dropped, while the publisher is
+    /// borrowed mutably, so it must not access the same publisher.
+    /// Default implementation does nothing.
+    fn on_unsubscribe(&self) {}
 }
 
 /// Implementation for boxed subscribers to enable trait object usage
@@ -41,6 +48,9 @@ impl<T> Subscriber for Box<dyn Subscriber<Data = T>> {
     fn handle_event(&self, data: &T) -> Subscription {
         self.as_ref().handle_event(data)
     }
+    fn on_unsubscribe(&self) {
+        self.as_ref().on_unsubscribe()
+    }
 }
 
 /// A subscriber implementation that wraps a function or closure
@@ -56,6 +66,8 @@ pub struct FnSubscriber<T: Send, F: Fn(&T) -> Subscription + Send> {
     f: Mutex<F>,
     /// The priority of this subscriber
     priority: Priority,
+    /// Optional cleanup to run when the subscriber is removed
+    on_unsubscribe: Mutex<Option<Box<dyn FnOnce() + Send>>>,
     /// Phantom data for type safety
     _data: std::marker::PhantomData<T>,
 }
@@ -68,6 +80,7 @@ impl<T: Send + 'static, F: Fn(&T) -> Subscription + Send + 'static> FnSubscriber
         Self {
             f: Mutex::new(f),
             priority: Priority::new(0),
+            on_unsubscribe: Mutex::new(None),
             _data: Default::default(),
         }
     }
@@ -78,6 +91,21 @@ impl<T: Send + 'static, F: Fn(&T) -> Subscription + Send + 'static> FnSubscriber
     pub fn with_priority(self, priority: Priority) -> Self {
         Self { priority, ..self }
     }
+    /// Sets a cleanup closure that runs once when the subscriber is removed
+    ///
+    /// This is useful to release resources owned by the handler, e.g. GPU
+    /// buffers, as soon as the subscription ends rather than whenever the
+    /// last reference happens to be dropped. See `Subscriber::on_unsubscribe`
+    /// for when exactly it runs.
+    ///
+    /// # Arguments
+    /// * `f` - The cleanup closure
+    pub fn on_unsubscribe(self, f: impl FnOnce() + Send + 'static) -> Self {
+        Self {
+            on_unsubscribe: Mutex::new(Some(Box::new(f))),
+            ..self
+        }
+    }
     /// Converts this subscriber into a boxed trait object
     ///
     /// This is useful when you need to store different types of subscribers
@@ -86,6 +114,28 @@ impl<T: Send + 'static, F: Fn(&T) -> Subscription + Send + 'static> FnSubscriber
         Box::new(self)
     }
 }
+impl<T: Send + 'static> FnSubscriber<T, fn(&T) -> Subscription> {
+    /// Creates a subscriber that handles only the first event
+    ///
+    /// `f` runs on the first notification, after which the subscriber
+    /// unsubscribes itself. Like other subscribers it is removed by the
+    /// publisher's next `maintain`. This composes with `with_priority`,
+    /// `on_unsubscribe` and `boxed`.
+    ///
+    /// # Arguments
+    /// * `f` - The function to call with the first event
+    pub fn once(
+        f: impl FnOnce(&T) + Send + 'static,
+    ) -> FnSubscriber<T, impl Fn(&T) -> Subscription + Send + 'static> {
+        let f = Mutex::new(Some(f));
+        FnSubscriber::new(move |data: &T| {
+            if let Some(f) = f.lock().take() {
+                f(data);
+            }
+            Subscription::Unsubscribe
+        })
+    }
+}
 /// Subscriber trait implementation for FnSubscriber
 impl<T: Send, F: Fn(&T) -> Subscription + Send> Subscriber for FnSubscriber<T, F> {
     type Data = T;
@@ -96,6 +146,11 @@ impl<T: Send, F: Fn(&T) -> Subscription + Send> Subscriber for FnSubscriber<T, F
         // Call the wrapped function with the event data
         self.f.lock()(data)
     }
+    fn on_unsubscribe(&self) {
+        if let Some(f) = self.on_unsubscribe.lock().take() {
+            f();
+        }
+    }
 }
 
 #[cfg(test)]
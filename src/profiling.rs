@@ -0,0 +1,19 @@
+//! Internal profiling instrumentation
+//!
+//! When the `tracing` feature is enabled, the engine wraps its main phases in
+//! `tracing` spans carrying the current frame number and delta. Without the
+//! feature these macros expand to nothing.
+
+/// Enters a `tracing` span named after an engine phase until the end of the
+/// enclosing scope
+macro_rules! phase_span {
+    ($name:literal) => {
+        #[cfg(feature = "tracing")]
+        let _span = ::tracing::info_span!(
+            $name,
+            frame = $crate::TIME.frame_count(),
+            delta = $crate::TIME.unscaled_delta()
+        )
+        .entered();
+    };
+}
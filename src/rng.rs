@@ -0,0 +1,139 @@
+//! Deterministic random number generation
+//!
+//! This module provides a small, fast PRNG (xoshiro128**) together with a
+//! global instance. The global instance is seeded from the clock on first
+//! use; call [`seed`] at startup with a fixed value to make a run reproduce
+//! exactly.
+
+use std::sync::LazyLock;
+
+use parking_lot::Mutex;
+
+/// Global generator used by the free functions of this module
+static GLOBAL: LazyLock<Mutex<Rng>> = LazyLock::new(|| {
+    let seed = web_time::SystemTime::now()
+        .duration_since(web_time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default();
+    Mutex::new(Rng::new(seed))
+});
+
+/// A xoshiro128** pseudo random number generator
+///
+/// Not suitable for cryptographic purposes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rng {
+    state: [u32; 4],
+}
+impl Rng {
+    /// Creates a generator from a seed
+    ///
+    /// The same seed always yields the same sequence of numbers.
+    pub fn new(seed: u64) -> Self {
+        // Expand the seed with splitmix64, which never yields an all-zero state
+        let mut x = seed;
+        let mut split = || {
+            x = x.wrapping_add(0x9e3779b97f4a7c15);
+            let mut z = x;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+            z ^ (z >> 31)
+        };
+        let (a, b) = (split(), split());
+        Self {
+            state: [a as u32, (a >> 32) as u32, b as u32, (b >> 32) as u32],
+        }
+    }
+
+    /// Returns the next random `u32`
+    pub fn next_u32(&mut self) -> u32 {
+        let s = &mut self.state;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 9;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(11);
+        result
+    }
+
+    /// Returns a random `f32` in range [0.0, 1.0)
+    #[inline]
+    pub fn next_f32(&mut self) -> f32 {
+        // Use the upper 24 bits, which fit the mantissa exactly
+        (self.next_u32() >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    /// Returns a random `f32` in range [min, max)
+    #[inline]
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+
+    /// Returns a uniformly distributed random direction of length 1
+    pub fn unit_vec3(&mut self) -> glam::Vec3 {
+        let z = self.range(-1.0, 1.0);
+        let angle = self.range(0.0, std::f32::consts::TAU);
+        let r = (1.0 - z * z).sqrt();
+        glam::vec3(r * angle.cos(), r * angle.sin(), z)
+    }
+}
+
+/// Reseeds the global generator
+///
+/// Seeding with a fixed value at startup makes everything drawn from the
+/// global generator reproducible.
+pub fn seed(seed: u64) {
+    *GLOBAL.lock() = Rng::new(seed);
+}
+
+/// Returns the next random `u32` from the global generator
+pub fn next_u32() -> u32 {
+    GLOBAL.lock().next_u32()
+}
+
+/// Returns a random `f32` in range [0.0, 1.0) from the global generator
+pub fn next_f32() -> f32 {
+    GLOBAL.lock().next_f32()
+}
+
+/// Returns a random `f32` in range [min, max) from the global generator
+pub fn range(min: f32, max: f32) -> f32 {
+    GLOBAL.lock().range(min, max)
+}
+
+/// Returns a random unit vector from the global generator
+pub fn unit_vec3() -> glam::Vec3 {
+    GLOBAL.lock().unit_vec3()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_sequence() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        let mut c = Rng::new(43);
+        let seq_a: Vec<_> = (0..8).map(|_| a.next_u32()).collect();
+        let seq_b: Vec<_> = (0..8).map(|_| b.next_u32()).collect();
+        let seq_c: Vec<_> = (0..8).map(|_| c.next_u32()).collect();
+        assert_eq!(seq_a, seq_b);
+        assert_ne!(seq_a, seq_c);
+    }
+
+    #[test]
+    fn ranges() {
+        let mut rng = Rng::new(7);
+        for _ in 0..1000 {
+            let f = rng.next_f32();
+            assert!((0.0..1.0).contains(&f));
+            let r = rng.range(-2.0, 3.0);
+            assert!((-2.0..3.0).contains(&r));
+            assert!((rng.unit_vec3().length() - 1.0).abs() < 1e-5);
+        }
+    }
+}
@@ -7,7 +7,7 @@
 use wasm_bindgen::prelude::*;
 pub use winit::event_loop::EventLoop;
 
-use crate::app::{App, SharedAppClient, set_app};
+use crate::app::{App, AppExitError, SharedAppClient, set_app};
 
 /// Runs the application with the given client
 /// 
@@ -20,7 +20,9 @@ use crate::app::{App, SharedAppClient, set_app};
 /// 
 /// # Returns
 /// 
-/// Returns `Ok(())` on successful completion, or an error if initialization fails.
+/// Returns `Ok(())` on successful completion, an [`AppExitError`] if the app
+/// exited via `App::exit_with` with a non-zero code, or an error if
+/// initialization fails.
 pub fn run(client: SharedAppClient) -> anyhow::Result<()> {
     // Initialize logging based on platform
     #[cfg(not(target_arch = "wasm32"))]
@@ -30,6 +32,7 @@ pub fn run(client: SharedAppClient) -> anyhow::Result<()> {
     #[cfg(target_arch = "wasm32")]
     {
         console_log::init_with_level(log::Level::Info).unwrap_throw();
+        crate::window::init_clipboard();
     }
 
     // Create the winit event loop with custom user events
@@ -44,8 +47,14 @@ pub fn run(client: SharedAppClient) -> anyhow::Result<()> {
     
     // Set the global application instance
     set_app(app.clone());
+
+    // Start tracking input before the first events arrive
+    std::sync::LazyLock::force(&crate::INPUT);
     
     // Start the main event loop
     event_loop.run_app(&mut app)?;
-    Ok(())
+    match app.exit_code() {
+        0 => Ok(()),
+        code => Err(AppExitError { code }.into()),
+    }
 }
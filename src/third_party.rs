@@ -20,6 +20,12 @@ mod _exports {
     pub use parking_lot;
     /// Derive macros for common traits
     pub use educe;
+    /// Image decoding and encoding
+    pub use image;
+
+    /// Structured tracing spans for profiling
+    #[cfg(feature = "tracing")]
+    pub use tracing;
 
     /// Async runtime for native platforms
     #[cfg(not(target_arch = "wasm32"))]
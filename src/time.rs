@@ -5,6 +5,7 @@
 //! The timing system automatically integrates with the engine's event system to
 //! maintain accurate frame timing.
 
+use std::collections::VecDeque;
 use std::sync::{Arc, LazyLock};
 
 use parking_lot::Mutex;
@@ -19,12 +20,7 @@ use crate::observer::{FnSubscriber, Priority, Subscription};
 /// It's automatically initialized on first access and integrates with the event system
 /// to track frame timing accurately.
 pub static TIME: LazyLock<Time> = LazyLock::new(|| {
-    let time = Time {
-        last_frame: Arc::new(Mutex::new(None)),
-        current_frame: Arc::new(Mutex::new(None)),
-        app_start: Mutex::new(None),
-        frame_delta: Arc::new(Mutex::new(Duration::new(0, 0))),
-    };
+    let time = Time::new();
     // Initialize timing system and subscribe to frame events
     time.init();
     time
@@ -33,9 +29,10 @@ pub static TIME: LazyLock<Time> = LazyLock::new(|| {
 /// High-precision time manager for frame timing and application runtime tracking.
 ///
 /// The `Time` struct provides thread-safe access to timing information including:
-/// - Frame delta time (time between frames)
+/// - Frame delta time (time between frames), optionally scaled or paused
 /// - Total application runtime
 /// - Current and previous frame timestamps
+/// - Number of frames since application start
 ///
 /// All timing data is automatically updated through the engine's event system.
 pub struct Time {
@@ -47,9 +44,73 @@ pub struct Time {
     app_start: Mutex<Option<Instant>>,
     /// Duration between the current and previous frame
     frame_delta: Arc<Mutex<Duration>>,
+    /// Frame delta multiplied by the time scale, 0 while paused
+    scaled_delta: Arc<Mutex<f32>>,
+    /// Sum of all scaled frame deltas
+    scaled_running_time: Arc<Mutex<f32>>,
+    /// Multiplier applied to the frame delta
+    time_scale: Arc<Mutex<f32>>,
+    /// Flag indicating scaled time is stopped
+    paused: Arc<Mutex<bool>>,
+    /// Number of frames started since application start
+    frame_count: Arc<Mutex<u64>>,
+    /// Time not yet consumed by fixed steps, in seconds
+    fixed_accumulator: Mutex<f32>,
+    /// Fraction of a fixed step left in the accumulator
+    fixed_alpha: Mutex<f32>,
+    /// Deltas of the recent frames, used for the smoothed values
+    history: Arc<Mutex<DeltaHistory>>,
+}
+
+/// Ring buffer of the frame deltas within the last `WINDOW` seconds
+#[derive(Default)]
+struct DeltaHistory {
+    deltas: VecDeque<f32>,
+}
+impl DeltaHistory {
+    /// Time span the smoothed values are averaged over, in seconds
+    const WINDOW: f32 = 1.0;
+    /// Maximum number of frames kept, bounding the memory at high frame rates
+    const CAPACITY: usize = 240;
+
+    fn push(&mut self, delta: f32) {
+        self.deltas.push_back(delta);
+        // Summed anew on every push so rounding errors can't build up
+        let mut total: f32 = self.deltas.iter().sum();
+        while self.deltas.len() > 1
+            && (total - self.deltas[0] >= Self::WINDOW || self.deltas.len() > Self::CAPACITY)
+        {
+            total -= self.deltas.pop_front().unwrap();
+        }
+    }
+    fn average(&self) -> f32 {
+        if self.deltas.is_empty() {
+            0.0
+        } else {
+            self.deltas.iter().sum::<f32>() / self.deltas.len() as f32
+        }
+    }
 }
 
 impl Time {
+    /// Creates a time manager that is not yet subscribed to frame events.
+    fn new() -> Self {
+        Self {
+            last_frame: Arc::new(Mutex::new(None)),
+            current_frame: Arc::new(Mutex::new(None)),
+            app_start: Mutex::new(None),
+            frame_delta: Arc::new(Mutex::new(Duration::new(0, 0))),
+            scaled_delta: Arc::new(Mutex::new(0.0)),
+            scaled_running_time: Arc::new(Mutex::new(0.0)),
+            time_scale: Arc::new(Mutex::new(1.0)),
+            paused: Arc::new(Mutex::new(false)),
+            frame_count: Arc::new(Mutex::new(0)),
+            fixed_accumulator: Mutex::new(0.0),
+            fixed_alpha: Mutex::new(0.0),
+            history: Arc::new(Mutex::new(DeltaHistory::default())),
+        }
+    }
+
     /// Initializes the timing system and subscribes to frame events.
     ///
     /// This method sets up the initial timestamps and registers event handlers
@@ -65,14 +126,31 @@ impl Time {
         let last_frame = self.last_frame.clone();
         let current_frame = self.current_frame.clone();
         let frame_delta = self.frame_delta.clone();
-        
+        let frame_count = self.frame_count.clone();
+        let history = self.history.clone();
+        let scaled_delta = self.scaled_delta.clone();
+        let scaled_running_time = self.scaled_running_time.clone();
+        let time_scale = self.time_scale.clone();
+        let paused = self.paused.clone();
+
         // Subscribe to start-of-frame events to update current frame time
         EVENTS.start_of_frame().subscribe(
             FnSubscriber::new(move |_| {
                 let now = Instant::now();
                 *current_frame.lock() = Some(now);
                 // Calculate frame delta using the previous frame's timestamp
-                *frame_delta.lock() = now - last_frame.lock().unwrap();
+                let delta = now - last_frame.lock().unwrap();
+                *frame_delta.lock() = delta;
+                *frame_count.lock() += 1;
+                history.lock().push(delta.as_secs_f32());
+                let scale = if *paused.lock() {
+                    0.0
+                } else {
+                    *time_scale.lock()
+                };
+                let scaled = delta.as_secs_f32() * scale;
+                *scaled_delta.lock() = scaled;
+                *scaled_running_time.lock() += scaled;
                 Subscription::Keep
             })
             .with_priority(Priority::early(i32::MIN)) // Run first to ensure accurate timing
@@ -97,6 +175,9 @@ impl Time {
 
     /// Returns the total time the application has been running in seconds.
     ///
+    /// This is real wall-clock time, unaffected by the time scale and pausing;
+    /// see [`Time::scaled_running_time`] for the game time.
+    ///
     /// # Returns
     ///
     /// The elapsed time since application start as a floating-point number of seconds.
@@ -114,12 +195,351 @@ impl Time {
     /// Returns the time elapsed between the current and previous frame in seconds.
     ///
     /// This value is commonly used for frame-rate independent animations and physics.
+    /// It is multiplied by the time scale and is 0.0 while paused, so gameplay
+    /// code using it honors slow motion and pause menus. `AppClient::update`
+    /// receives this value.
     ///
     /// # Returns
     ///
-    /// The frame delta time as a floating-point number of seconds.
+    /// The scaled frame delta time as a floating-point number of seconds.
     #[inline]
     pub fn frame_delta(&self) -> f32 {
+        *self.scaled_delta.lock()
+    }
+
+    /// Returns the real time elapsed between the current and previous frame in seconds.
+    ///
+    /// Unlike [`Time::frame_delta`] this ignores the time scale and pausing,
+    /// which is what UI animations in a pause menu need.
+    #[inline]
+    pub fn unscaled_delta(&self) -> f32 {
         self.frame_delta.lock().as_secs_f32()
     }
+
+    /// Returns the sum of all scaled frame deltas in seconds.
+    ///
+    /// This is the game time: it advances slower in slow motion and stops
+    /// while paused.
+    #[inline]
+    pub fn scaled_running_time(&self) -> f32 {
+        *self.scaled_running_time.lock()
+    }
+
+    /// Sets the multiplier applied to [`Time::frame_delta`], 1.0 by default.
+    ///
+    /// Takes effect with the next frame.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `scale` is negative or not finite.
+    pub fn set_time_scale(&self, scale: f32) {
+        assert!(
+            scale.is_finite() && scale >= 0.0,
+            "Time scale must be finite and non-negative"
+        );
+        *self.time_scale.lock() = scale;
+    }
+
+    /// Returns the multiplier applied to [`Time::frame_delta`].
+    #[inline]
+    pub fn time_scale(&self) -> f32 {
+        *self.time_scale.lock()
+    }
+
+    /// Stops scaled time from the next frame on, see [`Time::frame_delta`].
+    ///
+    /// The time scale is kept and applies again after [`Time::resume`].
+    pub fn pause(&self) {
+        *self.paused.lock() = true;
+    }
+
+    /// Resumes scaled time after [`Time::pause`].
+    pub fn resume(&self) {
+        *self.paused.lock() = false;
+    }
+
+    /// Returns `true` while scaled time is paused.
+    #[inline]
+    pub fn is_paused(&self) -> bool {
+        *self.paused.lock()
+    }
+
+    /// Returns the factor that turns real deltas into scaled deltas.
+    ///
+    /// This is the time scale, or 0.0 while paused. The update thread applies
+    /// it to its own deltas.
+    pub(crate) fn effective_scale(&self) -> f32 {
+        if self.is_paused() {
+            0.0
+        } else {
+            self.time_scale()
+        }
+    }
+
+    /// Returns the frame delta averaged over roughly the last second.
+    ///
+    /// Unlike [`Time::unscaled_delta`] a single slow frame barely changes this
+    /// value, which makes it suitable for animations that should not jitter
+    /// and for performance overlays. Like the fps it measures real time and
+    /// ignores the time scale. Returns 0.0 before the first frame.
+    #[inline]
+    pub fn smoothed_delta(&self) -> f32 {
+        self.history.lock().average()
+    }
+
+    /// Returns the frames per second averaged over roughly the last second.
+    ///
+    /// Returns 0.0 before the first frame.
+    #[inline]
+    pub fn fps(&self) -> f32 {
+        let delta = self.smoothed_delta();
+        if delta > 0.0 { 1.0 / delta } else { 0.0 }
+    }
+
+    /// Returns the number of frames started since application start.
+    ///
+    /// The first frame is frame 1; this returns 0 before any frame has started.
+    #[inline]
+    pub fn frame_count(&self) -> u64 {
+        *self.frame_count.lock()
+    }
+
+    /// Adds `delta` seconds to the fixed-timestep accumulator.
+    ///
+    /// Returns how many fixed steps of `fixed_delta` seconds are due, at most
+    /// `max_steps`. Time beyond that is dropped so a long stall does not
+    /// trigger an ever-growing number of catch-up steps. The app loop calls
+    /// this once per frame to drive `AppClient::fixed_update`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fixed_delta` is not positive.
+    pub(crate) fn accumulate_fixed(&self, delta: f32, fixed_delta: f32, max_steps: u32) -> u32 {
+        assert!(fixed_delta > 0.0, "Fixed timestep must be positive");
+        let mut accumulator = self.fixed_accumulator.lock();
+        *accumulator += delta;
+        let mut steps = 0;
+        while *accumulator >= fixed_delta && steps < max_steps {
+            *accumulator -= fixed_delta;
+            steps += 1;
+        }
+        // Drop the time that could not be caught up
+        if *accumulator >= fixed_delta {
+            *accumulator %= fixed_delta;
+        }
+        *self.fixed_alpha.lock() = *accumulator / fixed_delta;
+        steps
+    }
+
+    /// Returns how far the current frame is between two fixed steps, in `0..1`.
+    ///
+    /// Rendering at a variable frame rate while the simulation advances in
+    /// fixed steps makes motion stutter. To render smoothly, keep both the
+    /// previous and the current simulation state in `fixed_update` and
+    /// interpolate between them in `render`:
+    ///
+    /// ```ignore
+    /// let position = previous.lerp(current, TIME.fixed_alpha());
+    /// ```
+    #[inline]
+    pub fn fixed_alpha(&self) -> f32 {
+        *self.fixed_alpha.lock()
+    }
+}
+
+/// A countdown that is ticked manually with a frame delta.
+///
+/// Unlike [`TIME`] it holds no global state, so it works headlessly and in
+/// tests. Tick it in `AppClient::update` with the delta passed there:
+///
+/// ```ignore
+/// if self.spawn_timer.lock().tick(delta_time) {
+///     self.spawn_enemy();
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timer {
+    /// Time until the timer elapses, in seconds
+    duration: f32,
+    /// Time accumulated since the start or the last repetition, in seconds
+    elapsed: f32,
+    /// Flag to restart the timer whenever it elapses
+    repeating: bool,
+    /// Flag indicating a non-repeating timer has elapsed
+    finished: bool,
+}
+
+impl Timer {
+    /// Creates a one-shot timer elapsing after `duration` seconds.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `duration` is not positive.
+    pub fn new(duration: f32) -> Self {
+        assert!(duration > 0.0, "Timer duration must be positive");
+        Self {
+            duration,
+            elapsed: 0.0,
+            repeating: false,
+            finished: false,
+        }
+    }
+
+    /// Returns this timer restarting whenever it elapses.
+    #[inline]
+    pub fn with_repeating(mut self, repeating: bool) -> Self {
+        self.repeating = repeating;
+        self
+    }
+
+    /// Sets whether the timer restarts whenever it elapses.
+    #[inline]
+    pub fn set_repeating(&mut self, repeating: bool) {
+        self.repeating = repeating;
+    }
+
+    /// Returns `true` if the timer restarts whenever it elapses.
+    #[inline]
+    pub fn is_repeating(&self) -> bool {
+        self.repeating
+    }
+
+    /// Advances the timer by `delta` seconds.
+    ///
+    /// A repeating timer carries the time beyond its duration over into the
+    /// next repetition, so it stays in sync with the frame deltas. If `delta`
+    /// covers several repetitions they are reported as one.
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the timer elapsed during this tick.
+    pub fn tick(&mut self, delta: f32) -> bool {
+        if self.finished {
+            return false;
+        }
+        self.elapsed += delta;
+        if self.elapsed < self.duration {
+            return false;
+        }
+        if self.repeating {
+            self.elapsed %= self.duration;
+        } else {
+            self.elapsed = self.duration;
+            self.finished = true;
+        }
+        true
+    }
+
+    /// Restarts the timer from zero.
+    pub fn reset(&mut self) {
+        self.elapsed = 0.0;
+        self.finished = false;
+    }
+
+    /// Returns `true` if a one-shot timer has elapsed.
+    ///
+    /// Repeating timers never finish.
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Returns the duration of the timer in seconds.
+    #[inline]
+    pub fn duration(&self) -> f32 {
+        self.duration
+    }
+
+    /// Returns the time elapsed since the start or the last repetition in seconds.
+    #[inline]
+    pub fn elapsed(&self) -> f32 {
+        self.elapsed
+    }
+
+    /// Returns the progress towards the next elapse, in `0..=1`.
+    #[inline]
+    pub fn elapsed_fraction(&self) -> f32 {
+        (self.elapsed / self.duration).min(1.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn smoothed_delta_window() {
+        let mut history = DeltaHistory::default();
+        assert_eq!(history.average(), 0.0);
+        for _ in 0..100 {
+            history.push(0.03);
+        }
+        // Only the last second is kept
+        assert_eq!(history.deltas.len(), 34);
+        assert!((history.average() - 0.03).abs() < 1e-6);
+        // A single spike is smoothed out
+        history.push(0.2);
+        assert!(history.average() < 0.04);
+        // A frame longer than the window is still kept
+        history.push(5.0);
+        assert_eq!(history.deltas.len(), 1);
+        assert_eq!(history.average(), 5.0);
+    }
+
+    #[test]
+    fn time_scale_and_pause() {
+        let time = Time::new();
+        assert_eq!(time.effective_scale(), 1.0);
+        time.set_time_scale(0.5);
+        assert_eq!(time.effective_scale(), 0.5);
+        time.pause();
+        assert!(time.is_paused());
+        assert_eq!(time.effective_scale(), 0.0);
+        time.resume();
+        assert_eq!(time.effective_scale(), 0.5);
+    }
+
+    #[test]
+    fn fixed_steps_and_alpha() {
+        let time = Time::new();
+        assert_eq!(time.accumulate_fixed(0.25, 0.1, 8), 2);
+        assert!((time.fixed_alpha() - 0.5).abs() < 1e-4);
+        assert_eq!(time.accumulate_fixed(0.04, 0.1, 8), 0);
+        assert!((time.fixed_alpha() - 0.9).abs() < 1e-4);
+        // A long stall is clamped to `max_steps`
+        assert_eq!(time.accumulate_fixed(10.0, 0.1, 4), 4);
+        assert!(time.fixed_alpha() < 1.0);
+    }
+
+    #[test]
+    fn timer_accumulates_fractional_frames() {
+        let mut timer = Timer::new(0.1);
+        // Three frames of 1/30 s add up to the duration
+        assert!(!timer.tick(1.0 / 30.0));
+        assert!(!timer.tick(1.0 / 30.0));
+        assert!((timer.elapsed_fraction() - 2.0 / 3.0).abs() < 1e-5);
+        assert!(timer.tick(0.034));
+        assert!(timer.is_finished());
+        assert_eq!(timer.elapsed_fraction(), 1.0);
+        // A finished one-shot timer stays quiet until reset
+        assert!(!timer.tick(1.0));
+        timer.reset();
+        assert_eq!(timer.elapsed(), 0.0);
+        assert!(!timer.tick(0.05));
+    }
+
+    #[test]
+    fn repeating_timer_wraps_around() {
+        let mut timer = Timer::new(1.0).with_repeating(true);
+        assert!(!timer.tick(0.75));
+        assert!(timer.tick(0.5));
+        // The overshoot is carried into the next repetition
+        assert!((timer.elapsed() - 0.25).abs() < 1e-6);
+        assert!(!timer.is_finished());
+        assert!(!timer.tick(0.5));
+        assert!(timer.tick(0.25));
+        // Several repetitions in one tick keep the remainder
+        assert!(timer.tick(2.5));
+        assert!((timer.elapsed() - 0.5).abs() < 1e-6);
+    }
 }
@@ -1,14 +1,22 @@
 //! Window management utilities for the wgpu-engine.
 //!
 //! This module provides convenient functions for managing window state,
-//! particularly fullscreen control operations.
+//! particularly fullscreen control operations, monitor selection, the
+//! window title, the cursor, text input and the clipboard.
 
 use std::sync::Arc;
 
+use winit::monitor::{MonitorHandle, VideoModeHandle};
 use winit::window::{Fullscreen, Window};
 
+pub use winit::window::CursorGrabMode;
+
 use crate::app;
 
+mod clipboard;
+#[cfg(target_arch = "wasm32")]
+pub(crate) use clipboard::init as init_clipboard;
+
 /// Retrieves the current active window from the application state.
 ///
 /// # Returns
@@ -19,6 +27,114 @@ pub fn window() -> Option<Arc<Window>> {
     app().state().as_ref().and_then(|s| s.window.clone())
 }
 
+/// Returns the ratio of physical to logical pixels of the active window.
+///
+/// Returns 1.0 if no window has been created yet.
+pub fn scale_factor() -> f64 {
+    crate::EVENTS.scale_factor()
+}
+
+/// Returns the inner size of the active window in logical pixels.
+///
+/// # Panics
+///
+/// Panics if no active window is available.
+pub fn logical_size() -> glam::Vec2 {
+    let size = window().expect("No active window!").inner_size();
+    glam::vec2(size.width as f32, size.height as f32) / scale_factor() as f32
+}
+
+/// Sets the opacity of the canvas of the active window (web only).
+///
+/// Sets the CSS `opacity` of the canvas, which fades everything drawn into
+/// it and suits fade in/out effects driven from the update loop. Native
+/// platforms offer no window opacity through winit, so elsewhere this logs a
+/// warning and does nothing.
+///
+/// # Arguments
+///
+/// * `opacity` - Opacity from `0.0` (invisible) to `1.0` (opaque), clamped
+///
+/// # Returns
+///
+/// Returns `true` if the opacity was applied.
+///
+/// # Panics
+///
+/// Panics if no active window is available.
+pub fn set_opacity(opacity: f32) -> bool {
+    let window = window().expect("No active window!");
+    let opacity = opacity.clamp(0.0, 1.0);
+    #[cfg(target_arch = "wasm32")]
+    {
+        use winit::platform::web::WindowExtWebSys;
+        if let Some(canvas) = window.canvas() {
+            return canvas
+                .style()
+                .set_property("opacity", &opacity.to_string())
+                .is_ok();
+        }
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    let _ = (window, opacity);
+    log::warn!("Window opacity is not supported on this platform");
+    false
+}
+
+/// Information about a monitor available for fullscreen display.
+#[derive(Debug, Clone)]
+pub struct MonitorInfo {
+    /// Human-readable monitor name, if the platform provides one
+    pub name: Option<String>,
+    /// Handle to the monitor
+    pub handle: MonitorHandle,
+    /// Video modes supported by the monitor for exclusive fullscreen
+    pub video_modes: Vec<VideoModeHandle>,
+}
+
+/// Lists the monitors available to the active window.
+///
+/// The position of a monitor in the returned list is its index for
+/// [`set_exclusive_fullscreen`].
+///
+/// # Panics
+///
+/// Panics if no active window is available.
+pub fn available_monitors() -> Vec<MonitorInfo> {
+    window()
+        .expect("No active window!")
+        .available_monitors()
+        .map(|handle| MonitorInfo {
+            name: handle.name(),
+            video_modes: handle.video_modes().collect(),
+            handle,
+        })
+        .collect()
+}
+
+/// Switches the active window to exclusive fullscreen on a specific monitor.
+///
+/// # Arguments
+///
+/// * `monitor_index` - Index of the monitor in [`available_monitors`]
+/// * `video_mode` - Index of the video mode in that monitor's `video_modes`
+///
+/// # Panics
+///
+/// Panics if no active window is available or either index is out of range.
+pub fn set_exclusive_fullscreen(monitor_index: usize, video_mode: usize) {
+    let window = window().expect("No active window!");
+    let monitor = window
+        .available_monitors()
+        .nth(monitor_index)
+        .expect("Invalid monitor index!");
+    let video_mode = monitor
+        .video_modes()
+        .nth(video_mode)
+        .expect("Invalid video mode index!");
+    window.set_fullscreen(Some(Fullscreen::Exclusive(video_mode)));
+}
+
 /// Sets the fullscreen mode of the active window.
 ///
 /// # Arguments
@@ -59,3 +175,109 @@ pub fn is_fullscreen() -> bool {
 pub fn toggle_fullscreen() {
     set_fullscreen(!is_fullscreen());
 }
+
+/// Sets the title of the active window.
+///
+/// Does nothing if no window has been created yet. On the web this sets the
+/// title of the document.
+pub fn set_title(title: &str) {
+    match window() {
+        Some(window) => window.set_title(title),
+        None => log::warn!("Unable to set the title, no active window"),
+    }
+}
+
+/// Shows or hides the cursor while it is over the active window.
+///
+/// Does nothing if no window has been created yet.
+pub fn set_cursor_visible(visible: bool) {
+    match window() {
+        Some(window) => window.set_cursor_visible(visible),
+        None => log::warn!("Unable to change the cursor, no active window"),
+    }
+}
+
+/// Confines or locks the cursor to the active window, e.g. for mouse look.
+///
+/// Platforms support only one of `Confined` and `Locked`: the web and macOS
+/// can only lock, Windows and X11 can only confine. If `mode` is not
+/// supported the other one is tried, since both keep the cursor in the
+/// window. Use `MouseMoveData::delta` for movement while locked. On the web
+/// locking only succeeds in response to a user gesture such as a click.
+///
+/// # Returns
+///
+/// Returns the mode that was applied, or `None` if grabbing failed or no
+/// window has been created yet.
+pub fn set_cursor_grab(mode: CursorGrabMode) -> Option<CursorGrabMode> {
+    let window = window()?;
+    let fallback = match mode {
+        CursorGrabMode::None => None,
+        CursorGrabMode::Confined => Some(CursorGrabMode::Locked),
+        CursorGrabMode::Locked => Some(CursorGrabMode::Confined),
+    };
+    let result = window.set_cursor_grab(mode).map(|_| mode).or_else(|e| {
+        let fallback = fallback.ok_or(e)?;
+        window.set_cursor_grab(fallback).map(|_| fallback)
+    });
+    match result {
+        Ok(mode) => Some(mode),
+        Err(e) => {
+            log::warn!("Unable to grab the cursor: {e}");
+            None
+        }
+    }
+}
+
+/// Enables or disables composing text with an input method (IME).
+///
+/// While allowed, IME compositions for languages such as Chinese or
+/// Japanese are published on `EVENTS.text_input()`. Keep it disabled unless
+/// a text field has focus, since an active IME may swallow key presses
+/// meant for other controls. Does nothing if no window has been created yet.
+pub fn set_ime_allowed(allowed: bool) {
+    match window() {
+        Some(window) => window.set_ime_allowed(allowed),
+        None => log::warn!("Unable to change IME input, no active window"),
+    }
+}
+
+/// Tells the IME where the text being edited is, in physical pixels.
+///
+/// The IME places its candidate window next to this area, e.g. below the
+/// caret of the focused text field, without covering it.
+pub fn set_ime_cursor_area(position: glam::Vec2, size: glam::Vec2) {
+    use winit::dpi::{PhysicalPosition, PhysicalSize};
+
+    match window() {
+        Some(window) => window.set_ime_cursor_area(
+            PhysicalPosition::new(position.x, position.y),
+            PhysicalSize::new(size.x, size.y),
+        ),
+        None => log::warn!("Unable to move the IME cursor area, no active window"),
+    }
+}
+
+/// Returns the text on the system clipboard.
+///
+/// On the web reading the clipboard is asynchronous and needs the user's
+/// permission, so instead this returns the text of the last paste into the
+/// page, e.g. with Ctrl+V, or the text last passed to [`set_clipboard`].
+/// Text copied elsewhere is only seen once it has been pasted. The clipboard
+/// is not supported on Android and iOS.
+///
+/// # Returns
+///
+/// Returns `None` if there is no text on the clipboard or it is unavailable.
+pub fn get_clipboard() -> Option<String> {
+    clipboard::read()
+}
+
+/// Puts text on the system clipboard.
+///
+/// On the web the write completes asynchronously and browsers may reject it
+/// unless called in response to a user gesture such as a key press; failures
+/// are not reported. [`get_clipboard`] returns the text either way.
+pub fn set_clipboard(text: &str) {
+    clipboard::write(text);
+}
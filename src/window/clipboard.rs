@@ -0,0 +1,106 @@
+//! Platform backends of the clipboard functions
+
+#[cfg(not(any(target_arch = "wasm32", target_os = "android", target_os = "ios")))]
+mod native {
+    use parking_lot::Mutex;
+
+    /// Kept alive, since on X11 and Wayland the contents we set are lost once
+    /// the clipboard is dropped
+    static CLIPBOARD: Mutex<Option<arboard::Clipboard>> = Mutex::new(None);
+
+    fn with_clipboard<R>(f: impl FnOnce(&mut arboard::Clipboard) -> R) -> Option<R> {
+        let mut clipboard = CLIPBOARD.lock();
+        if clipboard.is_none() {
+            match arboard::Clipboard::new() {
+                Ok(new) => *clipboard = Some(new),
+                Err(e) => {
+                    log::warn!("Unable to access the clipboard: {e}");
+                    return None;
+                }
+            }
+        }
+        clipboard.as_mut().map(f)
+    }
+
+    pub(crate) fn read() -> Option<String> {
+        match with_clipboard(|c| c.get_text())? {
+            Ok(text) => Some(text),
+            Err(arboard::Error::ContentNotAvailable) => None,
+            Err(e) => {
+                log::warn!("Unable to read the clipboard: {e}");
+                None
+            }
+        }
+    }
+
+    pub(crate) fn write(text: &str) {
+        if let Some(Err(e)) = with_clipboard(|c| c.set_text(text)) {
+            log::warn!("Unable to write the clipboard: {e}");
+        }
+    }
+}
+#[cfg(not(any(target_arch = "wasm32", target_os = "android", target_os = "ios")))]
+pub(crate) use native::*;
+
+#[cfg(target_arch = "wasm32")]
+mod web {
+    use std::cell::RefCell;
+
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen::prelude::*;
+
+    thread_local! {
+        /// Text of the last paste event or `write`
+        static LAST_TEXT: RefCell<Option<String>> = const { RefCell::new(None) };
+    }
+
+    /// Starts caching the text of paste events on the document
+    pub(crate) fn init() {
+        let Some(document) = web_sys::window().and_then(|w| w.document()) else {
+            return;
+        };
+        let on_paste =
+            Closure::<dyn FnMut(web_sys::ClipboardEvent)>::new(|event: web_sys::ClipboardEvent| {
+                let text = event
+                    .clipboard_data()
+                    .and_then(|data| data.get_data("text/plain").ok());
+                if let Some(text) = text {
+                    LAST_TEXT.set(Some(text));
+                }
+            });
+        if document
+            .add_event_listener_with_callback("paste", on_paste.as_ref().unchecked_ref())
+            .is_err()
+        {
+            log::warn!("Unable to listen for paste events");
+        }
+        // The listener lives as long as the page
+        on_paste.forget();
+    }
+
+    pub(crate) fn read() -> Option<String> {
+        LAST_TEXT.with_borrow(Clone::clone)
+    }
+
+    pub(crate) fn write(text: &str) {
+        LAST_TEXT.set(Some(text.to_owned()));
+        if let Some(window) = web_sys::window() {
+            // Resolves once written, or rejects without a user gesture
+            let _ = window.navigator().clipboard().write_text(text);
+        }
+    }
+}
+#[cfg(target_arch = "wasm32")]
+pub(crate) use web::*;
+
+#[cfg(any(target_os = "android", target_os = "ios"))]
+pub(crate) fn read() -> Option<String> {
+    log::warn!("The clipboard is not supported on this platform");
+    None
+}
+
+#[cfg(any(target_os = "android", target_os = "ios"))]
+pub(crate) fn write(text: &str) {
+    let _ = text;
+    log::warn!("The clipboard is not supported on this platform");
+}
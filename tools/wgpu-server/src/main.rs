@@ -1,25 +1,51 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use actix_files as fs;
-use actix_web::{App, HttpServer, web};
+use actix_web::{App, Either, HttpResponse, HttpServer, web};
 use clap::Parser;
 
+/// The page served at `/`
+#[derive(Clone)]
+enum Index {
+    /// An HTML document on disk
+    File(PathBuf),
+    /// A page generated from the contents of the pkg directory
+    Generated(String),
+}
+
 #[derive(Clone)]
 struct AppData {
-    idx: PathBuf,
+    idx: Index,
 }
 
 #[actix_web::get("/")]
-async fn index(data: web::Data<AppData>) -> actix_web::Result<fs::NamedFile> {
-    Ok(fs::NamedFile::open(&data.idx)?)
+async fn index(data: web::Data<AppData>) -> actix_web::Result<Either<fs::NamedFile, HttpResponse>> {
+    Ok(match &data.idx {
+        Index::File(path) => Either::Left(fs::NamedFile::open(path)?),
+        Index::Generated(html) => Either::Right(
+            HttpResponse::Ok()
+                .content_type("text/html; charset=utf-8")
+                .body(html.clone()),
+        ),
+    })
 }
 
 #[derive(Parser, Clone)]
 #[command(version, about, long_about = None)]
 struct Cli {
     /// Path to the index HTML document
-    #[arg(long = "index")]
-    idx: PathBuf,
+    #[arg(long = "index", required_unless_present = "auto_index")]
+    idx: Option<PathBuf>,
+
+    /// Generate an index page loading the JS glue from the pkg directory
+    #[arg(long, conflicts_with = "idx")]
+    auto_index: bool,
+
+    /// Name of the crate whose JS glue the generated index loads
+    ///
+    /// Defaults to the only JS entry in the pkg directory.
+    #[arg(long, requires = "auto_index")]
+    crate_name: Option<String>,
 
     /// Path to the folder containing the assets
     #[arg(long)]
@@ -34,18 +60,91 @@ struct Cli {
     port: u16,
 }
 
+/// Finds the JS entry file generated by wasm-bindgen in `pkg`
+///
+/// With a crate name this is `<crate_name>.js` with dashes replaced by
+/// underscores, otherwise the only `.js` file that isn't a `_bg.js` helper.
+fn find_js_entry(pkg: &Path, crate_name: Option<&str>) -> std::io::Result<String> {
+    use std::io::{Error, ErrorKind};
+
+    if let Some(name) = crate_name {
+        let file = format!("{}.js", name.replace('-', "_"));
+        if !pkg.join(&file).is_file() {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("No {file} in {}", pkg.display()),
+            ));
+        }
+        return Ok(file);
+    }
+    let mut entries = Vec::new();
+    for entry in std::fs::read_dir(pkg)? {
+        let name = entry?.file_name().to_string_lossy().into_owned();
+        if name.ends_with(".js") && !name.ends_with("_bg.js") {
+            entries.push(name);
+        }
+    }
+    match entries.as_slice() {
+        [entry] => Ok(entry.clone()),
+        [] => Err(Error::new(
+            ErrorKind::NotFound,
+            format!("No JS entry in {}", pkg.display()),
+        )),
+        _ => Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "Several JS entries in {}, select one with --crate-name: {}",
+                pkg.display(),
+                entries.join(", ")
+            ),
+        )),
+    }
+}
+
+/// Generates an index page with a `wgpu-canvas` loading `js_entry` from `/pkg`
+fn generate_index(js_entry: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="utf-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1">
+    <title>wgpu-engine</title>
+    <style>
+        html, body {{ margin: 0; height: 100%; background: black; overflow: hidden; }}
+        #wgpu-canvas {{ display: block; width: 100%; height: 100%; }}
+    </style>
+</head>
+<body>
+    <canvas id="wgpu-canvas"></canvas>
+    <script type="module">
+        import init from "/pkg/{js_entry}";
+        init();
+    </script>
+</body>
+</html>
+"#
+    )
+}
+
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     let cli = Cli::parse();
     let port = cli.port;
+    let idx = match cli.idx.clone() {
+        Some(path) => Index::File(path),
+        None => {
+            let js_entry = find_js_entry(&cli.pkg, cli.crate_name.as_deref())?;
+            println!("Serving generated index for /pkg/{js_entry}");
+            Index::Generated(generate_index(&js_entry))
+        }
+    };
     println!("Starting server at 127.0.0.1:{port}");
     HttpServer::new(move || {
         let app = App::new() //
             .service(index)
             .service(fs::Files::new("/pkg", cli.pkg.clone()))
-            .app_data(web::Data::new(AppData {
-                idx: cli.idx.clone(),
-            }));
+            .app_data(web::Data::new(AppData { idx: idx.clone() }));
         if let Some(assets) = cli.assets.clone() {
             app.service(fs::Files::new("/assets", assets))
         } else {